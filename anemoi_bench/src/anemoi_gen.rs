@@ -2,8 +2,10 @@
 //derive the round constants from the given $\pi_0/\pi_1$ and $\beta/\delta$, and wrap them into a parameter structure to return.
 //Currently all matrices are not circulant and using cauchy matrices.
 
-use binius_field::BinaryField;
-use std::collections::HashMap;
+use binius_field::{
+    util::{is_mds, pow_const, pow_windowed, PowWindowTable},
+    BinaryField, BinaryField128b, BinaryField32b, BinaryField64b, PackedField,
+};
 use std::cmp::min;
 use std::fmt::Debug;
 
@@ -14,6 +16,13 @@ pub trait FieldOps:
     fn mul(self, rhs: Self) -> Self;
     fn safe_square(self) -> Self;
     fn from_u8(v: u8) -> Self;
+    // Both exponentiations forward to `binius_field::util`, shared with the circuit Anemoi.
+    #[allow(dead_code)]
+    fn pow_const(self, exp: u128) -> Self;
+    #[allow(dead_code)]
+    fn pow_windowed(self, table: &PowWindowTable) -> Self;
+    #[allow(dead_code)]
+    fn safe_invert(self) -> Option<Self>;
     fn pow_alpha(self) -> Self {
         let x2 = self.safe_square();
         let x4 = x2.safe_square();
@@ -24,10 +33,62 @@ pub trait FieldOps:
 pub trait FieldConst: FieldOps {
     type Raw: Copy;
     fn from_raw(v: Self::Raw) -> Self;
+    /// Inverse of `from_raw`, for printing states as integers in test vectors.
+    #[allow(dead_code)]
+    fn to_raw(self) -> Self::Raw;
 }
 
+macro_rules! impl_field_ops {
+    ($ty:ty, $raw:ty) => {
+        impl FieldOps for $ty {
+            #[inline(always)]
+            fn add(self, rhs: Self) -> Self { self + rhs }
+            #[inline(always)]
+            fn mul(self, rhs: Self) -> Self { self * rhs }
+            #[inline(always)]
+            fn safe_square(self) -> Self { self.square() }
+            #[inline(always)]
+            fn from_u8(v: u8) -> Self { Self::from(v as $raw) }
+            #[inline(always)]
+            fn pow_const(self, exp: u128) -> Self { pow_const(self, exp) }
+            #[inline(always)]
+            fn pow_windowed(self, table: &PowWindowTable) -> Self { pow_windowed(self, table) }
+            #[inline(always)]
+            fn safe_invert(self) -> Option<Self> { binius_field::Field::invert(&self) }
+        }
+        impl FieldConst for $ty {
+            type Raw = $raw;
+            #[inline(always)]
+            fn from_raw(v: Self::Raw) -> Self { Self::from(v) }
+            #[inline(always)]
+            fn to_raw(self) -> Self::Raw {
+                binius_field::underlier::WithUnderlier::to_underlier(self)
+            }
+        }
+    };
+}
+
+impl_field_ops!(BinaryField32b, u32);
+impl_field_ops!(BinaryField64b, u64);
+impl_field_ops!(BinaryField128b, u128);
+
 pub const ANEMOI_ALPHA: u32 = 7;
 
+/// `alpha^{-1} mod (2^n_bits - 1)`, the exponent of the inverse S-box power map over GF(2^n_bits).
+/// `None` if `alpha` is not invertible modulo the multiplicative group order.
+#[allow(dead_code)]
+pub fn compute_alpha_inv(alpha: u32, n_bits: usize) -> Option<u128> {
+    assert!((1..=128).contains(&n_bits), "n_bits must be in 1..=128");
+    let order = u128::MAX >> (128 - n_bits);
+    let alpha = alpha as u128;
+    // Find k < alpha with alpha | k * order + 1; then the inverse is (k * order + 1) / alpha.
+    // With order = q * alpha + r that is k * q + (k * r + 1) / alpha, which cannot overflow.
+    let (q, r) = (order / alpha, order % alpha);
+    (0..alpha)
+        .find(|k| (k * r + 1) % alpha == 0)
+        .map(|k| k * q + (k * r + 1) / alpha)
+}
+
 
 #[allow(dead_code)]
 pub fn estimate_rounds(alpha: u32, l: usize, security: usize) -> usize {
@@ -54,90 +115,83 @@ pub fn estimate_rounds(alpha: u32, l: usize, security: usize) -> usize {
     r.max(8)
 }
 
-#[allow(dead_code)]
-fn combinations(n: usize, k: usize, start: usize, cur: &mut Vec<usize>, f: &mut impl FnMut(&[usize])) {
-    if cur.len() == k {
-        f(cur);
-        return;
-    }
-    for i in start..n {
-        cur.push(i);
-        combinations(n, k, i + 1, cur, f);
-        cur.pop();
-    }
-}
+//Invert a square matrix by Gauss-Jordan elimination; returns None if it is singular.
 
 #[allow(dead_code)]
-#[inline(always)]
-fn mask_from_indices(indices: &[usize]) -> u16 {
-    let mut mask = 0u16;
-    for &i in indices {
-        mask |= 1u16 << i;
+pub fn invert_matrix<F: FieldOps>(m: &[Vec<F>]) -> Option<Vec<Vec<F>>> {
+    let n = m.len();
+    debug_assert!(m.iter().all(|row| row.len() == n));
+
+    let mut a: Vec<Vec<F>> = m.to_vec();
+    let mut inv = vec![vec![F::default(); n]; n];
+    for (i, row) in inv.iter_mut().enumerate() {
+        row[i] = F::from_u8(1);
     }
-    mask
-}
 
-#[allow(dead_code)]
-fn is_mds<F: FieldOps>(m: &[Vec<F>]) -> bool {
-    let n = m.len();
-    debug_assert!(n > 0 && m.iter().all(|row| row.len() == n));
+    for col in 0..n {
+        let pivot = (col..n).find(|&r| a[r][col] != F::default())?;
+        a.swap(col, pivot);
+        inv.swap(col, pivot);
 
-    for row in m {
-        for &v in row {
-            if v == F::default() {
-                return false;
-            }
+        let scale = a[col][col].safe_invert()?;
+        for v in a[col].iter_mut() {
+            *v = v.mul(scale);
+        }
+        for v in inv[col].iter_mut() {
+            *v = v.mul(scale);
         }
-    }
 
-    let mut det_cache = HashMap::<(u16, u16), F>::new();
-    for r in 0..n {
-        for c in 0..n {
-            det_cache.insert(((1u16 << r), (1u16 << c)), m[r][c]);
+        for r in 0..n {
+            let factor = a[r][col];
+            if r == col || factor == F::default() {
+                continue;
+            }
+            // Characteristic 2: subtracting a multiple of the pivot row is adding it.
+            for c in 0..n {
+                let (a_pivot, inv_pivot) = (a[col][c], inv[col][c]);
+                a[r][c] = a[r][c].add(factor.mul(a_pivot));
+                inv[r][c] = inv[r][c].add(factor.mul(inv_pivot));
+            }
         }
     }
 
-    let mut rows = Vec::new();
-    let mut cols = Vec::new();
-
-    for size in 2..=n {
-        let mut new_det_cache = HashMap::<(u16, u16), F>::new();
-        let mut ok = true;
-
-        combinations(n, size, 0, &mut rows, &mut |row_idx| {
-            combinations(n, size, 0, &mut cols, &mut |col_idx| {
-                let row_mask_tail = mask_from_indices(&row_idx[1..]);
-                let col_mask_full = mask_from_indices(col_idx);
-                let mut det = F::default();
-                let i = row_idx[0];
-                for &c in col_idx {
-                    let cofactor_key = (row_mask_tail, col_mask_full & !(1u16 << c));
-                    let cofactor = det_cache
-                        .get(&cofactor_key)
-                        .expect("cofactor should exist for smaller minors");
-                    det = det.add(m[i][c].mul(*cofactor));
-                }
-                if det == F::default() {
-                    ok = false;
-                    return;
-                }
-                new_det_cache.insert((mask_from_indices(row_idx), col_mask_full), det);
-            });
-        });
+    Some(inv)
+}
 
-        if !ok || new_det_cache.is_empty() {
-            return false;
+/// Largest coefficient tried by the circulant MDS search by default (l=6 needs 10, l=8 needs 16).
+pub const DEFAULT_CIRCULANT_LIMIT: usize = 16;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GenError {
+    /// No circulant MDS matrix with coefficients in `1..=max_limit` exists for this `l`.
+    NoMdsFound { l: usize, max_limit: usize },
+    /// A caller-supplied matrix is not `l x l` or not MDS.
+    NotMds { l: usize },
+}
+
+impl std::fmt::Display for GenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GenError::NoMdsFound { l, max_limit } => write!(
+                f,
+                "no circulant MDS matrix found for l={l} with coefficients up to {max_limit}"
+            ),
+            GenError::NotMds { l } => write!(f, "supplied matrix is not a {l}x{l} MDS matrix"),
         }
-        det_cache = new_det_cache;
     }
-
-    true
 }
 
+impl std::error::Error for GenError {}
+
 //Search for a circulant MDS matrix, used in the ``large-state'' setting (eprint 2022/840, Appendix~C, $l>4$). 
+//The coefficient bound grows from l+1 up to `max_limit` (at most 255, since coefficients are taken as small integers).
 
 #[allow(dead_code)]
-fn build_circulant_mds<F: FieldOps + BinaryField>(l: usize) -> Vec<Vec<F>> {
+fn build_circulant_mds<F: FieldOps + BinaryField>(
+    l: usize,
+    max_limit: usize,
+) -> Result<Vec<Vec<F>>, GenError> {
+    assert!(max_limit <= u8::MAX as usize, "circulant coefficients must fit in a u8");
    
     fn gen_vectors(
         l: usize,
@@ -159,7 +213,7 @@ fn build_circulant_mds<F: FieldOps + BinaryField>(l: usize) -> Vec<Vec<F>> {
 
     let mut limit = l + 1;
     let mut cur = Vec::with_capacity(l);
-    loop {
+    while limit <= max_limit {
         let mut found: Option<Vec<Vec<F>>> = None;
         gen_vectors(l, limit, 1, &mut cur, &mut |coeffs| {
             if found.is_some() {
@@ -177,17 +231,21 @@ fn build_circulant_mds<F: FieldOps + BinaryField>(l: usize) -> Vec<Vec<F>> {
             }
         });
         if let Some(m) = found {
-            return m;
+            return Ok(m);
         }
         limit += 1;
     }
+    Err(GenError::NoMdsFound { l, max_limit })
 }
 
 
 #[allow(dead_code)]
-pub fn build_mds_big<F: FieldOps + BinaryField>(l: usize) -> Vec<Vec<F>> {
+pub fn build_mds_big<F: FieldOps + BinaryField>(
+    l: usize,
+    max_limit: usize,
+) -> Result<Vec<Vec<F>>, GenError> {
     assert!(l > 4, "build_mds_big is intended for l>4 only");
-    build_circulant_mds::<F>(l)
+    build_circulant_mds::<F>(l, max_limit)
 }
 
 #[allow(dead_code)]
@@ -314,9 +372,42 @@ pub fn compute_params<F: FieldOps + FieldConst + BinaryField>(
     t: usize,
     pi0: F::Raw,
     pi1: F::Raw,
-) -> ComputedParams<F> {
+) -> Result<ComputedParams<F>, GenError> {
     assert_eq!(t % 2, 0, "Anemoi expects even state size (2l)");
     let l = t / 2;
+    let mds = if let Some(precomputed) = build_mds_small::<F>(l) {
+        precomputed
+    } else if l > 4 {
+        build_mds_big::<F>(l, DEFAULT_CIRCULANT_LIMIT)?
+    } else {
+        panic!("unsupported (t={t}, l={l}) for this field type");
+    };
+    Ok(params_from_mds(l, pi0, pi1, mds))
+}
+
+/// `compute_params` with a pinned `l x l` MDS matrix instead of the search, e.g. one taken from a
+/// published spec. Only the round constants are derived; `mds` is checked with `is_mds`.
+#[allow(dead_code)]
+pub fn compute_params_with_mds<F: FieldOps + FieldConst + BinaryField>(
+    t: usize,
+    pi0: F::Raw,
+    pi1: F::Raw,
+    mds: Vec<Vec<F>>,
+) -> Result<ComputedParams<F>, GenError> {
+    assert_eq!(t % 2, 0, "Anemoi expects even state size (2l)");
+    let l = t / 2;
+    if mds.len() != l || mds.iter().any(|row| row.len() != l) || !is_mds(&mds) {
+        return Err(GenError::NotMds { l });
+    }
+    Ok(params_from_mds(l, pi0, pi1, mds))
+}
+
+fn params_from_mds<F: FieldOps + FieldConst + BinaryField>(
+    l: usize,
+    pi0: F::Raw,
+    pi1: F::Raw,
+    mds: Vec<Vec<F>>,
+) -> ComputedParams<F> {
     let rounds = match l {
         2 => 13,
         3 => 12,
@@ -327,13 +418,6 @@ pub fn compute_params<F: FieldOps + FieldConst + BinaryField>(
     };
     let beta = F::MULTIPLICATIVE_GENERATOR;
     let delta = beta.invert().expect("generator is non-zero");
-    let mds = if let Some(precomputed) = build_mds_small::<F>(l) {
-        precomputed
-    } else if l > 4 {
-        build_mds_big::<F>(l)
-    } else {
-        panic!("unsupported (t={}, l={}) for this field type", t, l);
-    };
     let (c, d) = build_constants::<F>(l, rounds, pi0, pi1, beta, delta);
 
     ComputedParams {
@@ -345,5 +429,167 @@ pub fn compute_params<F: FieldOps + FieldConst + BinaryField>(
         c,
         d,
     }
-    
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use binius_field::BinaryField8b;
+
+    impl_field_ops!(BinaryField8b, u8);
+
+    // Determinant by Gaussian elimination (characteristic 2, so row swaps keep the sign).
+    fn det<F: FieldOps>(m: &[Vec<F>]) -> F {
+        let n = m.len();
+        let mut a = m.to_vec();
+        let mut acc = F::from_u8(1);
+        for col in 0..n {
+            let Some(pivot) = (col..n).find(|&r| a[r][col] != F::default()) else {
+                return F::default();
+            };
+            a.swap(col, pivot);
+            acc = acc.mul(a[col][col]);
+            let inv = a[col][col].safe_invert().unwrap();
+            let (top, bottom) = a.split_at_mut(col + 1);
+            for row in bottom {
+                let factor = row[col].mul(inv);
+                for (v, &p) in row[col..].iter_mut().zip(&top[col][col..]) {
+                    *v = v.add(factor.mul(p));
+                }
+            }
+        }
+        acc
+    }
+
+    // `b` is the search parameter the sub-matrix was built from; it can be read back from the
+    // entry whose column vector only passes through a single `b` multiplication.
+    fn check_mds_small<F: FieldOps + BinaryField>() {
+        let one = F::from_u8(1);
+        for l in 2..=4 {
+            let m = build_mds_small::<F>(l).expect("small MDS exists");
+            assert_eq!(m.len(), l);
+            assert!(is_mds(&m), "l={l}");
+
+            let inv = invert_matrix(&m).expect("MDS matrix is invertible");
+            for (r, row) in m.iter().enumerate() {
+                for c in 0..l {
+                    let v = row.iter().zip(&inv).fold(F::default(), |acc, (&x, inv_row)| {
+                        FieldOps::add(acc, FieldOps::mul(x, inv_row[c]))
+                    });
+                    assert_eq!(v, if r == c { one } else { F::default() });
+                }
+            }
+
+            let expected_det = match l {
+                2 => one,
+                3 => FieldOps::add(one, m[2][0].safe_square()),
+                4 => FieldOps::add(m[3][0], one),
+                _ => unreachable!(),
+            };
+            assert_eq!(det(&m), expected_det, "l={l}");
+        }
+    }
+
+    fn check_mds_big<F: FieldOps + BinaryField>(l: usize) {
+        let m = build_mds_big::<F>(l, DEFAULT_CIRCULANT_LIMIT).expect("circulant MDS exists");
+        assert_eq!(m.len(), l);
+        assert!(is_mds(&m), "l={l}");
+        assert!(invert_matrix(&m).is_some(), "l={l}");
+    }
+
+    #[test]
+    fn test_build_mds_small() {
+        check_mds_small::<BinaryField32b>();
+        check_mds_small::<BinaryField64b>();
+        check_mds_small::<BinaryField128b>();
+    }
+
+    fn check_raw_round_trip<F: FieldConst>(raws: &[F::Raw])
+    where
+        F::Raw: PartialEq + Debug,
+    {
+        for &raw in raws {
+            assert_eq!(F::from_raw(raw).to_raw(), raw);
+            let x = F::from_raw(raw);
+            assert_eq!(F::from_raw(x.to_raw()), x);
+        }
+    }
+
+    #[test]
+    fn test_raw_round_trip() {
+        check_raw_round_trip::<BinaryField8b>(&[0, 1, 0x53, u8::MAX]);
+        check_raw_round_trip::<BinaryField32b>(&[0, 1, 0xb559_eff7, u32::MAX]);
+        check_raw_round_trip::<BinaryField64b>(&[0, 1, 0x944c_e62e_b559_eff7, u64::MAX]);
+        check_raw_round_trip::<BinaryField128b>(&[
+            0,
+            1,
+            0x7464_2e34_fa54_06ba_944c_e62e_b559_eff7,
+            u128::MAX,
+        ]);
+    }
+
+    #[test]
+    fn test_build_mds_big() {
+        check_mds_big::<BinaryField64b>(6);
+    }
+
+    #[test]
+    fn test_circulant_search_bound() {
+        // Over GF(2^8) there is no 6x6 circulant MDS matrix with coefficients in 1..=8.
+        assert_eq!(
+            build_mds_big::<BinaryField8b>(6, 8),
+            Err(GenError::NoMdsFound { l: 6, max_limit: 8 })
+        );
+        // A bound below the starting point l+1 gives up without searching.
+        assert_eq!(
+            build_mds_big::<BinaryField64b>(6, 6),
+            Err(GenError::NoMdsFound { l: 6, max_limit: 6 })
+        );
+    }
+
+    #[test]
+    fn test_compute_alpha_inv() {
+        for n_bits in [8, 32, 64, 128] {
+            let order = u128::MAX >> (128 - n_bits);
+            let inv = compute_alpha_inv(ANEMOI_ALPHA, n_bits).unwrap();
+            assert!(inv < order);
+            // 7 * inv == 1 mod order, checked without overflowing u128.
+            let sum = (0..ANEMOI_ALPHA).fold(0u128, |acc, _| {
+                let (v, carry) = acc.overflowing_add(inv);
+                if carry || v >= order { v.wrapping_sub(order) } else { v }
+            });
+            assert_eq!(sum, 1, "n_bits={n_bits}");
+        }
+        // 2^6 - 1 = 63 is a multiple of 7.
+        assert_eq!(compute_alpha_inv(ANEMOI_ALPHA, 6), None);
+    }
+
+    #[test]
+    fn test_compute_params_with_mds() {
+        let searched = compute_params::<BinaryField64b>(8, 3, 5).unwrap();
+        let pinned =
+            compute_params_with_mds::<BinaryField64b>(8, 3, 5, searched.mds.clone()).unwrap();
+        assert_eq!((pinned.l, pinned.rounds), (searched.l, searched.rounds));
+        assert_eq!(pinned.mds, searched.mds);
+        assert_eq!((pinned.c, pinned.d), (searched.c, searched.d));
+
+        // The all-ones matrix has singular 2x2 minors.
+        let ones = vec![vec![BinaryField64b::from_u8(1); 4]; 4];
+        assert_eq!(
+            compute_params_with_mds::<BinaryField64b>(8, 3, 5, ones).err(),
+            Some(GenError::NotMds { l: 4 })
+        );
+        let wrong_shape = searched.mds[..3].to_vec();
+        assert_eq!(
+            compute_params_with_mds::<BinaryField64b>(8, 3, 5, wrong_shape).err(),
+            Some(GenError::NotMds { l: 4 })
+        );
+    }
+
+    // The l=8 circulant search takes minutes outside release builds.
+    #[test]
+    #[ignore]
+    fn test_build_mds_big_l8() {
+        check_mds_big::<BinaryField32b>(8);
+    }
 }
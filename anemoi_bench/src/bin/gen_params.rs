@@ -4,38 +4,10 @@
 #[path = "../anemoi_gen.rs"]
 mod anemoi_gen;
 
-use anemoi_gen::{compute_params, FieldConst, FieldOps};
-use binius_field::{
-    BinaryField, BinaryField128b, BinaryField32b, BinaryField64b, PackedField,
-    underlier::WithUnderlier,
-};
+use anemoi_gen::{compute_alpha_inv, compute_params, FieldConst, FieldOps, ANEMOI_ALPHA};
+use binius_field::{BinaryField, BinaryField128b, BinaryField32b, BinaryField64b};
 use std::fmt::Debug;
 
-macro_rules! impl_field_ops {
-    ($ty:ty, $raw:ty) => {
-        impl FieldOps for $ty {
-            #[inline(always)]
-            fn add(self, rhs: Self) -> Self { self + rhs }
-            #[inline(always)]
-            fn mul(self, rhs: Self) -> Self { self * rhs }
-            #[inline(always)]
-            fn safe_square(self) -> Self { self.square() }
-            #[inline(always)]
-            fn from_u8(v: u8) -> Self { Self::from(v as $raw) }
-        }
-        impl FieldConst for $ty {
-            type Raw = $raw;
-            #[inline(always)]
-            fn from_raw(v: Self::Raw) -> Self { Self::from(v) }
-        }
-    };
-}
-
-impl_field_ops!(BinaryField32b, u32);
-impl_field_ops!(BinaryField64b, u64);
-impl_field_ops!(BinaryField128b, u128);
-
-
 const PI0_32: u32 = 0xb559_eff7;
 const PI1_32: u32 = 0x9ac6_c074;
 const PI0_64: u64 = 0x944c_e62e_b559_eff7;
@@ -45,22 +17,33 @@ const PI1_128: u128 = 0x28e9_55fd_ff9b_d7e6_df30_73d3_9ac6_c074;
 
 fn print_params<F>(name: &str, t: usize, pi0: F::Raw, pi1: F::Raw)
 where
-    F: FieldConst + FieldOps + BinaryField + Debug + WithUnderlier,
+    F: FieldConst + FieldOps + BinaryField + Debug,
+    F::Raw: Debug,
 {
-    let p = compute_params::<F>(t, pi0, pi1);
     println!("== {} ==", name);
+    let p = match compute_params::<F>(t, pi0, pi1) {
+        Ok(p) => p,
+        Err(e) => {
+            println!("error: {e}");
+            return;
+        }
+    };
     println!("t={} l={} rounds={}", t, p.l, p.rounds);
+    match compute_alpha_inv(ANEMOI_ALPHA, F::N_BITS) {
+        Some(inv) => println!("alpha_inv=0x{inv:x}"),
+        None => println!("alpha_inv: {} is not invertible mod 2^{}-1", ANEMOI_ALPHA, F::N_BITS),
+    }
     println!("mds:");
     for row in p.mds.iter() {
-        println!("{:?}", row.iter().map(|v| v.to_underlier()).collect::<Vec<_>>());
+        println!("{:?}", row.iter().map(|v| v.to_raw()).collect::<Vec<_>>());
     }
     println!("c:");
     for row in p.c.iter() {
-        println!("{:?}", row.iter().map(|v| v.to_underlier()).collect::<Vec<_>>());
+        println!("{:?}", row.iter().map(|v| v.to_raw()).collect::<Vec<_>>());
     }
     println!("d:");
     for row in p.d.iter() {
-        println!("{:?}", row.iter().map(|v| v.to_underlier()).collect::<Vec<_>>());
+        println!("{:?}", row.iter().map(|v| v.to_raw()).collect::<Vec<_>>());
     }
 }
 
@@ -1,53 +1,15 @@
 mod anemoi_gen;
 mod params;
 
-use anemoi_gen::{FieldConst, FieldOps, ANEMOI_ALPHA};
+use anemoi_gen::{invert_matrix, ComputedParams, FieldConst, FieldOps, ANEMOI_ALPHA};
 use binius_field::{
-    BinaryField, BinaryField128b, BinaryField32b, BinaryField64b, PackedField,
+    util::PowWindowTable, BinaryField, BinaryField128b, BinaryField32b, BinaryField64b,
 };
 use params::{ALPHA_INV_128, ALPHA_INV_32, ALPHA_INV_64};
 use std::time::Instant;
 
 
 
-macro_rules! impl_field_ops {
-    ($ty:ty, $raw:ty) => {
-        impl FieldOps for $ty {
-            #[inline(always)]
-            fn add(self, rhs: Self) -> Self { self + rhs }
-            #[inline(always)]
-            fn mul(self, rhs: Self) -> Self { self * rhs }
-            #[inline(always)]
-            fn safe_square(self) -> Self { self.square() }
-            #[inline(always)]
-            fn from_u8(v: u8) -> Self { Self::from(v as $raw) }
-        }
-        impl FieldConst for $ty {
-            type Raw = $raw;
-            #[inline(always)]
-            fn from_raw(v: Self::Raw) -> Self { Self::from(v) }
-        }
-    };
-}
-
-impl_field_ops!(BinaryField32b, u32);
-impl_field_ops!(BinaryField64b, u64);
-impl_field_ops!(BinaryField128b, u128);
-
-#[inline(always)]
-fn pow_const<F: FieldOps>(mut base: F, mut exp: u128) -> F {
-    let mut acc = F::from_u8(1);
-    while exp > 0 {
-        if exp & 1 == 1 {
-            acc = acc.mul(base);
-        }
-        base = base.safe_square();
-        exp >>= 1;
-    }
-    acc
-}
-
-
 // Parameter preparation 
 
 struct PreparedParams<F: FieldConst> {
@@ -156,34 +118,107 @@ fn params_128_l3() -> PreparedParams<BinaryField128b> {
     )
 }
 
+/// The six shipped parameter sets, for code that loops over all of them. The field differs between
+/// instances, so callers match on the variant to get the typed `params_*` table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Instance {
+    B32L8,
+    B32L12,
+    B64L4,
+    B64L6,
+    B128L2,
+    B128L3,
+}
+
+impl Instance {
+    const ALL: [Instance; 6] = [
+        Instance::B32L8,
+        Instance::B32L12,
+        Instance::B64L4,
+        Instance::B64L6,
+        Instance::B128L2,
+        Instance::B128L3,
+    ];
+
+    fn all() -> impl ExactSizeIterator<Item = Instance> {
+        Self::ALL.into_iter()
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Instance::B32L8 => "GF(2^32) t=16",
+            Instance::B32L12 => "GF(2^32) t=24",
+            Instance::B64L4 => "GF(2^64) t=8",
+            Instance::B64L6 => "GF(2^64) t=12",
+            Instance::B128L2 => "GF(2^128) t=4",
+            Instance::B128L3 => "GF(2^128) t=6",
+        }
+    }
+}
+
 // Anemoi permutation
 
 struct AnemoiParams<F: FieldConst> {
     l: usize,
     rounds: usize,
-    alpha_inv: u128,
+    alpha_inv: PowWindowTable,
     beta: F,
     delta: F,
     c: Vec<Vec<F>>,
     d: Vec<Vec<F>>,
     mds: Vec<Vec<F>>,
+    mds_inv: Vec<Vec<F>>,
 }
 
 impl<F: FieldConst> AnemoiParams<F> {
     fn from_prepared(p: PreparedParams<F>) -> Self {
-        Self {
+        let mds_inv = invert_matrix(&p.mds).expect("MDS matrix is invertible");
+        let params = Self {
             l: p.l,
             rounds: p.rounds,
-            alpha_inv: p.alpha_inv,
+            alpha_inv: PowWindowTable::new(p.alpha_inv),
             beta: p.beta,
             delta: p.delta,
             c: p.c,
             d: p.d,
             mds: p.mds,
+            mds_inv,
+        };
+        debug_assert_eq!(params.validate(), Ok(()));
+        params
+    }
+
+    /// Checks that `alpha_inv` really inverts `x -> x^alpha`.
+    ///
+    /// `beta` is the multiplicative generator `g`, so `g^(alpha * alpha_inv) == g` holds exactly
+    /// when `alpha * alpha_inv == 1` modulo the group order.
+    fn validate(&self) -> Result<(), ParamsError> {
+        if self.beta.pow_alpha().pow_windowed(&self.alpha_inv) != self.beta {
+            return Err(ParamsError::AlphaInvNotInverse);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ParamsError {
+    /// `alpha_inv` is not the inverse of `alpha` modulo `2^n - 1`.
+    AlphaInvNotInverse,
+}
+
+impl std::fmt::Display for ParamsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParamsError::AlphaInvNotInverse => {
+                write!(f, "alpha_inv is not the inverse of alpha {}", ANEMOI_ALPHA)
+            }
         }
     }
 }
 
+impl std::error::Error for ParamsError {}
+
+// Immutable after `new` and `Send + Sync`: one instance can be shared across threads.
 struct Anemoi<F: FieldConst> {
     params: AnemoiParams<F>,
 }
@@ -193,6 +228,32 @@ impl<F: FieldConst> Anemoi<F> {
         Self { params }
     }
 
+    /// Builds the permutation straight from `anemoi_gen::compute_params` output, without going
+    /// through the `params` tables. `alpha_inv` is picked from the field size.
+    #[allow(dead_code)]
+    fn from_computed(p: ComputedParams<F>) -> Self
+    where
+        F: BinaryField,
+    {
+        let alpha_inv = match F::N_BITS {
+            32 => ALPHA_INV_32,
+            64 => ALPHA_INV_64,
+            128 => ALPHA_INV_128,
+            n => panic!("no alpha_inv for a {n}-bit field"),
+        };
+        Self::new(AnemoiParams::from_prepared(PreparedParams {
+            t: 2 * p.l,
+            l: p.l,
+            rounds: p.rounds,
+            alpha_inv,
+            beta: p.beta,
+            delta: p.delta,
+            c: p.c,
+            d: p.d,
+            mds: p.mds,
+        }))
+    }
+
     //$$\text{Anemoi} = \mathcal{M} \circ R_{n_r-1} \circ ... \circ R_0$$
     fn permute(&self, state: &mut [F]) {
         debug_assert_eq!(state.len(), 2 * self.params.l);
@@ -220,36 +281,38 @@ impl<F: FieldConst> Anemoi<F> {
         state[l..].copy_from_slice(&y);
     }
 
-    fn linear_layer(&self, x: &mut [F], y: &mut [F]) {
+    // Inverse of `permute`: undoes the final MDS, then each round's S-box, linear layer and constants in reverse.
+    #[allow(dead_code)]
+    fn permute_inverse(&self, state: &mut [F]) {
+        debug_assert_eq!(state.len(), 2 * self.params.l);
+
         let l = self.params.l;
-        let mut new_x = vec![F::default(); l];
-        let mut new_y = vec![F::default(); l];
+        let mut x: Vec<F> = state[..l].to_vec();
+        let mut y: Vec<F> = state[l..].to_vec();
 
-        // M_x(X)
-        for r in 0..l {
-            let mut acc = F::default();
-            for c in 0..l {
-                acc = acc.add(self.params.mds[r][c].mul(x[c]));
-            }
-            new_x[r] = acc;
-        }
-        
-        // M_y(Y) (including rotation rho) 
-        let mut y_rot = vec![F::default(); l];
-        for i in 0..l - 1 {
-            y_rot[i] = y[i + 1];
-        }
-        y_rot[l - 1] = y[0];
+        self.apply_mds_only_inverse(&mut x, &mut y);
 
-        for r in 0..l {
-            let mut acc = F::default();
-            for c in 0..l {
-                acc = acc.add(self.params.mds[r][c].mul(y_rot[c]));
+        for r in (0..self.params.rounds).rev() {
+            for i in 0..l {
+                let (px, py) = self.apply_sbox_inverse(x[i], y[i]);
+                x[i] = px;
+                y[i] = py;
+            }
+            self.linear_layer_inverse(&mut x, &mut y);
+            for i in 0..l {
+                x[i] = x[i].add(self.params.c[r][i]);
+                y[i] = y[i].add(self.params.d[r][i]);
             }
-            new_y[r] = acc;
         }
 
-        for i in 0..l {
+        state[..l].copy_from_slice(&x);
+        state[l..].copy_from_slice(&y);
+    }
+
+    fn linear_layer(&self, x: &mut [F], y: &mut [F]) {
+        let (mut new_x, mut new_y) = self.mds_rho(x, y);
+
+        for i in 0..self.params.l {
             new_y[i] = new_y[i].add(new_x[i]);
             new_x[i] = new_x[i].add(new_y[i]);
         }
@@ -259,6 +322,13 @@ impl<F: FieldConst> Anemoi<F> {
     }
 
     fn apply_mds_only(&self, x: &mut [F], y: &mut [F]) {
+        let (new_x, new_y) = self.mds_rho(x, y);
+        x.copy_from_slice(&new_x);
+        y.copy_from_slice(&new_y);
+    }
+
+    // $(M X, M\rho(Y))$, the part `linear_layer` and `apply_mds_only` share.
+    fn mds_rho(&self, x: &[F], y: &[F]) -> (Vec<F>, Vec<F>) {
         let l = self.params.l;
         let mut new_x = vec![F::default(); l];
         let mut new_y = vec![F::default(); l];
@@ -287,43 +357,129 @@ impl<F: FieldConst> Anemoi<F> {
             new_y[r] = acc;
         }
 
+        (new_x, new_y)
+    }
+
+    // The forward layer leaves $X = M\rho(Y)$ and $Y' = M\rho(Y) + M X$.
+    fn linear_layer_inverse(&self, x: &mut [F], y: &mut [F]) {
+        for i in 0..x.len() {
+            y[i] = y[i].add(x[i]);
+        }
+        self.apply_mds_only_inverse(y, x);
+        x.swap_with_slice(y);
+    }
+
+    fn apply_mds_only_inverse(&self, x: &mut [F], y: &mut [F]) {
+        let l = self.params.l;
+        let mut new_x = vec![F::default(); l];
+        let mut y_rot = vec![F::default(); l];
+
+        for r in 0..l {
+            let mut acc_x = F::default();
+            let mut acc_y = F::default();
+            for c in 0..l {
+                acc_x = acc_x.add(self.params.mds_inv[r][c].mul(x[c]));
+                acc_y = acc_y.add(self.params.mds_inv[r][c].mul(y[c]));
+            }
+            new_x[r] = acc_x;
+            y_rot[r] = acc_y;
+        }
+
+        // undo rho
+        y[0] = y_rot[l - 1];
+        y[1..].copy_from_slice(&y_rot[..l - 1]);
         x.copy_from_slice(&new_x);
-        y.copy_from_slice(&new_y);
+    }
+
+    // beta * y^alpha, the only non-trivial map besides x^(1/alpha); each S-box call evaluates it
+    // on two different inputs (y before and after the update), so there is nothing to share.
+    #[inline(always)]
+    fn flystel_q(&self, y: F) -> F {
+        self.params.beta.mul(y.pow_const(ANEMOI_ALPHA as u128))
     }
 
     fn apply_sbox(&self, x: F, y: F) -> (F, F) {
-        let mut x = x;
-        let mut y = y;
+        //$t = x + g \cdot y^\alpha + \mathbf{g^{-1}}$
+        let t = x.add(self.flystel_q(y)).add(self.params.delta);
+
+        //$y \leftarrow y + t^{1/\alpha}$
+        let y = y.add(t.pow_windowed(&self.params.alpha_inv));
 
-        let y_pow = pow_const(y, ANEMOI_ALPHA as u128);
+        //$x \leftarrow t + g \cdot y^\alpha$
+        (t.add(self.flystel_q(y)), y)
+    }
 
-        //$x \leftarrow x + g \cdot y^\alpha + \mathbf{g^{-1}}$
-        x = x.add(self.params.beta.mul(y_pow))
-             .add(self.params.delta);
+    // The Flystel is its own mirror image: run the three steps backwards with the same maps.
+    fn apply_sbox_inverse(&self, x: F, y: F) -> (F, F) {
+        let t = x.add(self.flystel_q(y));
+        let y = y.add(t.pow_windowed(&self.params.alpha_inv));
+        (t.add(self.flystel_q(y)).add(self.params.delta), y)
+    }
+}
 
-        //$y \leftarrow y + x^{1/\alpha}$
-        let x_alpha_inv = pow_const(x, self.params.alpha_inv);
-        y = y.add(x_alpha_inv);
+// Benchmark
 
-        //$x \leftarrow x + g \cdot y^\alpha$
-        let y_pow_new = pow_const(y, ANEMOI_ALPHA as u128);
-        x = x.add(self.params.beta.mul(y_pow_new));
+// Text is the default; `--output-format json` (or BENCH_OUTPUT_FORMAT=json) prints one JSON array
+// of `{instance, t, ns_per_op, perms_per_sec}` objects instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    Text,
+    Json,
+}
 
-        (x, y)
+impl OutputFormat {
+    fn from_args() -> Self {
+        let args: Vec<String> = std::env::args().collect();
+        let flag = args
+            .iter()
+            .position(|a| a == "--output-format")
+            .and_then(|i| args.get(i + 1).cloned());
+        match flag.or_else(|| std::env::var("BENCH_OUTPUT_FORMAT").ok()).as_deref() {
+            Some("json") => OutputFormat::Json,
+            Some("text") | None => OutputFormat::Text,
+            Some(other) => panic!("unknown output format {:?} (expected text or json)", other),
+        }
     }
 }
 
-// Benchmark
+struct BenchResult {
+    instance: String,
+    t: usize,
+    ns_per_op: f64,
+    perms_per_sec: f64,
+}
 
-fn run_anemoi_bench<F: FieldConst>(title: &str, pre: PreparedParams<F>) {
+fn results_to_json(results: &[BenchResult]) -> String {
+    let rows: Vec<serde_json::Value> = results
+        .iter()
+        .map(|r| {
+            serde_json::json!({
+                "instance": r.instance,
+                "t": r.t,
+                "ns_per_op": r.ns_per_op,
+                "perms_per_sec": r.perms_per_sec,
+            })
+        })
+        .collect();
+    serde_json::to_string_pretty(&rows).expect("bench results serialize")
+}
+
+fn run_anemoi_bench<F: FieldConst>(
+    title: &str,
+    pre: PreparedParams<F>,
+    format: OutputFormat,
+    iterations: Option<usize>,
+) -> BenchResult {
     let t = pre.t;
     let params = AnemoiParams::<F>::from_prepared(pre);
 
-    println!("--------------------------------------------------");
-    println!(
-        "{} | t={} | l={} | rounds={} | alpha={} ",
-        title, t, params.l, params.rounds, ANEMOI_ALPHA
-    );
+    if format == OutputFormat::Text {
+        println!("--------------------------------------------------");
+        println!(
+            "{} | t={} | l={} | rounds={} | alpha={} ",
+            title, t, params.l, params.rounds, ANEMOI_ALPHA
+        );
+    }
 
     let anemoi = Anemoi::new(params);
 
@@ -331,9 +487,7 @@ fn run_anemoi_bench<F: FieldConst>(title: &str, pre: PreparedParams<F>) {
         .map(|i| F::from_u8((i as u8).wrapping_add(1)))
         .collect();
 
-    let base_iter = 100000usize;
-    let scale = (t / 4).max(1);
-    let iterations = (base_iter / scale).max(20_000);
+    let iterations = iterations.unwrap_or(default_iterations(t));
 
     let start = Instant::now();
     for _ in 0..iterations {
@@ -343,19 +497,379 @@ fn run_anemoi_bench<F: FieldConst>(title: &str, pre: PreparedParams<F>) {
     let ns_per_op = elapsed.as_nanos() as f64 / iterations as f64;
     let ops_per_sec = iterations as f64 / elapsed.as_secs_f64();
 
-    println!("Time per perm: {:.2} ns", ns_per_op);
-    println!("Throughput:    {:.2} perms/sec", ops_per_sec);
- 
+    if format == OutputFormat::Text {
+        println!("Time per perm: {:.2} ns", ns_per_op);
+        println!("Throughput:    {:.2} perms/sec", ops_per_sec);
+    }
+
+    BenchResult {
+        instance: title.trim_end().to_string(),
+        t,
+        ns_per_op,
+        perms_per_sec: ops_per_sec,
+    }
+}
+
+// Fewer iterations for wider states, but never below 20,000.
+fn default_iterations(t: usize) -> usize {
+    (100_000 / (t / 4).max(1)).max(20_000)
+}
+
+// `--compare`: Poseidon2b (from `poseidon2b_runner`) and Anemoi on the same field and state size,
+// timed back to back on the same input. Text output only.
+struct CompareRow {
+    field: &'static str,
+    t: usize,
+    poseidon2b_ns: f64,
+    anemoi_ns: f64,
+}
+
+fn ns_per_perm<F>(state: &mut [F], iterations: usize, permute: impl Fn(&mut [F])) -> f64 {
+    let start = Instant::now();
+    for _ in 0..iterations {
+        permute(state);
+    }
+    start.elapsed().as_nanos() as f64 / iterations as f64
+}
+
+fn compare_one<F>(
+    field: &'static str,
+    anemoi: PreparedParams<F>,
+    poseidon2b: poseidon2b_runner::PreparedParams<F>,
+    iterations: Option<usize>,
+) -> CompareRow
+where
+    F: FieldConst + poseidon2b_runner::FieldConst,
+{
+    assert_eq!(anemoi.t, poseidon2b.t, "{} state sizes differ", field);
+    let t = anemoi.t;
+    let iterations = iterations.unwrap_or(default_iterations(t));
+    let anemoi = Anemoi::new(AnemoiParams::from_prepared(anemoi));
+    let poseidon2b = poseidon2b_runner::Poseidon2b::new(poseidon2b);
+
+    let input: Vec<F> = (0..t)
+        .map(|i| <F as FieldOps>::from_u8((i as u8).wrapping_add(1)))
+        .collect();
+    let poseidon2b_ns = ns_per_perm(&mut input.clone(), iterations, |s| poseidon2b.permute(s));
+    let anemoi_ns = ns_per_perm(&mut input.clone(), iterations, |s| anemoi.permute(s));
+    CompareRow {
+        field,
+        t,
+        poseidon2b_ns,
+        anemoi_ns,
+    }
+}
+
+fn run_compare(iterations: Option<usize>) -> Vec<CompareRow> {
+    use poseidon2b_runner as p2b;
+    vec![
+        compare_one::<BinaryField128b>("GF(2^128)", params_128_l2(), p2b::params_128_t4(), iterations),
+        compare_one::<BinaryField128b>("GF(2^128)", params_128_l3(), p2b::params_128_t6(), iterations),
+        compare_one::<BinaryField64b>("GF(2^64)", params_64_l4(), p2b::params_64_t8(), iterations),
+        compare_one::<BinaryField64b>("GF(2^64)", params_64_l6(), p2b::params_64_t12(), iterations),
+        compare_one::<BinaryField32b>("GF(2^32)", params_32_l8(), p2b::params_32_t16(), iterations),
+        compare_one::<BinaryField32b>("GF(2^32)", params_32_l12(), p2b::params_32_t24(), iterations),
+    ]
+}
+
+fn print_compare(rows: &[CompareRow]) {
+    println!(
+        "{:<10} {:>3} {:>15} {:>12} {:>18}",
+        "field", "t", "Poseidon2b ns", "Anemoi ns", "Anemoi/Poseidon2b"
+    );
+    for r in rows {
+        println!(
+            "{:<10} {:>3} {:>15.2} {:>12.2} {:>17.2}x",
+            r.field,
+            r.t,
+            r.poseidon2b_ns,
+            r.anemoi_ns,
+            r.anemoi_ns / r.poseidon2b_ns
+        );
+    }
+}
+
+fn run_all(format: OutputFormat, iterations: Option<usize>) -> Vec<BenchResult> {
+    Instance::all()
+        .map(|instance| {
+            let title = format!("{} (Anemoi)", instance.name());
+            match instance {
+                Instance::B32L8 => run_anemoi_bench(&title, params_32_l8(), format, iterations),
+                Instance::B32L12 => run_anemoi_bench(&title, params_32_l12(), format, iterations),
+                Instance::B64L4 => run_anemoi_bench(&title, params_64_l4(), format, iterations),
+                Instance::B64L6 => run_anemoi_bench(&title, params_64_l6(), format, iterations),
+                Instance::B128L2 => run_anemoi_bench(&title, params_128_l2(), format, iterations),
+                Instance::B128L3 => run_anemoi_bench(&title, params_128_l3(), format, iterations),
+            }
+        })
+        .collect()
 }
 
 fn main() {
-    println!("=== Anemoi Benchmark  ===");
-
-    run_anemoi_bench::<BinaryField32b>("GF(2^32) t=16 (Anemoi)", params_32_l8());
-    run_anemoi_bench::<BinaryField32b>("GF(2^32) t=24 (Anemoi)", params_32_l12());
-    run_anemoi_bench::<BinaryField64b>("GF(2^64) t=8  (Anemoi)", params_64_l4());
-    run_anemoi_bench::<BinaryField64b>("GF(2^64) t=12 (Anemoi)", params_64_l6());
-    run_anemoi_bench::<BinaryField128b>("GF(2^128) t=4 (Anemoi)", params_128_l2());
-    run_anemoi_bench::<BinaryField128b>("GF(2^128) t=6 (Anemoi)", params_128_l3());
-    
+    if std::env::args().any(|a| a == "--compare") {
+        println!("=== Poseidon2b vs Anemoi ===");
+        print_compare(&run_compare(None));
+        return;
+    }
+
+    let format = OutputFormat::from_args();
+    if format == OutputFormat::Text {
+        println!("=== Anemoi Benchmark  ===");
+    }
+
+    let results = run_all(format, None);
+    if format == OutputFormat::Json {
+        println!("{}", results_to_json(&results));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use binius_field::util::is_mds;
+    use proptest::prelude::*;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn permutation_is_send_sync() {
+        assert_send_sync::<Anemoi<BinaryField32b>>();
+        assert_send_sync::<Anemoi<BinaryField64b>>();
+        assert_send_sync::<Anemoi<BinaryField128b>>();
+    }
+
+    #[test]
+    fn json_output_lists_all_instances() {
+        let json = results_to_json(&run_all(OutputFormat::Json, Some(1)));
+        let rows: Vec<serde_json::Value> = serde_json::from_str(&json).unwrap();
+        assert_eq!(rows.len(), 6);
+        for (row, t) in rows.iter().zip([16, 24, 8, 12, 4, 6]) {
+            assert!(row["instance"].as_str().unwrap().contains(&format!("t={}", t)));
+            assert_eq!(row["t"], t);
+            assert!(row["ns_per_op"].is_number());
+            assert!(row["perms_per_sec"].is_number());
+        }
+    }
+
+    #[test]
+    fn compare_covers_matched_sizes() {
+        let rows = run_compare(Some(1));
+        let ts: Vec<usize> = rows.iter().map(|r| r.t).collect();
+        assert_eq!(ts, [4, 6, 8, 12, 16, 24]);
+        for r in &rows {
+            assert!(r.poseidon2b_ns > 0.0 && r.anemoi_ns > 0.0, "{} t={}", r.field, r.t);
+        }
+    }
+
+    // `apply_sbox` as written before the Flystel steps were factored through `flystel_q`.
+    fn apply_sbox_reference<F: FieldConst>(p: &AnemoiParams<F>, x: F, y: F) -> (F, F) {
+        let mut x = x;
+        let mut y = y;
+        let y_pow = y.pow_const(ANEMOI_ALPHA as u128);
+        x = x.add(p.beta.mul(y_pow)).add(p.delta);
+        let x_alpha_inv = x.pow_windowed(&p.alpha_inv);
+        y = y.add(x_alpha_inv);
+        let y_pow_new = y.pow_const(ANEMOI_ALPHA as u128);
+        x = x.add(p.beta.mul(y_pow_new));
+        (x, y)
+    }
+
+    fn check_sbox<F: FieldConst>(pre: PreparedParams<F>, raw: Vec<F::Raw>) {
+        let anemoi = Anemoi::new(AnemoiParams::from_prepared(pre));
+        for pair in raw.chunks_exact(2) {
+            let (x, y) = (F::from_raw(pair[0]), F::from_raw(pair[1]));
+            assert_eq!(anemoi.apply_sbox(x, y), apply_sbox_reference(&anemoi.params, x, y));
+        }
+    }
+
+    fn check_round_trip<F: FieldConst>(pre: PreparedParams<F>, raw: Vec<F::Raw>) {
+        let anemoi = Anemoi::new(AnemoiParams::from_prepared(pre));
+        let input: Vec<F> = raw.into_iter().map(F::from_raw).collect();
+
+        let mut state = input.clone();
+        anemoi.permute(&mut state);
+        anemoi.permute_inverse(&mut state);
+        assert_eq!(state, input);
+
+        anemoi.permute_inverse(&mut state);
+        anemoi.permute(&mut state);
+        assert_eq!(state, input);
+    }
+
+    // `linear_layer` is `apply_mds_only` followed by the cross-mix `y += x; x += y`.
+    fn check_linear_layer<F: FieldConst>(pre: PreparedParams<F>, raw: Vec<F::Raw>) {
+        let anemoi = Anemoi::new(AnemoiParams::from_prepared(pre));
+        let l = anemoi.params.l;
+        let input: Vec<F> = raw.into_iter().map(F::from_raw).collect();
+
+        let (mut mds_x, mut mds_y) = (input[..l].to_vec(), input[l..].to_vec());
+        anemoi.apply_mds_only(&mut mds_x, &mut mds_y);
+        assert_eq!(
+            (mds_x.clone(), mds_y.clone()),
+            anemoi.mds_rho(&input[..l], &input[l..])
+        );
+
+        let (mut x, mut y) = (input[..l].to_vec(), input[l..].to_vec());
+        anemoi.linear_layer(&mut x, &mut y);
+        for i in 0..l {
+            assert_eq!(y[i], mds_y[i].add(mds_x[i]), "y[{i}]");
+            assert_eq!(x[i], mds_x[i].add(y[i]), "x[{i}]");
+        }
+    }
+
+    fn check_validate<F: FieldConst>(pre: PreparedParams<F>, alpha_inv: u128) {
+        let mut params = AnemoiParams::from_prepared(pre);
+        assert_eq!(params.validate(), Ok(()));
+        params.alpha_inv = PowWindowTable::new(alpha_inv.wrapping_add(2));
+        assert_eq!(params.validate(), Err(ParamsError::AlphaInvNotInverse));
+    }
+
+    #[test]
+    fn test_validate_alpha_inv() {
+        check_validate(params_32_l8(), ALPHA_INV_32);
+        check_validate(params_64_l4(), ALPHA_INV_64);
+        check_validate(params_128_l2(), ALPHA_INV_128);
+    }
+
+    // Regenerate with `cargo run --bin gen_params`, which prints `alpha_inv` per field.
+    #[test]
+    fn shipped_alpha_inv_matches_computed() {
+        for (n_bits, shipped) in [(32, ALPHA_INV_32), (64, ALPHA_INV_64), (128, ALPHA_INV_128)] {
+            assert_eq!(
+                anemoi_gen::compute_alpha_inv(ANEMOI_ALPHA, n_bits),
+                Some(shipped),
+                "ALPHA_INV_{n_bits} is wrong"
+            );
+        }
+    }
+
+    // Everything a corrupted table could break: the MDS property, the table shapes, `alpha_inv`,
+    // and `permute_inverse` undoing `permute`.
+    fn check_shipped<F: FieldConst + binius_field::Field>(instance: Instance, pre: PreparedParams<F>) {
+        let name = instance.name();
+        let l = pre.l;
+        assert_eq!(pre.t, 2 * l, "{name}");
+        assert!(
+            pre.mds.len() == l && pre.mds.iter().all(|row| row.len() == l),
+            "{name}: MDS is not {l}x{l}"
+        );
+        assert!(is_mds(&pre.mds), "{name}: MDS has a singular square submatrix");
+        for (table, rows) in [("c", &pre.c), ("d", &pre.d)] {
+            assert_eq!(rows.len(), pre.rounds, "{name}: {table} does not have one row per round");
+            assert!(rows.iter().all(|row| row.len() == l), "{name}: {table} rows are not {l} wide");
+        }
+
+        let anemoi = Anemoi::new(AnemoiParams::from_prepared(pre));
+        assert_eq!(anemoi.params.validate(), Ok(()), "{name}");
+        let mut outputs = Vec::new();
+        for k in 0..4u8 {
+            let input: Vec<F> = (0..2 * l)
+                .map(|i| <F as FieldOps>::from_u8(k * 32 + i as u8))
+                .collect();
+            let mut state = input.clone();
+            anemoi.permute(&mut state);
+            assert!(!outputs.contains(&state), "{name}: two inputs map to the same output");
+            outputs.push(state.clone());
+            anemoi.permute_inverse(&mut state);
+            assert_eq!(state, input, "{name}: permute_inverse does not undo permute");
+        }
+    }
+
+    #[test]
+    fn shipped_params_are_valid() {
+        for instance in Instance::all() {
+            match instance {
+                Instance::B32L8 => check_shipped(instance, params_32_l8()),
+                Instance::B32L12 => check_shipped(instance, params_32_l12()),
+                Instance::B64L4 => check_shipped(instance, params_64_l4()),
+                Instance::B64L6 => check_shipped(instance, params_64_l6()),
+                Instance::B128L2 => check_shipped(instance, params_128_l2()),
+                Instance::B128L3 => check_shipped(instance, params_128_l3()),
+            }
+        }
+    }
+
+    #[test]
+    fn from_computed_is_a_bijection() {
+        let anemoi = Anemoi::<BinaryField64b>::from_computed(
+            anemoi_gen::compute_params(8, params::PI0_64, params::PI1_64).unwrap(),
+        );
+        let inputs: Vec<Vec<BinaryField64b>> = (0..16u64)
+            .map(|i| (0..8).map(|j| BinaryField64b::from_raw(i * 8 + j)).collect())
+            .collect();
+
+        let mut outputs = Vec::new();
+        for input in &inputs {
+            let mut state = input.clone();
+            anemoi.permute(&mut state);
+            assert!(!outputs.contains(&state), "two inputs map to the same output");
+            outputs.push(state.clone());
+            anemoi.permute_inverse(&mut state);
+            assert_eq!(&state, input);
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(16))]
+
+        #[test]
+        fn test_sbox_matches_reference_32(raw in prop::collection::vec(any::<u32>(), 8)) {
+            check_sbox(params_32_l8(), raw);
+        }
+
+        #[test]
+        fn test_sbox_matches_reference_64(raw in prop::collection::vec(any::<u64>(), 8)) {
+            check_sbox(params_64_l4(), raw);
+        }
+
+        #[test]
+        fn test_sbox_matches_reference_128(raw in prop::collection::vec(any::<u128>(), 8)) {
+            check_sbox(params_128_l2(), raw);
+        }
+
+        #[test]
+        fn test_linear_layer_32_l8(raw in prop::collection::vec(any::<u32>(), 16)) {
+            check_linear_layer(params_32_l8(), raw);
+        }
+
+        #[test]
+        fn test_linear_layer_64_l4(raw in prop::collection::vec(any::<u64>(), 8)) {
+            check_linear_layer(params_64_l4(), raw);
+        }
+
+        #[test]
+        fn test_linear_layer_128_l3(raw in prop::collection::vec(any::<u128>(), 6)) {
+            check_linear_layer(params_128_l3(), raw);
+        }
+
+        #[test]
+        fn test_round_trip_32_l8(raw in prop::collection::vec(any::<u32>(), 16)) {
+            check_round_trip(params_32_l8(), raw);
+        }
+
+        #[test]
+        fn test_round_trip_32_l12(raw in prop::collection::vec(any::<u32>(), 24)) {
+            check_round_trip(params_32_l12(), raw);
+        }
+
+        #[test]
+        fn test_round_trip_64_l4(raw in prop::collection::vec(any::<u64>(), 8)) {
+            check_round_trip(params_64_l4(), raw);
+        }
+
+        #[test]
+        fn test_round_trip_64_l6(raw in prop::collection::vec(any::<u64>(), 12)) {
+            check_round_trip(params_64_l6(), raw);
+        }
+
+        #[test]
+        fn test_round_trip_128_l2(raw in prop::collection::vec(any::<u128>(), 4)) {
+            check_round_trip(params_128_l2(), raw);
+        }
+
+        #[test]
+        fn test_round_trip_128_l3(raw in prop::collection::vec(any::<u128>(), 6)) {
+            check_round_trip(params_128_l3(), raw);
+        }
+    }
 }
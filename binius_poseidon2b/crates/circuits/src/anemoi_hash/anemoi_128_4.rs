@@ -6,17 +6,24 @@ use binius_field::BinaryField128b;
 
 use crate::{
 	anemoi_hash::{
-		common::{anemoi_permutation, prep_params, AnemoiParams},
+		common::{anemoi_permutation, prep_params, round_report, AnemoiParams, RoundReport},
 		params,
 	},
 	builder::ConstraintSystemBuilder,
+	hades::sbox::SboxGadget,
 };
 
 const STATE_SIZE: usize = 4;
 
-fn params_128_l2() -> AnemoiParams<BinaryField128b> {
+fn params_128_l2() -> Result<AnemoiParams<BinaryField128b>> {
 	use params::params128_l2 as p;
-	prep_params::<BinaryField128b, { p::L }, { p::ROUNDS }>(params::ALPHA_INV_128, &p::MDS, &p::C, &p::D)
+	const { assert!(p::C.len() == p::ROUNDS && p::D.len() == p::ROUNDS) };
+	prep_params::<BinaryField128b, { p::L }, { p::ROUNDS }>(
+		params::ALPHA_INV_128,
+		&p::MDS,
+		&p::C,
+		&p::D,
+	)
 }
 
 pub fn permutation(
@@ -24,7 +31,25 @@ pub fn permutation(
 	log_size: usize,
 	state_in: [OracleId; STATE_SIZE],
 ) -> Result<[OracleId; STATE_SIZE]> {
-	let params = params_128_l2();
+	permutation_with_sbox(builder, log_size, state_in, SboxGadget::default())
+}
+
+/// Same as [`permutation`], with the S-box x^7 checks built by `sbox`.
+pub fn permutation_with_sbox(
+	builder: &mut ConstraintSystemBuilder,
+	log_size: usize,
+	state_in: [OracleId; STATE_SIZE],
+	sbox: SboxGadget,
+) -> Result<[OracleId; STATE_SIZE]> {
+	let mut params = params_128_l2()?;
+	params.sbox = sbox;
 	let out = anemoi_permutation::<BinaryField128b>(builder, log_size, &state_in, &params)?;
 	Ok(out.try_into().expect("anemoi state size mismatch"))
 }
+
+/// Per-round committed columns, constraints and maximum degree with `sbox`.
+pub fn round_report_with_sbox(sbox: SboxGadget) -> Result<RoundReport> {
+	let mut params = params_128_l2()?;
+	params.sbox = sbox;
+	round_report(&params)
+}
@@ -14,9 +14,15 @@ use crate::{
 
 const STATE_SIZE: usize = 6;
 
-fn params_128_l3() -> AnemoiParams<BinaryField128b> {
+fn params_128_l3() -> Result<AnemoiParams<BinaryField128b>> {
 	use params::params128_l3 as p;
-	prep_params::<BinaryField128b, { p::L }, { p::ROUNDS }>(params::ALPHA_INV_128, &p::MDS, &p::C, &p::D)
+	const { assert!(p::C.len() == p::ROUNDS && p::D.len() == p::ROUNDS) };
+	prep_params::<BinaryField128b, { p::L }, { p::ROUNDS }>(
+		params::ALPHA_INV_128,
+		&p::MDS,
+		&p::C,
+		&p::D,
+	)
 }
 
 pub fn permutation(
@@ -24,7 +30,7 @@ pub fn permutation(
 	log_size: usize,
 	state_in: [OracleId; STATE_SIZE],
 ) -> Result<[OracleId; STATE_SIZE]> {
-	let params = params_128_l3();
+	let params = params_128_l3()?;
 	let out = anemoi_permutation::<BinaryField128b>(builder, log_size, &state_in, &params)?;
 	Ok(out.try_into().expect("anemoi state size mismatch"))
 }
@@ -14,8 +14,9 @@ use crate::{
 
 const STATE_SIZE: usize = 16;
 
-fn params_32_l8() -> AnemoiParams<BinaryField32b> {
+fn params_32_l8() -> Result<AnemoiParams<BinaryField32b>> {
 	use params::params32_l8 as p;
+	const { assert!(p::C.len() == p::ROUNDS && p::D.len() == p::ROUNDS) };
 	prep_params::<BinaryField32b, { p::L }, { p::ROUNDS }>(params::ALPHA_INV_32, &p::MDS, &p::C, &p::D)
 }
 
@@ -24,7 +25,29 @@ pub fn permutation(
 	log_size: usize,
 	state_in: [OracleId; STATE_SIZE],
 ) -> Result<[OracleId; STATE_SIZE]> {
-	let params = params_32_l8();
+	let params = params_32_l8()?;
 	let out = anemoi_permutation::<BinaryField32b>(builder, log_size, &state_in, &params)?;
 	Ok(out.try_into().expect("anemoi state size mismatch"))
 }
+
+#[cfg(test)]
+mod tests {
+	use binius_core::oracle::OracleId;
+	use binius_field::BinaryField32b;
+
+	use super::{STATE_SIZE, permutation};
+	use crate::{builder::test_utils::test_circuit, unconstrained::unconstrained};
+
+	#[test]
+	fn test_anemoi_single_row() {
+		test_circuit(|builder| {
+			let log_size = 0;
+			let state_in: [OracleId; STATE_SIZE] = std::array::from_fn(|i| {
+				unconstrained::<BinaryField32b>(builder, format!("a_in[{i}]"), log_size).unwrap()
+			});
+			let _state_out = permutation(builder, log_size, state_in).unwrap();
+			Ok(vec![])
+		})
+		.unwrap();
+	}
+}
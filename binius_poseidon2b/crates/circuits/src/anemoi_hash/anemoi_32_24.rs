@@ -14,8 +14,9 @@ use crate::{
 
 const STATE_SIZE: usize = 24;
 
-fn params_32_l12() -> AnemoiParams<BinaryField32b> {
+fn params_32_l12() -> Result<AnemoiParams<BinaryField32b>> {
 	use params::params32_l12 as p;
+	const { assert!(p::C.len() == p::ROUNDS && p::D.len() == p::ROUNDS) };
 	prep_params::<BinaryField32b, { p::L }, { p::ROUNDS }>(params::ALPHA_INV_32, &p::MDS, &p::C, &p::D)
 }
 
@@ -24,7 +25,7 @@ pub fn permutation(
 	log_size: usize,
 	state_in: [OracleId; STATE_SIZE],
 ) -> Result<[OracleId; STATE_SIZE]> {
-	let params = params_32_l12();
+	let params = params_32_l12()?;
 	let out = anemoi_permutation::<BinaryField32b>(builder, log_size, &state_in, &params)?;
 	Ok(out.try_into().expect("anemoi state size mismatch"))
 }
@@ -14,8 +14,9 @@ use crate::{
 
 const STATE_SIZE: usize = 12;
 
-fn params_64_l6() -> AnemoiParams<BinaryField64b> {
+fn params_64_l6() -> Result<AnemoiParams<BinaryField64b>> {
 	use params::params64_l6 as p;
+	const { assert!(p::C.len() == p::ROUNDS && p::D.len() == p::ROUNDS) };
 	prep_params::<BinaryField64b, { p::L }, { p::ROUNDS }>(params::ALPHA_INV_64, &p::MDS, &p::C, &p::D)
 }
 
@@ -24,7 +25,7 @@ pub fn permutation(
 	log_size: usize,
 	state_in: [OracleId; STATE_SIZE],
 ) -> Result<[OracleId; STATE_SIZE]> {
-	let params = params_64_l6();
+	let params = params_64_l6()?;
 	let out = anemoi_permutation::<BinaryField64b>(builder, log_size, &state_in, &params)?;
 	Ok(out.try_into().expect("anemoi state size mismatch"))
 }
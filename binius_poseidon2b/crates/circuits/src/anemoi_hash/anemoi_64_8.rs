@@ -6,7 +6,7 @@ use binius_field::BinaryField64b;
 
 use crate::{
 	anemoi_hash::{
-		common::{anemoi_permutation, prep_params, AnemoiParams},
+		common::{AnemoiParams, FlystelForm, anemoi_permutation, prep_params},
 		params,
 	},
 	builder::ConstraintSystemBuilder,
@@ -14,8 +14,9 @@ use crate::{
 
 const STATE_SIZE: usize = 8;
 
-fn params_64_l4() -> AnemoiParams<BinaryField64b> {
+fn params_64_l4() -> Result<AnemoiParams<BinaryField64b>> {
 	use params::params64_l4 as p;
+	const { assert!(p::C.len() == p::ROUNDS && p::D.len() == p::ROUNDS) };
 	prep_params::<BinaryField64b, { p::L }, { p::ROUNDS }>(params::ALPHA_INV_64, &p::MDS, &p::C, &p::D)
 }
 
@@ -24,7 +25,18 @@ pub fn permutation(
 	log_size: usize,
 	state_in: [OracleId; STATE_SIZE],
 ) -> Result<[OracleId; STATE_SIZE]> {
-	let params = params_64_l4();
+	permutation_with_flystel(builder, log_size, state_in, FlystelForm::default())
+}
+
+/// Same as [`permutation`], with the S-boxes constrained in the given Flystel form.
+pub fn permutation_with_flystel(
+	builder: &mut ConstraintSystemBuilder,
+	log_size: usize,
+	state_in: [OracleId; STATE_SIZE],
+	flystel: FlystelForm,
+) -> Result<[OracleId; STATE_SIZE]> {
+	let mut params = params_64_l4()?;
+	params.flystel = flystel;
 	let out = anemoi_permutation::<BinaryField64b>(builder, log_size, &state_in, &params)?;
 	Ok(out.try_into().expect("anemoi state size mismatch"))
 }
@@ -4,14 +4,19 @@ use std::fmt::Debug;
 
 use anyhow::Result;
 use bytemuck::Pod;
-use binius_core::oracle::OracleId;
+use binius_core::oracle::{MultilinearPolyVariant, OracleId};
 use binius_field::{
-	as_packed_field::PackScalar, BinaryField, BinaryField128b, BinaryField32b, BinaryField64b,
-	ExtensionField, Field as BiniusField, TowerField,
+	as_packed_field::PackScalar,
+	util::{PowWindowTable, pow_windowed},
+	BinaryField, BinaryField128b, BinaryField32b, BinaryField64b, ExtensionField,
+	Field as BiniusField, TowerField,
 };
 use binius_math::{ArithCircuit, ArithExpr};
 
-use crate::builder::{types::{F, U}, ConstraintSystemBuilder};
+use crate::{
+	builder::{types::{F, U}, ConstraintSystemBuilder},
+	hades::sbox::{self, SboxGadget},
+};
 
 use super::params;
 
@@ -55,19 +60,6 @@ impl_field_ops!(BinaryField32b, u32);
 impl_field_ops!(BinaryField64b, u64);
 impl_field_ops!(BinaryField128b, u128);
 
-#[inline(always)]
-fn pow_const<F: FieldOps>(mut base: F, mut exp: u128) -> F {
-	let mut acc = F::from_u8(1);
-	while exp > 0 {
-		if exp & 1 == 1 {
-			acc = acc.safe_mul(base);
-		}
-		base = base.safe_square();
-		exp >>= 1;
-	}
-	acc
-}
-
 #[inline(always)]
 fn pow_alpha<F: FieldOps>(x: F) -> F {
 	let x2 = x.safe_square();
@@ -75,18 +67,6 @@ fn pow_alpha<F: FieldOps>(x: F) -> F {
 	x.safe_mul(x2).safe_mul(x4) // x^7
 }
 
-fn x7_constraint_expr() -> ArithCircuit<FF> {
-	let x = ArithExpr::Var(0);
-	let x7 = ArithExpr::Var(1);
-
-	let x2 = x.clone() * x.clone();
-	let x4 = x2.clone() * x2.clone();
-	let x6 = x2 * x4;
-	let x7_expr = x6 * x;
-
-	(x7 - x7_expr).into()
-}
-
 pub trait AnemoiField: FieldConst + BinaryField + TowerField + Pod {
 	const ALPHA_INV: u128;
 }
@@ -103,25 +83,46 @@ impl AnemoiField for BinaryField128b {
 	const ALPHA_INV: u128 = params::ALPHA_INV_128;
 }
 
+/// Which constraint set proves each Flystel S-box.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FlystelForm {
+	/// Commits `t`, `y_out`, `x_out`, `y_out + y_in` and three x^7 columns, and closes the loop
+	/// with `(y_out + y_in)^7 == t`. All checks other than the three x^7 ones are linear.
+	#[default]
+	Closed,
+	/// Commits only `w = t^(1/alpha)` and `x_out`; `y_out = y_in + w` is a linear combination.
+	/// Two committed columns per S-box instead of seven, at the cost of two degree-7 checks that
+	/// also involve `y_in` (`w^7 == x_in + beta*y_in^7 + delta` and
+	/// `x_out == w^7 + beta*(y_in + w)^7`).
+	Open,
+}
+
 #[derive(Clone)]
 pub struct AnemoiParams<F: FieldConst> {
 	pub t: usize,
 	pub l: usize,
 	pub rounds: usize,
-	pub alpha_inv: u128,
+	pub alpha_inv: PowWindowTable,
 	pub beta: F,
 	pub delta: F,
 	pub c: Vec<Vec<F>>,
 	pub d: Vec<Vec<F>>,
 	pub mds: Vec<Vec<F>>,
+	pub flystel: FlystelForm,
+	/// How the closed form checks its x^7 powers. The open form only supports the default.
+	pub sbox: SboxGadget,
 }
 
+/// Builds the parameters from raw constant tables.
+///
+/// `R` is the number of rounds `anemoi_permutation` will run, one row of `c`/`d` constants per
+/// round. Callers pin it to their parameter module's `ROUNDS`.
 pub fn prep_params<F: AnemoiField, const L: usize, const R: usize>(
 	alpha_inv: u128,
 	mds_raw: &[[F::Raw; L]; L],
 	c_raw: &[[F::Raw; L]; R],
 	d_raw: &[[F::Raw; L]; R],
-) -> AnemoiParams<F> {
+) -> Result<AnemoiParams<F>> {
 	let t = 2 * L;
 	let mds = mds_raw
 		.iter()
@@ -135,11 +136,11 @@ pub fn prep_params<F: AnemoiField, const L: usize, const R: usize>(
 		.iter()
 		.map(|row| row.iter().copied().map(F::from_raw).collect())
 		.collect();
-	AnemoiParams {
+	let params = AnemoiParams {
 		t,
 		l: L,
 		rounds: R,
-		alpha_inv,
+		alpha_inv: PowWindowTable::new(alpha_inv),
 		beta: F::MULTIPLICATIVE_GENERATOR,
 		delta: F::MULTIPLICATIVE_GENERATOR
 			.invert()
@@ -147,6 +148,25 @@ pub fn prep_params<F: AnemoiField, const L: usize, const R: usize>(
 		c,
 		d,
 		mds,
+		flystel: FlystelForm::default(),
+		sbox: SboxGadget::default(),
+	};
+	#[cfg(debug_assertions)]
+	params.validate()?;
+	Ok(params)
+}
+
+impl<F: AnemoiField> AnemoiParams<F> {
+	/// Checks that `alpha_inv` really inverts `x -> x^7`.
+	///
+	/// `beta` is the multiplicative generator `g`, so `g^(7 * alpha_inv) == g` holds exactly when
+	/// `7 * alpha_inv == 1` modulo the group order.
+	pub fn validate(&self) -> Result<()> {
+		anyhow::ensure!(
+			pow_windowed(pow_alpha(self.beta), &self.alpha_inv) == self.beta,
+			"alpha_inv is not the inverse of 7 modulo the multiplicative group order"
+		);
+		Ok(())
 	}
 }
 
@@ -161,13 +181,14 @@ pub fn plain_permutation<F: AnemoiField>(state: &mut [F], params: &AnemoiParams<
 			y[i] = y[i].safe_add(params.d[r][i]);
 		}
 		linear_layer_plain(&mut x, &mut y, &params.mds);
+		// Flystel: t = x + Q(y) + delta, y' = y + t^(1/alpha), x' = t + Q(y') with Q(y) =
+		// beta*y^alpha. Q is evaluated on two different inputs, so nothing is shared between the
+		// two calls.
+		let q = |y: F| params.beta.safe_mul(pow_alpha(y));
 		for i in 0..l {
-			let y_pow = pow_alpha(y[i]);
-			let t = x[i].safe_add(params.beta.safe_mul(y_pow)).safe_add(params.delta);
-			let inv = pow_const(t, params.alpha_inv);
-			y[i] = y[i].safe_add(inv);
-			let y_pow_new = pow_alpha(y[i]);
-			x[i] = t.safe_add(params.beta.safe_mul(y_pow_new));
+			let t = x[i].safe_add(q(y[i])).safe_add(params.delta);
+			y[i] = y[i].safe_add(pow_windowed(t, &params.alpha_inv));
+			x[i] = t.safe_add(q(y[i]));
 		}
 	}
 
@@ -267,25 +288,33 @@ fn enforce_lin_comb<FBase: FieldConst>(
 #[derive(Copy, Clone)]
 struct Pow7Cols {
 	pow7: OracleId,
+	// x^2, x^4 and x^6, committed only with `SboxGadget::Split`.
+	split: Option<[OracleId; 3]>,
 }
 
 // Adds columns and constraints to compute input^7
 
-fn add_pow7<F: FieldConst>(
+fn add_pow7<F: AnemoiField>(
 	builder: &mut ConstraintSystemBuilder,
 	log_size: usize,
 	prefix: &str,
 	input: OracleId,
-) -> Pow7Cols
-	where
-		FF: ExtensionField<F>,
-		F: TowerField,
+	gadget: SboxGadget,
+) -> Result<Pow7Cols>
+where
+	FF: ExtensionField<F>,
 {
 	let pow7 = builder.add_committed(format!("{prefix}_pow7"), log_size, F::TOWER_LEVEL);
-
-	builder.assert_zero(format!("{prefix}_pow7_check"), [input, pow7], x7_constraint_expr());
-
-	Pow7Cols { pow7 }
+	let split = sbox::add_x7_constraints_without_witness::<F>(
+		builder,
+		log_size,
+		format!("{prefix}_pow7_check"),
+		input,
+		pow7,
+		gadget,
+	)?;
+
+	Ok(Pow7Cols { pow7, split })
 }
 
 struct SboxCols {
@@ -307,16 +336,17 @@ fn add_sbox<F: AnemoiField>(
 	params: &AnemoiParams<F>,
 	x_in: OracleId,
 	y_in: OracleId,
-) -> SboxCols
+) -> Result<SboxCols>
 where
 	FF: ExtensionField<F>,
 {
-	let y_pow = add_pow7::<F>(builder, log_size, &format!("sbox_{idx}_y_in"), y_in);
+	let gadget = params.sbox;
+	let y_pow = add_pow7::<F>(builder, log_size, &format!("sbox_{idx}_y_in"), y_in, gadget)?;
 	let t = builder.add_committed(format!("sbox_{idx}_t"), log_size, F::TOWER_LEVEL);
 	let y_out = builder.add_committed(format!("sbox_{idx}_y_out"), log_size, F::TOWER_LEVEL);
 	let sum = builder.add_committed(format!("sbox_{idx}_sum"), log_size, F::TOWER_LEVEL);
-	let sum_pow = add_pow7::<F>(builder, log_size, &format!("sbox_{idx}_sum"), sum);
-	let y_out_pow = add_pow7::<F>(builder, log_size, &format!("sbox_{idx}_y_out"), y_out);
+	let sum_pow = add_pow7::<F>(builder, log_size, &format!("sbox_{idx}_sum"), sum, gadget)?;
+	let y_out_pow = add_pow7::<F>(builder, log_size, &format!("sbox_{idx}_y_out"), y_out, gadget)?;
 	let x_out = builder.add_committed(format!("sbox_{idx}_x_out"), log_size, F::TOWER_LEVEL);
 
 	enforce_lin_comb(
@@ -345,7 +375,7 @@ where
 		F::ZERO,
 	);
 
-	SboxCols {
+	Ok(SboxCols {
 		y_pow,
 		t,
 		y_out,
@@ -353,7 +383,51 @@ where
 		sum_pow,
 		y_out_pow,
 		x_out,
-	}
+	})
+}
+
+struct OpenSboxCols {
+	w: OracleId,
+	y_out: OracleId,
+	x_out: OracleId,
+}
+
+// Adds columns and constraints for one Anemoi S-box in the open form: with w = t^(1/alpha) as the
+// only committed intermediate, t is w^7 and both outputs follow from w.
+
+fn add_sbox_open<F: AnemoiField>(
+	builder: &mut ConstraintSystemBuilder,
+	log_size: usize,
+	idx: usize,
+	params: &AnemoiParams<F>,
+	x_in: OracleId,
+	y_in: OracleId,
+) -> Result<OpenSboxCols>
+where
+	FF: ExtensionField<F>,
+{
+	let w = builder.add_committed(format!("sbox_{idx}_w"), log_size, F::TOWER_LEVEL);
+	let x_out = builder.add_committed(format!("sbox_{idx}_x_out"), log_size, F::TOWER_LEVEL);
+	let y_out = builder.add_linear_combination(
+		format!("sbox_{idx}_y_out"),
+		log_size,
+		[(y_in, FF::ONE), (w, FF::ONE)],
+	)?;
+
+	let beta = ArithExpr::Const(FF::from(params.beta));
+
+	// w^7 == x_in + beta*y_in^7 + delta
+	let (x, y, w_var) = (ArithExpr::Var(0), ArithExpr::Var(1), ArithExpr::Var(2));
+	let w_check =
+		w_var.pow(7) - x - beta.clone() * y.pow(7) - ArithExpr::Const(FF::from(params.delta));
+	builder.assert_zero(format!("sbox_{idx}_w_check"), [x_in, y_in, w], w_check.into());
+
+	// x_out == w^7 + beta*(y_in + w)^7
+	let (y, w_var, x_out_var) = (ArithExpr::Var(0), ArithExpr::Var(1), ArithExpr::Var(2));
+	let x_out_check = x_out_var - w_var.clone().pow(7) - beta * (y + w_var).pow(7);
+	builder.assert_zero(format!("sbox_{idx}_x_out_check"), [y_in, w, x_out], x_out_check.into());
+
+	Ok(OpenSboxCols { w, y_out, x_out })
 }
 
 // Fills columns for value^2, value^4, value^6, value^7
@@ -389,7 +463,6 @@ where
 	let mut mds_y = Vec::with_capacity(l);
 	let mut new_y = Vec::with_capacity(l);
 	let mut new_x = Vec::with_capacity(l);
-	let mut sboxes = Vec::with_capacity(l);
 
 	for i in 0..l {
 		x_rc.push(builder.add_committed(format!("r{round}_x_rc_{i}"), log_size, F::TOWER_LEVEL));
@@ -457,13 +530,6 @@ where
 		);
 	}
 
-	for i in 0..l {
-		let sbox = add_sbox(builder, log_size, i, params, new_x[i], new_y[i]);
-		sboxes.push(sbox);
-	}
-
-	builder.pop_namespace();
-
 	if let Some(witness) = builder.witness() {
 		let rows = 1 << log_size;
 
@@ -510,6 +576,45 @@ where
 				new_x_cols[row].as_mut_slice::<F>()[z] = ny.safe_add(acc_x);
 			}
 		}
+	}
+
+	let outputs = match params.flystel {
+		FlystelForm::Closed => closed_sboxes(builder, log_size, params, &new_x, &new_y)?,
+		FlystelForm::Open => open_sboxes(builder, log_size, params, &new_x, &new_y)?,
+	};
+
+	builder.pop_namespace();
+	Ok(outputs)
+}
+
+// Closed-form S-boxes on the round's (new_x, new_y) columns, with their witness; returns (x, y).
+
+fn closed_sboxes<F: AnemoiField>(
+	builder: &mut ConstraintSystemBuilder,
+	log_size: usize,
+	params: &AnemoiParams<F>,
+	new_x: &[OracleId],
+	new_y: &[OracleId],
+) -> Result<(Vec<OracleId>, Vec<OracleId>)>
+where
+	FF: ExtensionField<F>,
+	U: PackScalar<F>,
+{
+	let l = params.l;
+	let sboxes: Vec<SboxCols> = (0..l)
+		.map(|i| add_sbox(builder, log_size, i, params, new_x[i], new_y[i]))
+		.collect::<Result<_>>()?;
+
+	if let Some(witness) = builder.witness() {
+		let rows = 1 << log_size;
+		let new_x_cols = new_x
+			.iter()
+			.map(|&id| witness.get::<F>(id))
+			.collect::<Result<Vec<_>, _>>()?;
+		let new_y_cols = new_y
+			.iter()
+			.map(|&id| witness.get::<F>(id))
+			.collect::<Result<Vec<_>, _>>()?;
 
 		let mut sbox_y_pow: Vec<_> = sboxes
 			.iter()
@@ -527,20 +632,40 @@ where
 			.map(|ids| witness.new_column::<F>(ids.y_out_pow.pow7))
 			.collect();
 		let mut sbox_x_out: Vec<_> = sboxes.iter().map(|ids| witness.new_column::<F>(ids.x_out)).collect();
+		// Split intermediates of the y_in, sum and y_out powers, in that order.
+		let mut sbox_split: Vec<_> = sboxes
+			.iter()
+			.map(|ids| {
+				[ids.y_pow, ids.sum_pow, ids.y_out_pow].map(|pow| {
+					pow.split
+						.map(|cols| cols.map(|id| witness.new_column::<F>(id)))
+				})
+			})
+			.collect();
 
 		for z in 0..rows {
 			for i in 0..l {
-				let y_lin = new_y_cols[i].as_mut_slice::<F>()[z];
-				let (_, _, _, y_pow7) = fill_pow7_column(y_lin);
-				let x_lin = new_x_cols[i].as_mut_slice::<F>()[z];
+				let y_lin = new_y_cols[i].as_slice::<F>()[z];
+				let y_pows = fill_pow7_column(y_lin);
+				let y_pow7 = y_pows.3;
+				let x_lin = new_x_cols[i].as_slice::<F>()[z];
 				let t_val = x_lin.safe_add(params.beta.safe_mul(y_pow7)).safe_add(params.delta);
-				let inv = pow_const(t_val, params.alpha_inv);
+				let inv = pow_windowed(t_val, &params.alpha_inv);
 				let y_out_val = y_lin.safe_add(inv);
 				let sum_val = y_out_val.safe_add(y_lin);
-				let (_, _, _, sum_pow7) = fill_pow7_column(sum_val);
-				let (_, _, _, y_out_pow7) = fill_pow7_column(y_out_val);
+				let sum_pows = fill_pow7_column(sum_val);
+				let y_out_pows = fill_pow7_column(y_out_val);
+				let (sum_pow7, y_out_pow7) = (sum_pows.3, y_out_pows.3);
 				let x_out_val = t_val.safe_add(params.beta.safe_mul(y_out_pow7));
 
+				for (cols, pows) in sbox_split[i].iter_mut().zip([y_pows, sum_pows, y_out_pows]) {
+					if let Some([x2, x4, x6]) = cols {
+						x2.as_mut_slice::<F>()[z] = pows.0;
+						x4.as_mut_slice::<F>()[z] = pows.1;
+						x6.as_mut_slice::<F>()[z] = pows.2;
+					}
+				}
+
 				sbox_y_pow[i].as_mut_slice::<F>()[z] = y_pow7;
 				sbox_t[i].as_mut_slice::<F>()[z] = t_val;
 				sbox_y_out[i].as_mut_slice::<F>()[z] = y_out_val;
@@ -557,6 +682,57 @@ where
 	Ok((x_out, y_out))
 }
 
+// Open-form S-boxes on the round's (new_x, new_y) columns, with their witness; returns (x, y).
+
+fn open_sboxes<F: AnemoiField>(
+	builder: &mut ConstraintSystemBuilder,
+	log_size: usize,
+	params: &AnemoiParams<F>,
+	new_x: &[OracleId],
+	new_y: &[OracleId],
+) -> Result<(Vec<OracleId>, Vec<OracleId>)>
+where
+	FF: ExtensionField<F>,
+	U: PackScalar<F>,
+{
+	anyhow::ensure!(
+		params.sbox == SboxGadget::Direct,
+		"the open Flystel form has no split x^7 checks; use SboxGadget::Direct"
+	);
+	let l = params.l;
+	let sboxes: Vec<OpenSboxCols> = (0..l)
+		.map(|i| add_sbox_open(builder, log_size, i, params, new_x[i], new_y[i]))
+		.collect::<Result<_>>()?;
+
+	if let Some(witness) = builder.witness() {
+		let rows = 1 << log_size;
+		for (i, sbox) in sboxes.iter().enumerate() {
+			let x_in = witness.get::<F>(new_x[i])?;
+			let y_in = witness.get::<F>(new_y[i])?;
+			let (x_in, y_in) = (x_in.as_slice::<F>(), y_in.as_slice::<F>());
+
+			let mut w = witness.new_column::<F>(sbox.w);
+			let mut y_out = witness.new_column::<F>(sbox.y_out);
+			let mut x_out = witness.new_column::<F>(sbox.x_out);
+			let (w, y_out, x_out) =
+				(w.as_mut_slice::<F>(), y_out.as_mut_slice::<F>(), x_out.as_mut_slice::<F>());
+
+			for z in 0..rows {
+				let t = x_in[z]
+					.safe_add(params.beta.safe_mul(pow_alpha(y_in[z])))
+					.safe_add(params.delta);
+				w[z] = pow_windowed(t, &params.alpha_inv);
+				y_out[z] = y_in[z].safe_add(w[z]);
+				x_out[z] = t.safe_add(params.beta.safe_mul(pow_alpha(y_out[z])));
+			}
+		}
+	}
+
+	let x_out = sboxes.iter().map(|s| s.x_out).collect();
+	let y_out = sboxes.iter().map(|s| s.y_out).collect();
+	Ok((x_out, y_out))
+}
+
 // Final MDS layer in the round function
 
 fn apply_mds_only<F: AnemoiField>(
@@ -693,3 +869,195 @@ where
 
 	Ok(out)
 }
+
+/// Size of one round's constraint set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RoundReport {
+	/// Committed columns added by the round, excluding its input state.
+	pub committed_columns: usize,
+	pub constraints: usize,
+	/// Highest total degree among the round's constraints.
+	pub max_degree: usize,
+}
+
+/// Builds round 0 alone, without a witness, and measures it.
+///
+/// Every round has the same shape, so this is also the per-round cost of `anemoi_permutation`.
+pub fn round_report<F: AnemoiField>(params: &AnemoiParams<F>) -> Result<RoundReport>
+where
+	FF: ExtensionField<F>,
+	U: PackScalar<F>,
+{
+	let mut builder = ConstraintSystemBuilder::new();
+	let state: Vec<OracleId> = (0..params.t)
+		.map(|i| builder.add_committed(format!("in_{i}"), 1, F::TOWER_LEVEL))
+		.collect();
+	let (x_state, y_state) = (state[..params.l].to_vec(), state[params.l..].to_vec());
+	anemoi_round(&mut builder, 1, 0, x_state, y_state, params)?;
+
+	let system = builder.build()?;
+	let committed = system
+		.oracles
+		.polys()
+		.filter(|oracle| matches!(oracle.variant, MultilinearPolyVariant::Committed))
+		.count();
+	let constraints = system
+		.table_constraints
+		.iter()
+		.flat_map(|set| &set.constraints);
+	Ok(RoundReport {
+		committed_columns: committed - params.t,
+		constraints: constraints.clone().count(),
+		max_degree: constraints
+			.map(|constraint| constraint.composition.degree())
+			.max()
+			.unwrap_or(0),
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use binius_core::oracle::OracleId;
+	use binius_field::{BinaryField32b, BinaryField64b, BinaryField128b, util::PowWindowTable};
+
+	use super::{
+		AnemoiField, AnemoiParams, FlystelForm, RoundReport, anemoi_permutation,
+		prep_params, round_report,
+	};
+	use crate::{
+		anemoi_hash::params::{self, params64_l4 as p},
+		builder::test_utils::test_circuit,
+		hades::sbox::SboxGadget,
+		unconstrained::unconstrained,
+	};
+
+	fn params_128_l2() -> AnemoiParams<BinaryField128b> {
+		use params::params128_l2 as p;
+		prep_params::<BinaryField128b, { p::L }, { p::ROUNDS }>(
+			params::ALPHA_INV_128,
+			&p::MDS,
+			&p::C,
+			&p::D,
+		)
+		.unwrap()
+	}
+
+	#[test]
+	fn test_prep_params_rounds() {
+		let params = prep_params::<BinaryField64b, { p::L }, { p::ROUNDS }>(
+			params::ALPHA_INV_64,
+			&p::MDS,
+			&p::C,
+			&p::D,
+		)
+		.unwrap();
+		assert_eq!(params.rounds, p::ROUNDS);
+		assert_eq!(params.c.len(), params.rounds);
+		assert_eq!(params.d.len(), params.rounds);
+	}
+
+	fn check_validate<F: AnemoiField>(mut params: AnemoiParams<F>) {
+		params.validate().unwrap();
+		params.alpha_inv = PowWindowTable::new(F::ALPHA_INV.wrapping_add(2));
+		assert!(params.validate().is_err());
+	}
+
+	#[test]
+	fn test_validate_alpha_inv() {
+		use params::{params32_l8 as p32, params128_l2 as p128};
+
+		check_validate(
+			prep_params::<BinaryField32b, { p32::L }, { p32::ROUNDS }>(
+				params::ALPHA_INV_32,
+				&p32::MDS,
+				&p32::C,
+				&p32::D,
+			)
+			.unwrap(),
+		);
+		check_validate(
+			prep_params::<BinaryField64b, { p::L }, { p::ROUNDS }>(
+				params::ALPHA_INV_64,
+				&p::MDS,
+				&p::C,
+				&p::D,
+			)
+			.unwrap(),
+		);
+		check_validate(
+			prep_params::<BinaryField128b, { p128::L }, { p128::ROUNDS }>(
+				params::ALPHA_INV_128,
+				&p128::MDS,
+				&p128::C,
+				&p128::D,
+			)
+			.unwrap(),
+		);
+	}
+
+	#[test]
+	fn test_open_flystel_permutation() {
+		test_circuit(|builder| {
+			let log_size = 2;
+			let mut params = prep_params::<BinaryField64b, { p::L }, { p::ROUNDS }>(
+				params::ALPHA_INV_64,
+				&p::MDS,
+				&p::C,
+				&p::D,
+			)?;
+			params.flystel = FlystelForm::Open;
+			let state_in = (0..params.t)
+				.map(|i| unconstrained::<BinaryField64b>(builder, format!("in[{i}]"), log_size))
+				.collect::<Result<Vec<OracleId>, _>>()?;
+			anemoi_permutation(builder, log_size, &state_in, &params)?;
+			Ok(vec![])
+		})
+		.unwrap();
+	}
+
+	#[test]
+	fn test_split_sbox_permutation() {
+		test_circuit(|builder| {
+			let log_size = 2;
+			let mut params = params_128_l2();
+			params.sbox = SboxGadget::Split;
+			let state_in = (0..params.t)
+				.map(|i| unconstrained::<BinaryField128b>(builder, format!("in[{i}]"), log_size))
+				.collect::<Result<Vec<OracleId>, _>>()?;
+			anemoi_permutation(builder, log_size, &state_in, &params)?;
+			Ok(vec![])
+		})
+		.unwrap();
+	}
+
+	#[test]
+	fn test_round_report() {
+		let mut params = params_128_l2();
+		// Per round: 6 linear-layer columns and 7 closed S-box columns per lane, with 6 linear
+		// checks and 7 S-box checks per lane (three of them x^7).
+		let direct = round_report(&params).unwrap();
+		assert_eq!(
+			direct,
+			RoundReport {
+				committed_columns: 2 * 13,
+				constraints: 2 * 13,
+				max_degree: 7,
+			}
+		);
+
+		// Each x^7 check becomes three more columns and four degree-2 checks.
+		params.sbox = SboxGadget::Split;
+		let split = round_report(&params).unwrap();
+		assert_eq!(
+			split,
+			RoundReport {
+				committed_columns: direct.committed_columns + 2 * 3 * 3,
+				constraints: direct.constraints + 2 * 3 * 3,
+				max_degree: 2,
+			}
+		);
+
+		params.flystel = FlystelForm::Open;
+		assert!(round_report(&params).is_err());
+	}
+}
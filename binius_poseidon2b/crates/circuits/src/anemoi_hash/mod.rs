@@ -6,3 +6,74 @@ pub mod anemoi_64_8;
 pub mod anemoi_64_12;
 pub mod anemoi_128_4;
 pub mod anemoi_128_6;
+
+/// `(field bits, t)` pairs that have an Anemoi permutation.
+pub fn supported_instances() -> &'static [(u32, u32)] {
+	&[(32, 16), (32, 24), (64, 8), (64, 12), (128, 4), (128, 6)]
+}
+
+pub fn is_supported(field_bits: u32, t: u32) -> bool {
+	supported_instances().contains(&(field_bits, t))
+}
+
+#[cfg(test)]
+mod tests {
+	use anyhow::{Result, bail};
+	use binius_field::{BinaryField32b as B32, BinaryField64b as B64, BinaryField128b as B128};
+
+	use super::*;
+	use crate::{
+		builder::{ConstraintSystemBuilder, test_utils::test_circuit},
+		unconstrained::unconstrained,
+	};
+
+	fn build(
+		builder: &mut ConstraintSystemBuilder,
+		field_bits: u32,
+		t: u32,
+		log_size: usize,
+	) -> Result<()> {
+		macro_rules! build {
+			($fs:ty, $($module:ident),+) => {{
+				$(
+					let state_in = array_util::try_from_fn(|i| {
+						unconstrained::<$fs>(
+							builder,
+							format!("{}_in[{i}]", stringify!($module)),
+							log_size,
+						)
+					})?;
+					$module::permutation(builder, log_size, state_in)?;
+				)+
+			}};
+		}
+		match (field_bits, t) {
+			(32, 16) => build!(B32, anemoi_32_16),
+			(32, 24) => build!(B32, anemoi_32_24),
+			(64, 8) => build!(B64, anemoi_64_8),
+			(64, 12) => build!(B64, anemoi_64_12),
+			(128, 4) => build!(B128, anemoi_128_4),
+			(128, 6) => build!(B128, anemoi_128_6),
+			_ => bail!("no permutation for field_bits={field_bits}, t={t}"),
+		}
+		Ok(())
+	}
+
+	#[test]
+	fn test_supported_instances_build() {
+		test_circuit(|builder| {
+			for &(field_bits, t) in supported_instances() {
+				build(builder, field_bits, t, 1)?;
+			}
+			Ok(vec![])
+		})
+		.unwrap();
+	}
+
+	#[test]
+	fn test_is_supported() {
+		assert!(is_supported(64, 8));
+		assert!(!is_supported(64, 16));
+		assert!(!is_supported(256, 4));
+	}
+}
@@ -0,0 +1,231 @@
+// Copyright 2024-2025 Irreducible Inc.
+
+//! Merkle authentication path verification over the Poseidon2b BinaryField32b t=16 permutation.
+//!
+//! Two-to-one compression: the 16-element state is `left || right` (8 elements each) and the
+//! node is the first 8 elements of the permuted state, with no feed-forward. This is the layout of
+//! the native `poseidon2b_runner::Poseidon2b::compress` with `params_32_t16`, so roots computed
+//! off-circuit match the circuit. At every level the index bit picks which side the running node
+//! sits on: bit 0 puts it on the left, bit 1 on the right.
+
+use std::array;
+
+use anyhow::Result;
+use binius_core::oracle::OracleId;
+use binius_field::{BinaryField32b, Field, TowerField};
+use binius_math::{ArithCircuit, ArithExpr};
+
+use crate::{
+	builder::ConstraintSystemBuilder,
+	hades::poseidon2b_x7_32_512::{self, STATE_SIZE},
+};
+
+type B32 = BinaryField32b;
+
+pub const DIGEST_SIZE: usize = STATE_SIZE / 2;
+
+// bit * (bit + 1) == 0, i.e. the index column only holds 0 or 1.
+fn bit_constraint_expr<F: TowerField>() -> Result<ArithCircuit<F>> {
+	let bit = ArithExpr::Var(0);
+	Ok((bit.clone() * (bit + ArithExpr::Const(F::ONE))).into())
+}
+
+// left == cur + bit * (cur + sib)
+fn mux_constraint_expr<F: TowerField>() -> Result<ArithCircuit<F>> {
+	let bit = ArithExpr::Var(0);
+	let cur = ArithExpr::Var(1);
+	let sib = ArithExpr::Var(2);
+	let left = ArithExpr::Var(3);
+
+	Ok((left - (cur.clone() + bit * (cur + sib))).into())
+}
+
+fn compress(
+	builder: &mut ConstraintSystemBuilder,
+	log_size: usize,
+	left: [OracleId; DIGEST_SIZE],
+	right: [OracleId; DIGEST_SIZE],
+) -> Result<[OracleId; DIGEST_SIZE]> {
+	let state_in: [OracleId; STATE_SIZE] = array::from_fn(|i| {
+		if i < DIGEST_SIZE {
+			left[i]
+		} else {
+			right[i - DIGEST_SIZE]
+		}
+	});
+	let state_out = poseidon2b_x7_32_512::permutation(builder, log_size, state_in)?;
+	Ok(array::from_fn(|i| state_out[i]))
+}
+
+/// Recomputes the root from `leaf` and its authentication path, one compression per level.
+///
+/// `path[d]` is the sibling at depth `d` counted from the leaf, and `index_bits[d]` is a
+/// BinaryField32b column holding 0 or 1 that says whether the running node is the right child.
+/// Returns the root columns; the caller constrains them against the expected root.
+pub fn verify_path(
+	builder: &mut ConstraintSystemBuilder,
+	log_size: usize,
+	leaf: [OracleId; DIGEST_SIZE],
+	path: &[[OracleId; DIGEST_SIZE]],
+	index_bits: &[OracleId],
+) -> Result<[OracleId; DIGEST_SIZE]> {
+	anyhow::ensure!(
+		path.len() == index_bits.len(),
+		"merkle path has {} siblings but {} index bits",
+		path.len(),
+		index_bits.len()
+	);
+
+	path.iter()
+		.zip(index_bits)
+		.enumerate()
+		.try_fold(leaf, |cur, (depth, (&sib, &bit))| {
+			builder.push_namespace(format!("merkle level[{depth}]"));
+
+			let left = builder.add_committed_multiple::<DIGEST_SIZE>(
+				"left",
+				log_size,
+				BinaryField32b::TOWER_LEVEL,
+			);
+			// left + right == cur + sib, so the right input needs no commitment of its own.
+			let right: [OracleId; DIGEST_SIZE] = array::from_fn(|i| {
+				builder
+					.add_linear_combination(
+						format!("right_{}", i),
+						log_size,
+						[
+							(cur[i], Field::ONE),
+							(sib[i], Field::ONE),
+							(left[i], Field::ONE),
+						],
+					)
+					.unwrap()
+			});
+
+			builder.pop_namespace();
+
+			// Witness gen
+			if let Some(witness) = builder.witness() {
+				let bit_data = witness.get::<B32>(bit)?;
+				let bit_32b = bit_data.as_slice::<B32>();
+				let cur_data: [_; DIGEST_SIZE] =
+					array_util::try_from_fn(|i| witness.get::<B32>(cur[i]))?;
+				let cur_32b: [_; DIGEST_SIZE] = cur_data.map(|elem| elem.as_slice::<B32>());
+				let sib_data: [_; DIGEST_SIZE] =
+					array_util::try_from_fn(|i| witness.get::<B32>(sib[i]))?;
+				let sib_32b: [_; DIGEST_SIZE] = sib_data.map(|elem| elem.as_slice::<B32>());
+
+				let mut left = left.map(|id| witness.new_column::<B32>(id));
+				let left_32b: [&mut [BinaryField32b]; DIGEST_SIZE] =
+					left.each_mut().map(|elem| elem.as_mut_slice());
+
+				let mut right = right.map(|id| witness.new_column::<B32>(id));
+				let right_32b: [&mut [BinaryField32b]; DIGEST_SIZE] =
+					right.each_mut().map(|elem| elem.as_mut_slice());
+
+				for z in 0..1 << log_size {
+					for i in 0..DIGEST_SIZE {
+						left_32b[i][z] =
+							cur_32b[i][z] + bit_32b[z] * (cur_32b[i][z] + sib_32b[i][z]);
+						right_32b[i][z] = cur_32b[i][z] + sib_32b[i][z] + left_32b[i][z];
+					}
+				}
+			}
+
+			// zero check constraints
+			builder.assert_zero(format!("index_bit_{depth}"), [bit], bit_constraint_expr()?);
+			for i in 0..DIGEST_SIZE {
+				builder.assert_zero(
+					format!("mux_{depth}_{i}"),
+					[bit, cur[i], sib[i], left[i]],
+					mux_constraint_expr()?,
+				);
+			}
+
+			compress(builder, log_size, left, right)
+		})
+}
+
+#[cfg(test)]
+mod tests {
+
+	use binius_core::oracle::OracleId;
+	use binius_field::BinaryField32b;
+	use poseidon2b_runner::{Poseidon2b, params_32_t16};
+
+	use super::{B32, DIGEST_SIZE, verify_path};
+	use crate::{
+		builder::test_utils::test_circuit,
+		unconstrained::{fixed_u32, unconstrained},
+	};
+
+	const DEPTH: usize = 4;
+
+	#[test]
+	fn test_merkle_verify_path() {
+		test_circuit(|builder| {
+			let log_size = 4;
+			let leaf: [OracleId; DIGEST_SIZE] = std::array::from_fn(|i| {
+				unconstrained::<BinaryField32b>(builder, format!("leaf[{i}]"), log_size).unwrap()
+			});
+			let path: [[OracleId; DIGEST_SIZE]; DEPTH] = std::array::from_fn(|d| {
+				std::array::from_fn(|i| {
+					unconstrained::<BinaryField32b>(builder, format!("path[{d}][{i}]"), log_size)
+						.unwrap()
+				})
+			});
+			// Row z proves leaf index z, so every left/right combination of a depth-4 tree is used.
+			let index_bits: [OracleId; DEPTH] = std::array::from_fn(|d| {
+				let bits = (0..1u32 << log_size).map(|z| (z >> d) & 1).collect();
+				fixed_u32::<BinaryField32b>(builder, format!("index_bit[{d}]"), log_size, bits)
+					.unwrap()
+			});
+			let _root = verify_path(builder, log_size, leaf, &path, &index_bits).unwrap();
+			Ok(vec![])
+		})
+		.unwrap();
+	}
+
+	#[test]
+	fn test_merkle_root_matches_native_compress() {
+		test_circuit(|builder| {
+			let log_size = 2;
+			let leaf: [OracleId; DIGEST_SIZE] = std::array::from_fn(|i| {
+				unconstrained::<BinaryField32b>(builder, format!("leaf[{i}]"), log_size).unwrap()
+			});
+			let path: [[OracleId; DIGEST_SIZE]; DEPTH] = std::array::from_fn(|d| {
+				std::array::from_fn(|i| {
+					unconstrained::<BinaryField32b>(builder, format!("path[{d}][{i}]"), log_size)
+						.unwrap()
+				})
+			});
+			let index_bits: [OracleId; DEPTH] = std::array::from_fn(|d| {
+				let bits = (0..1u32 << log_size).map(|z| ((z * 5) >> d) & 1).collect();
+				fixed_u32::<BinaryField32b>(builder, format!("index_bit[{d}]"), log_size, bits)
+					.unwrap()
+			});
+			let root = verify_path(builder, log_size, leaf, &path, &index_bits).unwrap();
+
+			if let Some(witness) = builder.witness() {
+				let column =
+					|id: OracleId| witness.get::<B32>(id).unwrap().as_slice::<B32>().to_vec();
+				let perm = Poseidon2b::new(params_32_t16());
+				for z in 0..1 << log_size {
+					let mut node: Vec<B32> = leaf.iter().map(|&id| column(id)[z]).collect();
+					for (sib, &bit) in path.iter().zip(&index_bits) {
+						let sib: Vec<B32> = sib.iter().map(|&id| column(id)[z]).collect();
+						node = if column(bit)[z] == B32::new(1) {
+							perm.compress(&sib, &node)
+						} else {
+							perm.compress(&node, &sib)
+						};
+					}
+					let circuit_root: Vec<B32> = root.iter().map(|&id| column(id)[z]).collect();
+					assert_eq!(circuit_root, node, "row {z}");
+				}
+			}
+			Ok(vec![])
+		})
+		.unwrap();
+	}
+}
@@ -1,3 +1,6 @@
+pub mod merkle;
+pub mod periodic;
+pub mod sbox;
 pub mod poseidon2b_x7_32_512; 
 pub mod poseidon2b_x7_32_768; 
 pub mod poseidon2b_x7_64_512; 
@@ -11,4 +14,77 @@ pub mod poseidonb_x7_32_768;
 pub mod poseidonb_x7_64_512; 
 pub mod poseidonb_x7_64_768; 
 pub mod poseidonb_x7_128_512; 
-pub mod poseidonb_x7_128_768; 
\ No newline at end of file
+pub mod poseidonb_x7_128_768;
+
+/// `(field bits, t)` pairs that have a `poseidon2b_x7_*` and a `poseidonb_x7_*` permutation.
+/// Nothing wider than t=24 has a circuit yet; the native t=32 instance
+/// (`poseidon2b_runner::poseidon2b_32_t32_with_rounds`) is for research only.
+pub fn supported_instances() -> &'static [(u32, u32)] {
+	&[(32, 16), (32, 24), (64, 8), (64, 12), (128, 4), (128, 6)]
+}
+
+pub fn is_supported(field_bits: u32, t: u32) -> bool {
+	supported_instances().contains(&(field_bits, t))
+}
+
+#[cfg(test)]
+mod tests {
+	use anyhow::{Result, bail};
+	use binius_field::{BinaryField32b as B32, BinaryField64b as B64, BinaryField128b as B128};
+
+	use super::*;
+	use crate::{
+		builder::{ConstraintSystemBuilder, test_utils::test_circuit},
+		unconstrained::unconstrained,
+	};
+
+	fn build(
+		builder: &mut ConstraintSystemBuilder,
+		field_bits: u32,
+		t: u32,
+		log_size: usize,
+	) -> Result<()> {
+		macro_rules! build {
+			($fs:ty, $($module:ident),+) => {{
+				$(
+					let state_in = array_util::try_from_fn(|i| {
+						unconstrained::<$fs>(
+							builder,
+							format!("{}_in[{i}]", stringify!($module)),
+							log_size,
+						)
+					})?;
+					$module::permutation(builder, log_size, state_in)?;
+				)+
+			}};
+		}
+		match (field_bits, t) {
+			(32, 16) => build!(B32, poseidon2b_x7_32_512, poseidonb_x7_32_512),
+			(32, 24) => build!(B32, poseidon2b_x7_32_768, poseidonb_x7_32_768),
+			(64, 8) => build!(B64, poseidon2b_x7_64_512, poseidonb_x7_64_512),
+			(64, 12) => build!(B64, poseidon2b_x7_64_768, poseidonb_x7_64_768),
+			(128, 4) => build!(B128, poseidon2b_x7_128_512, poseidonb_x7_128_512),
+			(128, 6) => build!(B128, poseidon2b_x7_128_768, poseidonb_x7_128_768),
+			_ => bail!("no permutation for field_bits={field_bits}, t={t}"),
+		}
+		Ok(())
+	}
+
+	#[test]
+	fn test_supported_instances_build() {
+		test_circuit(|builder| {
+			for &(field_bits, t) in supported_instances() {
+				build(builder, field_bits, t, 1)?;
+			}
+			Ok(vec![])
+		})
+		.unwrap();
+	}
+
+	#[test]
+	fn test_is_supported() {
+		assert!(is_supported(64, 8));
+		assert!(!is_supported(64, 16));
+		assert!(!is_supported(256, 4));
+	}
+}
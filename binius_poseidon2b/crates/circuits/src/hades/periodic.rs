@@ -0,0 +1,84 @@
+//! Round-constant tables that repeat with a short period.
+
+/// A round-constant table kept as one cycle per lane, every lane sharing the same period.
+///
+/// Only [`PeriodicConstants::detect`] builds one, after checking that the table really repeats.
+/// None of the shipped `RC` tables do, so the permutations still index their flat tables.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeriodicConstants<R> {
+	n_rounds: usize,
+	period: usize,
+	cycle: Vec<Vec<R>>,
+}
+
+impl<R: Copy + PartialEq> PeriodicConstants<R> {
+	/// The shortest period shared by every lane of `table` (indexed `[lane][round]`), or `None`
+	/// when the table does not repeat at least once in full, i.e. has no period `p <= N / 2`.
+	pub fn detect<const N: usize>(table: &[[R; N]]) -> Option<Self> {
+		let period = (1..=N / 2).find(|&p| {
+			table
+				.iter()
+				.all(|lane| (p..N).all(|r| lane[r] == lane[r - p]))
+		})?;
+		Some(Self {
+			n_rounds: N,
+			period,
+			cycle: table.iter().map(|lane| lane[..period].to_vec()).collect(),
+		})
+	}
+
+	pub fn period(&self) -> usize {
+		self.period
+	}
+
+	/// The constant for `lane` in round `round`.
+	pub fn get(&self, lane: usize, round: usize) -> R {
+		assert!(round < self.n_rounds, "round {round} out of {}", self.n_rounds);
+		self.cycle[lane][round % self.period]
+	}
+
+	/// The flat `[lane][round]` table this was detected from.
+	pub fn expand(&self) -> Vec<Vec<R>> {
+		(0..self.cycle.len())
+			.map(|lane| (0..self.n_rounds).map(|r| self.get(lane, r)).collect())
+			.collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::PeriodicConstants;
+
+	#[test]
+	fn test_periodic_table_expands_to_flat() {
+		let cycles: [&[u128]; 3] = [&[1, 2, 3], &[7, 7, 0], &[5, 4, 3]];
+		let table: [[u128; 20]; 3] =
+			std::array::from_fn(|lane| std::array::from_fn(|r| cycles[lane][r % 3]));
+
+		let periodic = PeriodicConstants::detect(&table).unwrap();
+		assert_eq!(periodic.period(), 3);
+		assert_eq!(periodic.expand(), table.map(|lane| lane.to_vec()).to_vec());
+	}
+
+	#[test]
+	fn test_lanes_share_one_period() {
+		// Lane 0 repeats every 2 rounds and lane 1 every 3, so the table repeats every 6.
+		let table: [[u32; 12]; 2] = [
+			std::array::from_fn(|r| (r % 2) as u32),
+			std::array::from_fn(|r| (r % 3) as u32),
+		];
+		assert_eq!(PeriodicConstants::detect(&table).unwrap().period(), 6);
+	}
+
+	#[test]
+	fn test_non_periodic_table_is_flagged() {
+		let mut table: [[u64; 12]; 2] = [std::array::from_fn(|r| (r % 4) as u64); 2];
+		assert!(PeriodicConstants::detect(&table).is_some());
+		table[1][9] ^= 1;
+		assert!(PeriodicConstants::detect(&table).is_none());
+
+		// Equal first and last constants are not a cycle that repeats in full.
+		let table: [[u64; 5]; 1] = [[1, 2, 3, 4, 1]];
+		assert!(PeriodicConstants::detect(&table).is_none());
+	}
+}
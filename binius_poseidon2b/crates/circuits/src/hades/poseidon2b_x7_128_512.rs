@@ -5,11 +5,13 @@
 use std::array;
 
 use anyhow::Result;
-use binius_core::{oracle::OracleId, transparent::constant::Constant};
+use binius_core::oracle::OracleId;
 use binius_field::{BinaryField128b, Field, PackedField, TowerField,};
-use binius_math::{ArithCircuit, ArithExpr};
 
-use crate::builder::{ConstraintSystemBuilder, types::F};
+use crate::{
+	builder::{ConstraintSystemBuilder, types::F},
+	hades::sbox::{self, SboxGadget},
+};
 
 type B128 = BinaryField128b;
 const P_ROUNDS: usize = 58;
@@ -18,6 +20,11 @@ const N_ROUNDS: usize = F_ROUNDS + P_ROUNDS;
 
 const STATE_SIZE: usize = 4; 
 
+// The first and last `F_ROUNDS / 2` rounds are full, the `P_ROUNDS` between them partial.
+fn is_full_round(r: usize) -> bool {
+	r < F_ROUNDS / 2 || r >= F_ROUNDS / 2 + P_ROUNDS
+}
+
 fn plain_permutation(state: &mut [B128; STATE_SIZE], n_rounds: usize) {
 	// initial mds matrix mult
 	let mds_input = state.clone();
@@ -31,7 +38,7 @@ fn plain_permutation(state: &mut [B128; STATE_SIZE], n_rounds: usize) {
 	}
 	//full and partial rounds
 	for r in 0..n_rounds {
-		if r < F_ROUNDS / 2 || r >= F_ROUNDS / 2 + P_ROUNDS {
+		if is_full_round(r) {
 			//  Full (external) rounds
 			for i in 0..state.len() {
 				//rc add
@@ -78,11 +85,16 @@ pub fn permutation(
 ) -> Result<[OracleId; STATE_SIZE]> {
 	println!("Number of rounds: {}", F_ROUNDS + P_ROUNDS);
 
+	// Round constants are public, so each round's constants are folded into the offset of the
+	// linear combination that produces its input: every round starts from `state + rc` and needs
+	// no constant or `add_rc` oracles of its own.
+	let rc_0 = round_rc(RC, 0);
 	let round_0_input: [OracleId; STATE_SIZE] = array::from_fn(|row| {
 		builder
-			.add_linear_combination(
+			.add_linear_combination_with_offset(
 				format!("mds_out_full_{}", row),
 				log_size,
+				F::from(rc_0[row]),
 				MDS_FULL[row]
 					.iter()
 					.enumerate()
@@ -95,6 +107,7 @@ pub fn permutation(
 		let perm_in_data_owned: [_; STATE_SIZE] =
 			array_util::try_from_fn(|i| witness.get::<B128>(p_in[i]))?;
 		let perm_in_data: [_; STATE_SIZE] = perm_in_data_owned.map(|elem| elem.as_slice::<B128>());
+		let mds_full = MDS_FULL.map(|row| row.map(B128::new));
 		let mut round_0_input_data: [_; STATE_SIZE] =
 			round_0_input.map(|id| witness.new_column::<B128>(id));
 		let round_0_input_128b = round_0_input_data
@@ -105,9 +118,9 @@ pub fn permutation(
 			for i in 0..STATE_SIZE {
 				let mut mds_out_curr = B128::ZERO;
 				for j in 0..STATE_SIZE {
-					mds_out_curr += B128::new(MDS_FULL[i][j] as u128) * perm_in_data[j][z];
+					mds_out_curr += mds_full[i][j] * perm_in_data[j][z];
 				}
-				round_0_input_128b[i][z] = mds_out_curr;
+				round_0_input_128b[i][z] = mds_out_curr + rc_0[i];
 			}
 		}
 	}
@@ -169,16 +182,16 @@ const MDS_PARTIAL: [[u128; STATE_SIZE]; STATE_SIZE] = [
 [0x00000001, 0x00000001, 0x00000001, 0x800],
 ];
 
-fn x7_constraint_expr<F: TowerField>() -> Result<ArithCircuit<F>> {
-	let x = ArithExpr::Var(0);
-	let x7 = ArithExpr::Var(1);
-
-	let input_pow2 = x.clone()*x.clone();
-	let input_pow4 = input_pow2.clone()* input_pow2.clone();
-	let input_pow6 = input_pow2 * input_pow4;
-	let input_pow7 = input_pow6 * x.clone();
-
-	Ok((x7.clone() - input_pow7).into())
+// Constants added to the state at the start of round `round_i`: all of them in a full round, only
+// the first in a partial round, and none after the last round.
+fn round_rc(round_constants: [[u128; N_ROUNDS]; STATE_SIZE], round_i: usize) -> [B128; STATE_SIZE] {
+	array::from_fn(|i| {
+		if round_i < N_ROUNDS && (is_full_round(round_i) || i == 0) {
+			B128::new(round_constants[i][round_i])
+		} else {
+			B128::ZERO
+		}
+	})
 }
 
 fn full_round(
@@ -190,36 +203,18 @@ fn full_round(
 ) -> Result<[OracleId; STATE_SIZE]>
 where {
 	builder.push_namespace(format!("full round[{round_i}]"));
-	let full_round_consts: [OracleId; STATE_SIZE] = array::from_fn(|i| {
-		builder
-			.add_transparent(
-				format!("full_round_const{}", i),
-				Constant::new(log_size, B128::new(round_constants[i][round_i])),
-			)
-			.unwrap()
-	});
-
-	let add_rc: [OracleId; STATE_SIZE] = array::from_fn(|row| {
-		builder
-			.add_linear_combination(
-				format!("add_rc_full_{}", row),
-				log_size,
-				[
-					(state_in[row], Field::ONE),
-					(full_round_consts[row], Field::ONE),
-				],
-			)
-			.unwrap()
-	});
+	// `state_in` already includes this round's constants; the next round's go into `mds_out`.
+	let next_rc = round_rc(round_constants, round_i + 1);
 
 	let s_box_out =
 		builder.add_committed_multiple::<STATE_SIZE>("sbox_out_full", log_size, B128::TOWER_LEVEL);
 
 	let mds_out: [OracleId; STATE_SIZE] = array::from_fn(|row| {
 		builder
-			.add_linear_combination(
+			.add_linear_combination_with_offset(
 				format!("mds_out_full_{}", row),
 				log_size,
+				F::from(next_rc[row]),
 				MDS_FULL[row]
 					.iter()
 					.enumerate()
@@ -236,15 +231,6 @@ where {
 			array_util::try_from_fn(|i| witness.get::<B128>(state_in[i]))?;
 		let state_in_u128: [_; STATE_SIZE] = state_in.map(|elem| elem.as_slice::<B128>());
 
-		let mut full_round_consts = full_round_consts.map(|id| witness.new_column::<B128>(id));
-		let full_round_consts_128b = full_round_consts
-			.each_mut()
-			.map(|elem| elem.as_mut_slice::<B128>());
-
-		let mut add_rc = add_rc.map(|id| witness.new_column::<B128>(id));
-		let add_rc_128b: [&mut [B128]; STATE_SIZE] =
-			add_rc.each_mut().map(|elem| elem.as_mut_slice());
-
 		let mut s_box_out = s_box_out.map(|id| witness.new_column::<B128>(id));
 		let s_box_out_128b: [&mut [B128]; STATE_SIZE] =
 			s_box_out.each_mut().map(|elem| elem.as_mut_slice());
@@ -253,20 +239,9 @@ where {
 		let mds_out_128b: [&mut [B128]; STATE_SIZE] =
 			mds_out.each_mut().map(|elem| elem.as_mut_slice());
 
-		// Fill in constants
-		for i in 0..STATE_SIZE {
-			full_round_consts_128b[i]
-				.iter_mut()
-				.for_each(|rc| *rc = B128::new(round_constants[i][round_i]));
-		}
-
 		for z in 0..1 << log_size {
-			for i in 0..STATE_SIZE {
-				add_rc_128b[i][z] = state_in_u128[i][z] + full_round_consts_128b[i][z];
-			}
-
 			for i in 0..STATE_SIZE {			
-				s_box_out_128b[i][z] = add_rc_128b[i][z] * add_rc_128b[i][z]*add_rc_128b[i][z]*add_rc_128b[i][z]*add_rc_128b[i][z]*add_rc_128b[i][z]*add_rc_128b[i][z];
+				s_box_out_128b[i][z] = state_in_u128[i][z] * state_in_u128[i][z]*state_in_u128[i][z]*state_in_u128[i][z]*state_in_u128[i][z]*state_in_u128[i][z]*state_in_u128[i][z];
 
 			}
 
@@ -275,18 +250,21 @@ where {
 				for j in 0..STATE_SIZE {
 					mds_out_curr += B128::new(MDS_FULL[i][j] as u128) * s_box_out_128b[j][z];
 				}
-				mds_out_128b[i][z] = mds_out_curr;
+				mds_out_128b[i][z] = mds_out_curr + next_rc[i];
 			}
 		}
 	}
 
 	// zero check constraints
 	for s in 0..STATE_SIZE {
-		builder.assert_zero(
+		sbox::add_x7_constraints::<B128>(
+			builder,
+			log_size,
 			format!("x7_{s}"),
-			[add_rc[s], s_box_out[s]],
-			x7_constraint_expr()?,
-		);
+			state_in[s],
+			s_box_out[s],
+			SboxGadget::Direct,
+		)?;
 	}
 
 	Ok(mds_out)
@@ -301,30 +279,18 @@ fn partial_round(
 ) -> Result<[OracleId; STATE_SIZE]>
 where {
 	builder.push_namespace(format!("round[{round_i}]"));
-
-	let partial_round_const: OracleId = builder
-		.add_transparent(
-			"add_rc_partial",
-			Constant::new(log_size, B128::new(round_constants[0][round_i])),
-		)
-		.unwrap();
-
-	let add_rc: OracleId = builder
-		.add_linear_combination(
-			format!("add_rc_partial_0"),
-			log_size,
-			[(state_in[0], Field::ONE), (partial_round_const, Field::ONE)],
-		)
-		.unwrap();
+	// `state_in[0]` already includes this round's constant; the next round's go into `mds_out`.
+	let next_rc = round_rc(round_constants, round_i + 1);
 
 	let s_box_out: OracleId =
 		builder.add_committed("sbox_out_partial", log_size, B128::TOWER_LEVEL);
 
 	let mds_out: [OracleId; STATE_SIZE] = array::from_fn(|row| {
 		builder
-			.add_linear_combination(
+			.add_linear_combination_with_offset(
 				format!("mds_out_partial_{}", row),
 				log_size,
+				F::from(next_rc[row]),
 				MDS_PARTIAL[row].iter().enumerate().map(|(i, &elem)| {
 					if i == 0 {
 						(s_box_out, F::from(B128::new(elem as u128)))
@@ -344,12 +310,6 @@ where {
 		let state_in: [_; STATE_SIZE] =
 			array_util::try_from_fn(|i| witness.get::<B128>(state_in[i]))?;
 		let state_in_u128: [_; STATE_SIZE] = state_in.map(|elem| elem.as_slice::<B128>());
-		let mut partial_round_const = witness.new_column::<B128>(partial_round_const);
-		let partial_round_const_128b = partial_round_const.as_mut_slice::<B128>();
-
-		let mut add_rc = witness.new_column::<B128>(add_rc);
-		let add_rc_128b: &mut [B128] = add_rc.as_mut_slice();
-
 		let mut s_box_out = witness.new_column::<B128>(s_box_out);
 		let s_box_out_128b: &mut [B128] = s_box_out.as_mut_slice();
 
@@ -357,14 +317,8 @@ where {
 		let mds_out_128b: [&mut [B128]; STATE_SIZE] =
 			mds_out.each_mut().map(|elem| elem.as_mut_slice());
 
-		// Fill in constants
-		partial_round_const_128b
-			.iter_mut()
-			.for_each(|rc| *rc = B128::new(round_constants[0][round_i]));
 		for z in 0..1 << log_size {
-			add_rc_128b[z] = state_in_u128[0][z] + partial_round_const_128b[z];
-			
-			s_box_out_128b[z] = add_rc_128b[z] * add_rc_128b[z]*add_rc_128b[z]*add_rc_128b[z]*add_rc_128b[z]*add_rc_128b[z]*add_rc_128b[z];
+			s_box_out_128b[z] = state_in_u128[0][z] * state_in_u128[0][z]*state_in_u128[0][z]*state_in_u128[0][z]*state_in_u128[0][z]*state_in_u128[0][z]*state_in_u128[0][z];
 			let mut input_mds = [B128::ZERO; STATE_SIZE];
 			input_mds[0] = s_box_out_128b[z];
 
@@ -378,17 +332,20 @@ where {
 				for j in 0..STATE_SIZE {
 					mds_out_curr += B128::new(MDS_PARTIAL[i][j] as u128) * input_mds[j];
 				}
-				mds_out_128b[i][z] = mds_out_curr;
+				mds_out_128b[i][z] = mds_out_curr + next_rc[i];
 			}
 		}
 	}
 
 	// zero check constraints
-	builder.assert_zero(
+	sbox::add_x7_constraints::<B128>(
+		builder,
+		log_size,
 		format!("x7_0_partial"),
-		[add_rc, s_box_out],
-		x7_constraint_expr()?,
-	);
+		state_in[0],
+		s_box_out,
+		SboxGadget::Direct,
+	)?;
 
 	Ok(mds_out)
 }
@@ -397,14 +354,40 @@ where {
 mod tests {
 
 	use binius_core::oracle::OracleId;
-	use binius_field::BinaryField128b;
+	use binius_field::{BinaryField128b, Field, PackedField};
+	use rand::{SeedableRng, rngs::StdRng};
 
-	use super::permutation;
+	use super::{B128, RC, permutation};
 	use crate::{
 		builder::test_utils::test_circuit,
-		hades::poseidon2b_x7_128_512::STATE_SIZE,
+		hades::{periodic::PeriodicConstants, poseidon2b_x7_128_512::STATE_SIZE},
 		unconstrained::unconstrained,
 	};
+
+	// The lanes only take 8 distinct values, but no lane repeats with a period of 33 rounds or
+	// less, so the round functions keep indexing the flat table. If `RC` is ever regenerated with a
+	// cycle, this fails and the witness can switch to `PeriodicConstants`.
+	#[test]
+	fn test_rc_is_not_periodic() {
+		assert!(PeriodicConstants::detect(&RC).is_none());
+	}
+
+	// The witness and `plain_permutation` compute x^7 as a seven-fold product or `.pow(7)`, and
+	// `sbox::x7_constraint_expr` as x^6 * x with x^6 = x^2 * x^4; all of them must agree.
+	#[test]
+	fn test_sbox_power_forms_agree() {
+		let mut rng = StdRng::seed_from_u64(0);
+		let samples = (0..256).map(|_| <B128 as Field>::random(&mut rng));
+		for x in [B128::ZERO, B128::ONE].into_iter().chain(samples) {
+			let product = x * x * x * x * x * x * x;
+			let x2 = x.square();
+			let x6 = x2 * x2.square();
+			assert_eq!(PackedField::pow(x, 7), product, "x = {x}");
+			assert_eq!(Field::pow(&x, [7]), product, "x = {x}");
+			assert_eq!(x6 * x, product, "x = {x}");
+		}
+	}
+
 	#[test]
 	fn test_poseidon2b() {
 		test_circuit(|builder| {
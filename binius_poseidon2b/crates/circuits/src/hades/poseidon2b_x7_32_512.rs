@@ -5,21 +5,48 @@
 use std::array;
 
 use anyhow::Result;
-use binius_core::{oracle::OracleId, transparent::constant::Constant};
+use binius_core::oracle::OracleId;
 use binius_field::{BinaryField32b, Field, PackedField, TowerField};
-use binius_math::{ArithCircuit, ArithExpr};
 
-use crate::builder::{ConstraintSystemBuilder, types::F};
+use crate::{
+	builder::{ConstraintSystemBuilder, types::F},
+	hades::sbox::{self, SboxGadget},
+};
 
 type B32 = BinaryField32b;
 const P_ROUNDS: usize = 15;
 const F_ROUNDS: usize = 10;
 const N_ROUNDS: usize = F_ROUNDS + P_ROUNDS;
 
-const STATE_SIZE: usize = 16;
+pub const STATE_SIZE: usize = 16;
+
+// The first and last `F_ROUNDS / 2` rounds are full, the `P_ROUNDS` between them partial.
+fn is_full_round(r: usize) -> bool {
+	r < F_ROUNDS / 2 || r >= F_ROUNDS / 2 + P_ROUNDS
+}
 
 fn plain_permutation(state: &mut [BinaryField32b; STATE_SIZE], n_rounds: usize) {
-	// initial mds matrix mult
+	plain_initial_mds(state);
+	for r in 0..n_rounds {
+		plain_round(state, r);
+	}
+}
+
+/// Every intermediate state of [`plain_permutation`] over all `N_ROUNDS`: entry 0 follows the
+/// initial MDS and entry `r + 1` follows round `r`. This is the layout of the native
+/// `poseidon2b_runner::Poseidon2b::permute_trace` (with `params_32_t16`), so the two can be diffed
+/// round by round when a witness and the reference disagree.
+pub fn plain_trace(mut state: [BinaryField32b; STATE_SIZE]) -> Vec<[BinaryField32b; STATE_SIZE]> {
+	plain_initial_mds(&mut state);
+	let mut trace = vec![state];
+	for r in 0..N_ROUNDS {
+		plain_round(&mut state, r);
+		trace.push(state);
+	}
+	trace
+}
+
+fn plain_initial_mds(state: &mut [BinaryField32b; STATE_SIZE]) {
 	let mds_input = state.clone();
 	for i in 0..state.len() {
 		// mds matrix mult
@@ -29,46 +56,45 @@ fn plain_permutation(state: &mut [BinaryField32b; STATE_SIZE], n_rounds: usize)
 		}
 		state[i] = mds_out_curr;
 	}
+}
 
-	//full and partial rounds
-	for r in 0..n_rounds {
-		if r < F_ROUNDS / 2 || r >= F_ROUNDS / 2 + P_ROUNDS {
-			//  Full (external) rounds
-			for i in 0..state.len() {
-				//rc add
-				state[i] = state[i] + BinaryField32b::new(RC[i][r]);
-			}
+fn plain_round(state: &mut [BinaryField32b; STATE_SIZE], r: usize) {
+	if is_full_round(r) {
+		//  Full (external) rounds
+		for i in 0..state.len() {
+			//rc add
+			state[i] = state[i] + BinaryField32b::new(RC[i][r]);
+		}
 
-			for i in 0..state.len() {
-				//sbox
-				state[i] = state[i].pow(7);
-			}
+		for i in 0..state.len() {
+			//sbox
+			state[i] = state[i].pow(7);
+		}
 
-			let mds_input = state.clone();
-			for i in 0..state.len() {
-				// mds matrix mult
-				let mut mds_out_curr = B32::ZERO;
-				for j in 0..STATE_SIZE {
-					mds_out_curr += BinaryField32b::new(MDS_FULL[i][j] as u32) * mds_input[j];
-				}
-				state[i] = mds_out_curr;
+		let mds_input = state.clone();
+		for i in 0..state.len() {
+			// mds matrix mult
+			let mut mds_out_curr = B32::ZERO;
+			for j in 0..STATE_SIZE {
+				mds_out_curr += BinaryField32b::new(MDS_FULL[i][j] as u32) * mds_input[j];
 			}
-		} else {
-			//  Partial (internal) rounds
-			// rc add
-			state[0] = state[0] + BinaryField32b::new(RC[0][r]);
-			//sbox
-			state[0] = state[0].pow(7);
+			state[i] = mds_out_curr;
+		}
+	} else {
+		//  Partial (internal) rounds
+		// rc add
+		state[0] = state[0] + BinaryField32b::new(RC[0][r]);
+		//sbox
+		state[0] = state[0].pow(7);
+		// mds matrix mult
+		let mds_input = state.clone();
+		for i in 0..state.len() {
 			// mds matrix mult
-			let mds_input = state.clone();
-			for i in 0..state.len() {
-				// mds matrix mult
-				let mut mds_out_curr = B32::ZERO;
-				for j in 0..STATE_SIZE {
-					mds_out_curr += BinaryField32b::new(MDS_PARTIAL[i][j] as u32) * mds_input[j];
-				}
-				state[i] = mds_out_curr;
+			let mut mds_out_curr = B32::ZERO;
+			for j in 0..STATE_SIZE {
+				mds_out_curr += BinaryField32b::new(MDS_PARTIAL[i][j] as u32) * mds_input[j];
 			}
+			state[i] = mds_out_curr;
 		}
 	}
 }
@@ -77,26 +103,107 @@ pub fn permutation(
 	builder: &mut ConstraintSystemBuilder,
 	log_size: usize,
 	p_in: [OracleId; STATE_SIZE],
+) -> Result<[OracleId; STATE_SIZE]> {
+	permutation_with_init(builder, log_size, p_in.map(LaneInit::column))
+}
+
+/// One lane of the permutation input given as `offset + sum(coeff * column)` instead of a column.
+#[derive(Debug, Clone, Default)]
+pub struct LaneInit {
+	pub terms: Vec<(OracleId, BinaryField32b)>,
+	pub offset: BinaryField32b,
+}
+
+impl LaneInit {
+	pub fn column(id: OracleId) -> Self {
+		Self {
+			terms: vec![(id, BinaryField32b::ONE)],
+			offset: BinaryField32b::ZERO,
+		}
+	}
+
+	// Looks the term columns up once, so evaluating a row is only the multiply-adds.
+	fn resolve<'a>(&self, columns: &[(OracleId, &'a [B32])]) -> ResolvedLane<'a> {
+		let terms = self
+			.terms
+			.iter()
+			.map(|&(id, coeff)| {
+				let (_, values) = columns
+					.iter()
+					.find(|(col, _)| *col == id)
+					.expect("every term column is loaded");
+				(*values, coeff)
+			})
+			.collect();
+		ResolvedLane {
+			terms,
+			offset: self.offset,
+		}
+	}
+}
+
+struct ResolvedLane<'a> {
+	terms: Vec<(&'a [B32], B32)>,
+	offset: B32,
+}
+
+impl ResolvedLane<'_> {
+	fn eval(&self, z: usize) -> B32 {
+		self.terms
+			.iter()
+			.fold(self.offset, |acc, &(values, coeff)| acc + coeff * values[z])
+	}
+}
+
+/// [`permutation`] on a state whose lanes are linear combinations of existing columns.
+///
+/// The lanes are folded into the initial MDS layer, so they never get columns of their own. A
+/// sponge gadget uses this to absorb a block as `previous output + block` (and the padding as an
+/// offset) between permutations without committing the absorbed state.
+pub fn permutation_with_init(
+	builder: &mut ConstraintSystemBuilder,
+	log_size: usize,
+	init: [LaneInit; STATE_SIZE],
 ) -> Result<[OracleId; STATE_SIZE]> {
 	println!("Number of rounds: {}", F_ROUNDS + P_ROUNDS);
 
+	// Round constants are public, so each round's constants are folded into the offset of the
+	// linear combination that produces its input: every round starts from `state + rc` and needs
+	// no constant or `add_rc` oracles of its own.
+	let rc_0 = round_rc(RC, 0);
 	let round_0_input: [OracleId; STATE_SIZE] = array::from_fn(|row| {
+		let mut terms: Vec<(OracleId, B32)> = Vec::new();
+		let mut offset = rc_0[row];
+		for (lane, &elem) in init.iter().zip(&MDS_FULL[row]) {
+			let m = BinaryField32b::from(elem);
+			offset += m * lane.offset;
+			for &(id, coeff) in &lane.terms {
+				match terms.iter_mut().find(|(t, _)| *t == id) {
+					Some((_, c)) => *c += m * coeff,
+					None => terms.push((id, m * coeff)),
+				}
+			}
+		}
 		builder
-			.add_linear_combination(
+			.add_linear_combination_with_offset(
 				format!("mds_out_full_{}", row),
 				log_size,
-				MDS_FULL[row]
-					.iter()
-					.enumerate()
-					.map(|(i, &elem)| (p_in[i], F::from(BinaryField32b::from(elem as u32)))),
+				F::from(offset),
+				terms.into_iter().map(|(id, c)| (id, F::from(c))),
 			)
 			.unwrap()
 	});
 
 	if let Some(witness) = builder.witness() {
-		let perm_in_data_owned: [_; STATE_SIZE] =
-			array_util::try_from_fn(|i| witness.get::<B32>(p_in[i]))?;
-		let perm_in_data: [_; STATE_SIZE] = perm_in_data_owned.map(|elem| elem.as_slice::<B32>());
+		let init_data = init_columns(&init, |id| witness.get::<B32>(id))?;
+		let init_32b: Vec<(OracleId, &[B32])> = init_data
+			.iter()
+			.map(|(id, data)| (*id, data.as_slice::<B32>()))
+			.collect();
+		let resolved: [_; STATE_SIZE] = array::from_fn(|j| init[j].resolve(&init_32b));
+		let mds_full = MDS_FULL.map(|row| row.map(BinaryField32b::new));
+		// The linear combinations above only define these columns: the witness builder never
+		// evaluates an oracle, and `validate_witness` compares this data against the definition.
 		let mut round_0_input_data: [_; STATE_SIZE] =
 			round_0_input.map(|id| witness.new_column::<B32>(id));
 		let round_0_input_32b = round_0_input_data
@@ -104,12 +211,13 @@ pub fn permutation(
 			.map(|elem| elem.as_mut_slice::<B32>());
 
 		for z in 0..1 << log_size {
+			let lanes: [B32; STATE_SIZE] = array::from_fn(|j| resolved[j].eval(z));
 			for i in 0..STATE_SIZE {
 				let mut mds_out_curr = B32::ZERO;
 				for j in 0..STATE_SIZE {
-					mds_out_curr += BinaryField32b::new(MDS_FULL[i][j] as u32) * perm_in_data[j][z];
+					mds_out_curr += mds_full[i][j] * lanes[j];
 				}
-				round_0_input_32b[i][z] = mds_out_curr;
+				round_0_input_32b[i][z] = mds_out_curr + rc_0[i];
 			}
 		}
 	}
@@ -130,16 +238,19 @@ pub fn permutation(
 
 	#[cfg(debug_assertions)]
 	if let Some(witness) = builder.witness() {
-		let p_in_data: [_; STATE_SIZE] =
-			array_util::try_from_fn(|i| witness.get::<B32>(p_in[i])).unwrap();
-		let p_in_32b: [_; STATE_SIZE] = p_in_data.map(|elem| elem.as_slice::<B32>());
+		let init_data = init_columns(&init, |id| witness.get::<B32>(id)).unwrap();
+		let init_32b: Vec<(OracleId, &[B32])> = init_data
+			.iter()
+			.map(|(id, data)| (*id, data.as_slice::<B32>()))
+			.collect();
 		let p_out_data: [_; STATE_SIZE] =
 			array_util::try_from_fn(|i| witness.get::<B32>(perm_out[i])).unwrap();
 		let p_out_32b: [_; STATE_SIZE] = p_out_data.map(|elem| elem.as_slice::<B32>());
+		let resolved: [_; STATE_SIZE] = array::from_fn(|i| init[i].resolve(&init_32b));
 		for z in 0..1 << log_size {
 			let expected_out: [B32; STATE_SIZE] = array::from_fn(|s| p_out_32b[s][z]);
 			let mut state_in: [BinaryField32b; STATE_SIZE] =
-				std::array::from_fn(|i| p_in_32b[i][z]);
+				std::array::from_fn(|i| resolved[i].eval(z));
 			plain_permutation(&mut state_in, N_ROUNDS);
 			assert_eq!(state_in, expected_out);
 		}
@@ -147,6 +258,20 @@ pub fn permutation(
 	Ok(perm_out)
 }
 
+// Loads every column the lanes refer to, once each.
+fn init_columns<T, E>(
+	init: &[LaneInit; STATE_SIZE],
+	mut get: impl FnMut(OracleId) -> Result<T, E>,
+) -> Result<Vec<(OracleId, T)>, E> {
+	let mut columns: Vec<(OracleId, T)> = Vec::new();
+	for &(id, _) in init.iter().flat_map(|lane| &lane.terms) {
+		if !columns.iter().any(|(col, _)| *col == id) {
+			columns.push((id, get(id)?));
+		}
+	}
+	Ok(columns)
+}
+
 #[rustfmt::skip]
 const RC: [[u32; N_ROUNDS]; STATE_SIZE] = [
 [0x7684, 0x5f2f, 0x1f28, 0x70d5, 0x782c, 0x5c52, 0x51b9, 0x621b, 0x4eaa, 0x2106, 0x4248, 0x22a5, 0x4145, 0x1ea1, 0x51dc, 0x49b3, 0x320f, 0x7acf, 0x2c4b, 0x5b76, 0x152c, 0x473f, 0x5301, 0x4b2b, 0x821, ],
@@ -207,16 +332,16 @@ const MDS_PARTIAL: [[u32; STATE_SIZE]; STATE_SIZE] = [
 [0x1, 0x1, 0x1, 0x1, 0x1, 0x1, 0x1, 0x1, 0x1, 0x1, 0x1, 0x1, 0x1, 0x1, 0x1, 0x3],
 ];
 
-fn x7_constraint_expr<F: TowerField>() -> Result<ArithCircuit<F>> {
-	let x = ArithExpr::Var(0);
-	let x7 = ArithExpr::Var(1);
-
-	let input_pow2 = x.clone().pow(2);
-	let input_pow4 = input_pow2.clone().pow(2);
-	let input_pow6 = input_pow2 * input_pow4;
-	let input_pow7 = input_pow6 * x.clone();
-
-	Ok((x7.clone() - input_pow7).into())
+// Constants added to the state at the start of round `round_i`: all of them in a full round, only
+// the first in a partial round, and none after the last round.
+fn round_rc(round_constants: [[u32; N_ROUNDS]; STATE_SIZE], round_i: usize) -> [B32; STATE_SIZE] {
+	array::from_fn(|i| {
+		if round_i < N_ROUNDS && (is_full_round(round_i) || i == 0) {
+			B32::new(round_constants[i][round_i])
+		} else {
+			B32::ZERO
+		}
+	})
 }
 
 fn full_round(
@@ -228,28 +353,8 @@ fn full_round(
 ) -> Result<[OracleId; STATE_SIZE]>
 where {
 	builder.push_namespace(format!("full round[{round_i}]"));
-	// let state_size = state_in.len();
-	let full_round_consts: [OracleId; STATE_SIZE] = array::from_fn(|i| {
-		builder
-			.add_transparent(
-				format!("full_round_const{}", i),
-				Constant::new(log_size, BinaryField32b::new(round_constants[i][round_i])),
-			)
-			.unwrap()
-	});
-
-	let add_rc: [OracleId; STATE_SIZE] = array::from_fn(|row| {
-		builder
-			.add_linear_combination(
-				format!("add_rc_full_{}", row),
-				log_size,
-				[
-					(state_in[row], Field::ONE),
-					(full_round_consts[row], Field::ONE),
-				],
-			)
-			.unwrap()
-	});
+	// `state_in` already includes this round's constants; the next round's go into `mds_out`.
+	let next_rc = round_rc(round_constants, round_i + 1);
 
 	let s_box_out = builder.add_committed_multiple::<STATE_SIZE>(
 		"sbox_out_full",
@@ -259,9 +364,10 @@ where {
 
 	let mds_out: [OracleId; STATE_SIZE] = array::from_fn(|row| {
 		builder
-			.add_linear_combination(
+			.add_linear_combination_with_offset(
 				format!("mds_out_full_{}", row),
 				log_size,
+				F::from(next_rc[row]),
 				MDS_FULL[row]
 					.iter()
 					.enumerate()
@@ -278,15 +384,6 @@ where {
 			array_util::try_from_fn(|i| witness.get::<B32>(state_in[i]))?;
 		let state_in_u32: [_; STATE_SIZE] = state_in.map(|elem| elem.as_slice::<B32>());
 
-		let mut full_round_consts = full_round_consts.map(|id| witness.new_column::<B32>(id));
-		let full_round_consts_32b = full_round_consts
-			.each_mut()
-			.map(|elem| elem.as_mut_slice::<B32>());
-
-		let mut add_rc = add_rc.map(|id| witness.new_column::<B32>(id));
-		let add_rc_32b: [&mut [BinaryField32b]; STATE_SIZE] =
-			add_rc.each_mut().map(|elem| elem.as_mut_slice());
-
 		let mut s_box_out = s_box_out.map(|id| witness.new_column::<B32>(id));
 		let s_box_out_32b: [&mut [BinaryField32b]; STATE_SIZE] =
 			s_box_out.each_mut().map(|elem| elem.as_mut_slice());
@@ -295,20 +392,9 @@ where {
 		let mds_out_32b: [&mut [BinaryField32b]; STATE_SIZE] =
 			mds_out.each_mut().map(|elem| elem.as_mut_slice());
 
-		// Fill in constants
-		for i in 0..STATE_SIZE {
-			full_round_consts_32b[i]
-				.iter_mut()
-				.for_each(|rc| *rc = BinaryField32b::new(round_constants[i][round_i]));
-		}
-
 		for z in 0..1 << log_size {
 			for i in 0..STATE_SIZE {
-				add_rc_32b[i][z] = state_in_u32[i][z] + full_round_consts_32b[i][z];
-			}
-
-			for i in 0..STATE_SIZE {
-				s_box_out_32b[i][z] = add_rc_32b[i][z].pow(7);
+				s_box_out_32b[i][z] = state_in_u32[i][z].pow(7);
 			}
 
 			for i in 0..STATE_SIZE {
@@ -317,14 +403,21 @@ where {
 					mds_out_curr +=
 						BinaryField32b::new(MDS_FULL[i][j] as u32) * s_box_out_32b[j][z];
 				}
-				mds_out_32b[i][z] = mds_out_curr;
+				mds_out_32b[i][z] = mds_out_curr + next_rc[i];
 			}
 		}
 	}
 
 	// zero check constraints
 	for s in 0..STATE_SIZE {
-		builder.assert_zero(format!("x7_{s}"), [add_rc[s], s_box_out[s]], x7_constraint_expr()?);
+		sbox::add_x7_constraints::<B32>(
+			builder,
+			log_size,
+			format!("x7_{s}"),
+			state_in[s],
+			s_box_out[s],
+			SboxGadget::Direct,
+		)?;
 	}
 
 	Ok(mds_out)
@@ -339,30 +432,18 @@ fn partial_round(
 ) -> Result<[OracleId; STATE_SIZE]>
 where {
 	builder.push_namespace(format!("round[{round_i}]"));
-
-	let partial_round_const: OracleId = builder
-		.add_transparent(
-			format!("partial_round_const{}", 0),
-			Constant::new(log_size, BinaryField32b::new(round_constants[0][round_i])),
-		)
-		.unwrap();
-
-	let add_rc: OracleId = builder
-		.add_linear_combination(
-			format!("add_rc_partial_0"),
-			log_size,
-			[(state_in[0], Field::ONE), (partial_round_const, Field::ONE)],
-		)
-		.unwrap();
+	// `state_in[0]` already includes this round's constant; the next round's go into `mds_out`.
+	let next_rc = round_rc(round_constants, round_i + 1);
 
 	let s_box_out: OracleId =
 		builder.add_committed("sbox_out_partial", log_size, BinaryField32b::TOWER_LEVEL);
 
 	let mds_out: [OracleId; STATE_SIZE] = array::from_fn(|row| {
 		builder
-			.add_linear_combination(
+			.add_linear_combination_with_offset(
 				format!("mds_out_partial_{}", row),
 				log_size,
+				F::from(next_rc[row]),
 				MDS_PARTIAL[row].iter().enumerate().map(|(i, &elem)| {
 					if i == 0 {
 						(s_box_out, F::from(BinaryField32b::new(elem as u32)))
@@ -382,12 +463,6 @@ where {
 		let state_in: [_; STATE_SIZE] =
 			array_util::try_from_fn(|i| witness.get::<B32>(state_in[i]))?;
 		let state_in_u32: [_; STATE_SIZE] = state_in.map(|elem| elem.as_slice::<B32>());
-		let mut partial_round_const = witness.new_column::<B32>(partial_round_const);
-		let partial_round_const_32b = partial_round_const.as_mut_slice();
-
-		let mut add_rc = witness.new_column::<B32>(add_rc);
-		let add_rc_32b: &mut [BinaryField32b] = add_rc.as_mut_slice();
-
 		let mut s_box_out = witness.new_column::<B32>(s_box_out);
 		let s_box_out_32b: &mut [BinaryField32b] = s_box_out.as_mut_slice();
 
@@ -395,15 +470,8 @@ where {
 		let mds_out_32b: [&mut [BinaryField32b]; STATE_SIZE] =
 			mds_out.each_mut().map(|elem| elem.as_mut_slice());
 
-		// Fill in constants
-		partial_round_const_32b
-			.iter_mut()
-			.for_each(|rc| *rc = BinaryField32b::new(round_constants[0][round_i]));
-
 		for z in 0..1 << log_size {
-			add_rc_32b[z] = state_in_u32[0][z] + partial_round_const_32b[z];
-
-			s_box_out_32b[z] = add_rc_32b[z].pow(7);
+			s_box_out_32b[z] = state_in_u32[0][z].pow(7);
 
 			let mut input_mds = [B32::ZERO; STATE_SIZE];
 			input_mds[0] = s_box_out_32b[z];
@@ -418,13 +486,20 @@ where {
 				for j in 0..STATE_SIZE {
 					mds_out_curr += BinaryField32b::new(MDS_PARTIAL[i][j] as u32) * input_mds[j];
 				}
-				mds_out_32b[i][z] = mds_out_curr;
+				mds_out_32b[i][z] = mds_out_curr + next_rc[i];
 			}
 		}
 	}
 
 	// zero check constraints
-	builder.assert_zero(format!("x7_0_partial"), [add_rc, s_box_out], x7_constraint_expr()?);
+	sbox::add_x7_constraints::<B32>(
+		builder,
+		log_size,
+		format!("x7_0_partial"),
+		state_in[0],
+		s_box_out,
+		SboxGadget::Direct,
+	)?;
 
 	Ok(mds_out)
 }
@@ -433,12 +508,19 @@ where {
 mod tests {
 
 	use binius_core::oracle::OracleId;
-	use binius_field::BinaryField32b;
-	use super::permutation;
+	use binius_field::{BinaryField32b, Field};
+	use poseidon2b_runner::{
+		Poseidon2b, params_32_t16,
+		sponge::{Padding, Poseidon2bSponge},
+	};
+
+	use super::{
+		LaneInit, N_ROUNDS, permutation, permutation_with_init, plain_permutation, plain_trace,
+	};
 	use crate::{
 		builder::test_utils::test_circuit,
 		hades::poseidon2b_x7_32_512::STATE_SIZE,
-		unconstrained::unconstrained,
+		unconstrained::{unconstrained, unconstrained_padded},
 	};
 	#[test]
 	fn test_poseidon2b() {
@@ -452,4 +534,128 @@ mod tests {
 		})
 		.unwrap();
 	}
+
+	#[test]
+	fn test_plain_trace_matches_native_permute_trace() {
+		let input: [BinaryField32b; STATE_SIZE] =
+			std::array::from_fn(|i| BinaryField32b::new(0x9e37_79b9u32.wrapping_mul(i as u32 + 1)));
+		let trace = plain_trace(input);
+		let native = Poseidon2b::new(params_32_t16()).permute_trace(&input);
+		assert_eq!(trace.len(), N_ROUNDS + 1);
+		assert_eq!(native.len(), trace.len());
+		for (r, (circuit, native)) in trace.iter().zip(&native).enumerate() {
+			assert_eq!(&circuit[..], &native[..], "traces diverge at entry {r}");
+		}
+
+		let mut out = input;
+		plain_permutation(&mut out, N_ROUNDS);
+		assert_eq!(trace[N_ROUNDS], out);
+	}
+
+	#[test]
+	fn test_poseidon2b_single_row() {
+		test_circuit(|builder| {
+			let log_size = 0;
+			let state_in: [OracleId; STATE_SIZE] = std::array::from_fn(|i| {
+				unconstrained::<BinaryField32b>(builder, format!("p_in[{i}]"), log_size).unwrap()
+			});
+			let _state_out = permutation(builder, log_size, state_in).unwrap();
+			Ok(vec![])
+		})
+		.unwrap();
+	}
+
+	#[test]
+	fn test_poseidon2b_padded_rows() {
+		test_circuit(|builder| {
+			let log_size = 3;
+			let n_permutations = 5;
+			let state_in: [OracleId; STATE_SIZE] = std::array::from_fn(|i| {
+				unconstrained_padded::<BinaryField32b>(
+					builder,
+					format!("p_in[{i}]"),
+					log_size,
+					n_permutations,
+				)
+				.unwrap()
+			});
+			let state_out = permutation(builder, log_size, state_in).unwrap();
+
+			if let Some(witness) = builder.witness() {
+				let mut dummy = [BinaryField32b::ZERO; STATE_SIZE];
+				plain_permutation(&mut dummy, N_ROUNDS);
+				for (i, &id) in state_out.iter().enumerate() {
+					let out = witness.get::<BinaryField32b>(id)?;
+					let out = out.as_slice::<BinaryField32b>();
+					for (z, &value) in out[n_permutations..].iter().enumerate() {
+						let z = n_permutations + z;
+						assert_eq!(value, dummy[i], "padding row {z}, element {i}");
+					}
+				}
+			}
+			Ok(vec![])
+		})
+		.unwrap();
+	}
+
+	// Two chained permutations absorbing a 29-element message: the first block fills the rate,
+	// the second is 14 elements plus the `10*` padding one, added onto the first output.
+	#[test]
+	fn test_permutation_with_init_matches_native_sponge() {
+		const RATE: usize = STATE_SIZE - 1;
+		test_circuit(|builder| {
+			let log_size = 2;
+			let msg: [OracleId; 2 * RATE - 1] = std::array::from_fn(|i| {
+				unconstrained::<BinaryField32b>(builder, format!("msg[{i}]"), log_size).unwrap()
+			});
+
+			let first = permutation_with_init(
+				builder,
+				log_size,
+				std::array::from_fn(|i| {
+					if i < RATE {
+						LaneInit::column(msg[i])
+					} else {
+						LaneInit::default()
+					}
+				}),
+			)
+			.unwrap();
+			let second = permutation_with_init(
+				builder,
+				log_size,
+				std::array::from_fn(|i| {
+					let mut lane = LaneInit::column(first[i]);
+					if i < RATE - 1 {
+						lane.terms.push((msg[RATE + i], BinaryField32b::ONE));
+					} else if i == RATE - 1 {
+						lane.offset = BinaryField32b::ONE;
+					}
+					lane
+				}),
+			)
+			.unwrap();
+
+			if let Some(witness) = builder.witness() {
+				let column = |id: OracleId| {
+					witness
+						.get::<BinaryField32b>(id)
+						.unwrap()
+						.as_slice::<BinaryField32b>()
+						.to_vec()
+				};
+				let perm = Poseidon2b::new(params_32_t16());
+				for z in 0..1 << log_size {
+					let input: Vec<BinaryField32b> = msg.iter().map(|&id| column(id)[z]).collect();
+					let mut sponge = Poseidon2bSponge::new(&perm, Padding::Pad10);
+					sponge.absorb(&input);
+					let circuit_out: Vec<BinaryField32b> =
+						second[..RATE].iter().map(|&id| column(id)[z]).collect();
+					assert_eq!(circuit_out, sponge.squeeze(RATE).unwrap(), "row {z}");
+				}
+			}
+			Ok(vec![])
+		})
+		.unwrap();
+	}
 }
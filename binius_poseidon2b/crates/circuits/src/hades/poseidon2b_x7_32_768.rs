@@ -5,11 +5,13 @@
 use std::array;
 
 use anyhow::Result;
-use binius_core::{oracle::OracleId, transparent::constant::Constant};
+use binius_core::oracle::OracleId;
 use binius_field::{BinaryField32b, Field, PackedField, TowerField};
-use binius_math::{ArithCircuit, ArithExpr};
 
-use crate::builder::{ConstraintSystemBuilder, types::F};
+use crate::{
+	builder::{ConstraintSystemBuilder, types::F},
+	hades::sbox::{self, SboxGadget},
+};
 
 type B32 = BinaryField32b;
 const P_ROUNDS: usize = 15;
@@ -18,6 +20,11 @@ const N_ROUNDS: usize = F_ROUNDS + P_ROUNDS;
 
 const STATE_SIZE: usize = 24;
 
+// The first and last `F_ROUNDS / 2` rounds are full, the `P_ROUNDS` between them partial.
+fn is_full_round(r: usize) -> bool {
+	r < F_ROUNDS / 2 || r >= F_ROUNDS / 2 + P_ROUNDS
+}
+
 fn plain_permutation(state: &mut [BinaryField32b; STATE_SIZE], n_rounds: usize) {
 	// initial mds matrix mult
 	let mds_input = state.clone();
@@ -32,7 +39,7 @@ fn plain_permutation(state: &mut [BinaryField32b; STATE_SIZE], n_rounds: usize)
 
 	//full and partial rounds
 	for r in 0..n_rounds {
-		if r < F_ROUNDS / 2 || r >= F_ROUNDS / 2 + P_ROUNDS {
+		if is_full_round(r) {
 			//  Full (external) rounds
 			for i in 0..state.len() {
 				//rc add
@@ -78,11 +85,16 @@ pub fn permutation(
 	p_in: [OracleId; STATE_SIZE],
 ) -> Result<[OracleId; STATE_SIZE]> {
 	println!("Number of rounds: {}", F_ROUNDS + P_ROUNDS);
+	// Round constants are public, so each round's constants are folded into the offset of the
+	// linear combination that produces its input: every round starts from `state + rc` and needs
+	// no constant or `add_rc` oracles of its own.
+	let rc_0 = round_rc(RC, 0);
 	let round_0_input: [OracleId; STATE_SIZE] = array::from_fn(|row| {
 		builder
-			.add_linear_combination(
+			.add_linear_combination_with_offset(
 				format!("mds_out_full_{}", row),
 				log_size,
+				F::from(rc_0[row]),
 				MDS_FULL[row]
 					.iter()
 					.enumerate()
@@ -95,6 +107,7 @@ pub fn permutation(
 		let perm_in_data_owned: [_; STATE_SIZE] =
 			array_util::try_from_fn(|i| witness.get::<B32>(p_in[i]))?;
 		let perm_in_data: [_; STATE_SIZE] = perm_in_data_owned.map(|elem| elem.as_slice::<B32>());
+		let mds_full = MDS_FULL.map(|row| row.map(BinaryField32b::new));
 		let mut round_0_input_data: [_; STATE_SIZE] =
 			round_0_input.map(|id| witness.new_column::<B32>(id));
 		let round_0_input_32b = round_0_input_data
@@ -105,9 +118,9 @@ pub fn permutation(
 			for i in 0..STATE_SIZE {
 				let mut mds_out_curr = B32::ZERO;
 				for j in 0..STATE_SIZE {
-					mds_out_curr += BinaryField32b::new(MDS_FULL[i][j] as u32) * perm_in_data[j][z];
+					mds_out_curr += mds_full[i][j] * perm_in_data[j][z];
 				}
-				round_0_input_32b[i][z] = mds_out_curr;
+				round_0_input_32b[i][z] = mds_out_curr + rc_0[i];
 			}
 		}
 	}
@@ -228,16 +241,16 @@ const MDS_PARTIAL: [[u32; STATE_SIZE]; STATE_SIZE] = [
 [0x1, 0x1, 0x1, 0x1, 0x1, 0x1, 0x1, 0x1, 0x1, 0x1, 0x1, 0x1, 0x1, 0x1, 0x1, 0x1, 0x1, 0x1, 0x1, 0x1, 0x1, 0x1, 0x1, 0x8200],
 ];
 
-fn x7_constraint_expr<F: TowerField>() -> Result<ArithCircuit<F>> {
-	let x = ArithExpr::Var(0);
-	let x7 = ArithExpr::Var(1);
-
-	let input_pow2 = x.clone().pow(2);
-	let input_pow4 = input_pow2.clone().pow(2);
-	let input_pow6 = input_pow2 * input_pow4;
-	let input_pow7 = input_pow6 * x.clone();
-	
-	Ok((x7.clone() - input_pow7).into())
+// Constants added to the state at the start of round `round_i`: all of them in a full round, only
+// the first in a partial round, and none after the last round.
+fn round_rc(round_constants: [[u32; N_ROUNDS]; STATE_SIZE], round_i: usize) -> [B32; STATE_SIZE] {
+	array::from_fn(|i| {
+		if round_i < N_ROUNDS && (is_full_round(round_i) || i == 0) {
+			B32::new(round_constants[i][round_i])
+		} else {
+			B32::ZERO
+		}
+	})
 }
 
 fn full_round(
@@ -249,27 +262,8 @@ fn full_round(
 ) -> Result<[OracleId; STATE_SIZE]>
 where {
 	builder.push_namespace(format!("full round[{round_i}]"));
-	let full_round_consts: [OracleId; STATE_SIZE] = array::from_fn(|i| {
-		builder
-			.add_transparent(
-				format!("full_round_const{}", i),
-				Constant::new(log_size, BinaryField32b::new(round_constants[i][round_i])),
-			)
-			.unwrap()
-	});
-
-	let add_rc: [OracleId; STATE_SIZE] = array::from_fn(|row| {
-		builder
-			.add_linear_combination(
-				format!("add_rc_full_{}", row),
-				log_size,
-				[
-					(state_in[row], Field::ONE),
-					(full_round_consts[row], Field::ONE),
-				],
-			)
-			.unwrap()
-	});
+	// `state_in` already includes this round's constants; the next round's go into `mds_out`.
+	let next_rc = round_rc(round_constants, round_i + 1);
 
 	let s_box_out = builder.add_committed_multiple::<STATE_SIZE>(
 		"sbox_out_full",
@@ -279,9 +273,10 @@ where {
 
 	let mds_out: [OracleId; STATE_SIZE] = array::from_fn(|row| {
 		builder
-			.add_linear_combination(
+			.add_linear_combination_with_offset(
 				format!("mds_out_full_{}", row),
 				log_size,
+				F::from(next_rc[row]),
 				MDS_FULL[row]
 					.iter()
 					.enumerate()
@@ -298,15 +293,6 @@ where {
 			array_util::try_from_fn(|i| witness.get::<B32>(state_in[i]))?;
 		let state_in_u32: [_; STATE_SIZE] = state_in.map(|elem| elem.as_slice::<B32>());
 
-		let mut full_round_consts = full_round_consts.map(|id| witness.new_column::<B32>(id));
-		let full_round_consts_32b = full_round_consts
-			.each_mut()
-			.map(|elem| elem.as_mut_slice::<B32>());
-
-		let mut add_rc = add_rc.map(|id| witness.new_column::<B32>(id));
-		let add_rc_32b: [&mut [BinaryField32b]; STATE_SIZE] =
-			add_rc.each_mut().map(|elem| elem.as_mut_slice());
-
 		let mut s_box_out = s_box_out.map(|id| witness.new_column::<B32>(id));
 		let s_box_out_32b: [&mut [BinaryField32b]; STATE_SIZE] =
 			s_box_out.each_mut().map(|elem| elem.as_mut_slice());
@@ -315,20 +301,9 @@ where {
 		let mds_out_32b: [&mut [BinaryField32b]; STATE_SIZE] =
 			mds_out.each_mut().map(|elem| elem.as_mut_slice());
 
-		// Fill in constants
-		for i in 0..STATE_SIZE {
-			full_round_consts_32b[i]
-				.iter_mut()
-				.for_each(|rc| *rc = BinaryField32b::new(round_constants[i][round_i]));
-		}
-
 		for z in 0..1 << log_size {
 			for i in 0..STATE_SIZE {
-				add_rc_32b[i][z] = state_in_u32[i][z] + full_round_consts_32b[i][z];
-			}
-
-			for i in 0..STATE_SIZE {
-				s_box_out_32b[i][z] = add_rc_32b[i][z].pow(7);
+				s_box_out_32b[i][z] = state_in_u32[i][z].pow(7);
 			}
 
 			for i in 0..STATE_SIZE {
@@ -337,14 +312,21 @@ where {
 					mds_out_curr +=
 						BinaryField32b::new(MDS_FULL[i][j] as u32) * s_box_out_32b[j][z];
 				}
-				mds_out_32b[i][z] = mds_out_curr;
+				mds_out_32b[i][z] = mds_out_curr + next_rc[i];
 			}
 		}
 	}
 
 	// zero check constraints
 	for s in 0..STATE_SIZE {
-		builder.assert_zero(format!("x7_{s}"), [add_rc[s], s_box_out[s]], x7_constraint_expr()?);
+		sbox::add_x7_constraints::<B32>(
+			builder,
+			log_size,
+			format!("x7_{s}"),
+			state_in[s],
+			s_box_out[s],
+			SboxGadget::Direct,
+		)?;
 	}
 
 	Ok(mds_out)
@@ -359,30 +341,18 @@ fn partial_round(
 ) -> Result<[OracleId; STATE_SIZE]>
 where {
 	builder.push_namespace(format!("round[{round_i}]"));
-
-	let partial_round_const: OracleId = builder
-		.add_transparent(
-			format!("partial_round_const{}", 0),
-			Constant::new(log_size, BinaryField32b::new(round_constants[0][round_i])),
-		)
-		.unwrap();
-
-	let add_rc: OracleId = builder
-		.add_linear_combination(
-			format!("add_rc_partial_0"),
-			log_size,
-			[(state_in[0], Field::ONE), (partial_round_const, Field::ONE)],
-		)
-		.unwrap();
+	// `state_in[0]` already includes this round's constant; the next round's go into `mds_out`.
+	let next_rc = round_rc(round_constants, round_i + 1);
 
 	let s_box_out: OracleId =
 		builder.add_committed("sbox_out_partial", log_size, BinaryField32b::TOWER_LEVEL);
 
 	let mds_out: [OracleId; STATE_SIZE] = array::from_fn(|row| {
 		builder
-			.add_linear_combination(
+			.add_linear_combination_with_offset(
 				format!("mds_out_partial_{}", row),
 				log_size,
+				F::from(next_rc[row]),
 				MDS_PARTIAL[row].iter().enumerate().map(|(i, &elem)| {
 					if i == 0 {
 						(s_box_out, F::from(BinaryField32b::new(elem as u32)))
@@ -403,12 +373,6 @@ where {
 		let state_in: [_; STATE_SIZE] =
 			array_util::try_from_fn(|i| witness.get::<B32>(state_in[i]))?;
 		let state_in_u32: [_; STATE_SIZE] = state_in.map(|elem| elem.as_slice::<B32>());
-		let mut partial_round_const = witness.new_column::<B32>(partial_round_const);
-		let partial_round_const_32b = partial_round_const.as_mut_slice::<B32>();
-
-		let mut add_rc = witness.new_column::<B32>(add_rc);
-		let add_rc_32b: &mut [BinaryField32b] = add_rc.as_mut_slice();
-
 		let mut s_box_out = witness.new_column::<B32>(s_box_out);
 		let s_box_out_32b: &mut [BinaryField32b] = s_box_out.as_mut_slice();
 
@@ -416,14 +380,8 @@ where {
 		let mds_out_32b: [&mut [BinaryField32b]; STATE_SIZE] =
 			mds_out.each_mut().map(|elem| elem.as_mut_slice());
 
-		// Fill in constants
-		partial_round_const_32b
-			.iter_mut()
-			.for_each(|rc| *rc = BinaryField32b::new(round_constants[0][round_i]));
 		for z in 0..1 << log_size {
-			add_rc_32b[z] = state_in_u32[0][z] + partial_round_const_32b[z];
-
-			s_box_out_32b[z] = add_rc_32b[z].pow(7);
+			s_box_out_32b[z] = state_in_u32[0][z].pow(7);
 
 			let mut input_mds = [B32::ZERO; STATE_SIZE];
 			input_mds[0] = s_box_out_32b[z];
@@ -438,13 +396,20 @@ where {
 				for j in 0..STATE_SIZE {
 					mds_out_curr += BinaryField32b::new(MDS_PARTIAL[i][j] as u32) * input_mds[j];
 				}
-				mds_out_32b[i][z] = mds_out_curr;
+				mds_out_32b[i][z] = mds_out_curr + next_rc[i];
 			}
 		}
 	}
 
 	// zero check constraints
-	builder.assert_zero(format!("x7_0_partial"), [add_rc, s_box_out], x7_constraint_expr()?);
+	sbox::add_x7_constraints::<B32>(
+		builder,
+		log_size,
+		format!("x7_0_partial"),
+		state_in[0],
+		s_box_out,
+		SboxGadget::Direct,
+	)?;
 
 	Ok(mds_out)
 }
@@ -4,11 +4,12 @@
 use std::array;
 
 use anyhow::Result;
-use binius_core::{oracle::OracleId, transparent::constant::Constant};
+use binius_core::oracle::OracleId;
 use binius_field::{BinaryField64b, Field, PackedField, TowerField};
-use binius_math::{ArithCircuit, ArithExpr};
-
-use crate::builder::{ConstraintSystemBuilder, types::F};
+use crate::{
+	builder::{ConstraintSystemBuilder, types::F},
+	hades::sbox::{self, SboxGadget},
+};
 
 type B64 = BinaryField64b;
 const P_ROUNDS: usize = 29;
@@ -17,20 +18,27 @@ const N_ROUNDS: usize = F_ROUNDS + P_ROUNDS;
 
 const STATE_SIZE: usize = 8;
 
-fn plain_permutation(state: &mut [B64; STATE_SIZE], n_rounds: usize) {
+// The first and last `F_ROUNDS / 2` rounds are full, the `P_ROUNDS` between them partial.
+fn is_full_round(r: usize) -> bool {
+	r < F_ROUNDS / 2 || r >= F_ROUNDS / 2 + P_ROUNDS
+}
+
+fn plain_permutation(state: &mut [B64; STATE_SIZE], n_rounds: usize, apply_initial_mds: bool) {
 	// initial mds matrix mult
-	let mds_input = state.clone();
-	for i in 0..state.len() {
-		// mds matrix mult
-		let mut mds_out_curr = B64::ZERO;
-		for j in 0..STATE_SIZE {
-			mds_out_curr += B64::new(MDS_FULL[i][j] as u64) * mds_input[j];
+	if apply_initial_mds {
+		let mds_input = state.clone();
+		for i in 0..state.len() {
+			// mds matrix mult
+			let mut mds_out_curr = B64::ZERO;
+			for j in 0..STATE_SIZE {
+				mds_out_curr += B64::new(MDS_FULL[i][j] as u64) * mds_input[j];
+			}
+			state[i] = mds_out_curr;
 		}
-		state[i] = mds_out_curr;
 	}
 	//full and partial rounds
 	for r in 0..n_rounds {
-		if r < F_ROUNDS / 2 || r >= F_ROUNDS / 2 + P_ROUNDS {
+		if is_full_round(r) {
 			//  Full (external) rounds
 			for i in 0..state.len() {
 				//rc add
@@ -75,54 +83,57 @@ pub fn permutation(
 	log_size: usize,
 	p_in: [OracleId; STATE_SIZE],
 ) -> Result<[OracleId; STATE_SIZE]> {
-	println!("Number of rounds: {}", F_ROUNDS + P_ROUNDS);
+	permutation_with_options(builder, log_size, p_in, SboxGadget::Direct, true)
+}
 
-	let round_0_input: [OracleId; STATE_SIZE] = array::from_fn(|row| {
-		builder
-			.add_linear_combination(
-				format!("mds_out_full_{}", row),
-				log_size,
-				MDS_FULL[row]
-					.iter()
-					.enumerate()
-					.map(|(i, &elem)| (p_in[i], F::from(B64::from(elem as u64)))),
-			)
-			.unwrap()
-	});
+/// [`permutation`] with the S-box constrained by `gadget` instead of the default direct x^7 check.
+pub fn permutation_with_sbox(
+	builder: &mut ConstraintSystemBuilder,
+	log_size: usize,
+	p_in: [OracleId; STATE_SIZE],
+	gadget: SboxGadget,
+) -> Result<[OracleId; STATE_SIZE]> {
+	permutation_with_options(builder, log_size, p_in, gadget, true)
+}
 
-	if let Some(witness) = builder.witness() {
-		let perm_in_data_owned: [_; STATE_SIZE] =
-			array_util::try_from_fn(|i| witness.get::<B64>(p_in[i]))?;
-		let perm_in_data: [_; STATE_SIZE] = perm_in_data_owned.map(|elem| elem.as_slice::<B64>());
-		let mut round_0_input_data: [_; STATE_SIZE] =
-			round_0_input.map(|id| witness.new_column::<B64>(id));
-		let round_0_input_64b = round_0_input_data
-			.each_mut()
-			.map(|elem| elem.as_mut_slice::<B64>());
+/// [`permutation`] with the initial `MDS_FULL` layer made optional. With `apply_initial_mds`
+/// false the first full round reads `p_in` directly, matching the native
+/// `Poseidon2b::with_initial_mds(false)`.
+pub fn permutation_with_initial_mds(
+	builder: &mut ConstraintSystemBuilder,
+	log_size: usize,
+	p_in: [OracleId; STATE_SIZE],
+	apply_initial_mds: bool,
+) -> Result<[OracleId; STATE_SIZE]> {
+	permutation_with_options(builder, log_size, p_in, SboxGadget::Direct, apply_initial_mds)
+}
 
-		for z in 0..1 << log_size {
-			for i in 0..STATE_SIZE {
-				let mut mds_out_curr = B64::ZERO;
-				for j in 0..STATE_SIZE {
-					mds_out_curr += B64::new(MDS_FULL[i][j] as u64) * perm_in_data[j][z];
-				}
-				round_0_input_64b[i][z] = mds_out_curr;
-			}
-		}
-	}
+fn permutation_with_options(
+	builder: &mut ConstraintSystemBuilder,
+	log_size: usize,
+	p_in: [OracleId; STATE_SIZE],
+	gadget: SboxGadget,
+	apply_initial_mds: bool,
+) -> Result<[OracleId; STATE_SIZE]> {
+	println!("Number of rounds: {}", F_ROUNDS + P_ROUNDS);
+
+	// Round constants are public, so each round's constants are folded into the offset of the
+	// linear combination that produces its input: every round starts from `state + rc` and needs
+	// no constant or `add_rc` oracles of its own.
+	let round_0_input = round_0_input(builder, log_size, p_in, apply_initial_mds)?;
 
 	let full_0_out = (0..F_ROUNDS / 2).try_fold(round_0_input, |state, round_i| {
-		full_round(builder, log_size, round_i, state, RC)
+		full_round(builder, log_size, round_i, state, RC, gadget)
 	})?;
 
 	let partial_out = (F_ROUNDS / 2..(F_ROUNDS / 2 + P_ROUNDS))
 		.try_fold(full_0_out, |state, round_i| {
-			partial_round(builder, log_size, round_i, state, RC)
+			partial_round(builder, log_size, round_i, state, RC, gadget)
 		})?;
 
 	let perm_out = (F_ROUNDS / 2 + P_ROUNDS..N_ROUNDS)
 		.try_fold(partial_out, |state, round_i| {
-			full_round(builder, log_size, round_i, state, RC)
+			full_round(builder, log_size, round_i, state, RC, gadget)
 		})?;
 
 	#[cfg(debug_assertions)]
@@ -136,13 +147,66 @@ pub fn permutation(
 		for z in 0..1 << log_size {
 			let expected_out: [B64; STATE_SIZE] = array::from_fn(|s| p_out_64b[s][z]);
 			let mut state_in: [B64; STATE_SIZE] = std::array::from_fn(|i| p_in_64b[i][z]);
-			plain_permutation(&mut state_in, N_ROUNDS);
+			plain_permutation(&mut state_in, N_ROUNDS, apply_initial_mds);
 			assert_eq!(state_in, expected_out);
 		}
 	}
 
 	Ok(perm_out)
 }
+
+// `Minit * p_in` plus the round 0 constants, with `Minit` the identity when `apply_initial_mds` is
+// false.
+fn round_0_input(
+	builder: &mut ConstraintSystemBuilder,
+	log_size: usize,
+	p_in: [OracleId; STATE_SIZE],
+	apply_initial_mds: bool,
+) -> Result<[OracleId; STATE_SIZE]> {
+	let rc_0 = round_rc(RC, 0);
+	let m_init: [[B64; STATE_SIZE]; STATE_SIZE] = if apply_initial_mds {
+		MDS_FULL.map(|row| row.map(B64::new))
+	} else {
+		array::from_fn(|i| array::from_fn(|j| if i == j { B64::ONE } else { B64::ZERO }))
+	};
+	let round_0_input: [OracleId; STATE_SIZE] = array::from_fn(|row| {
+		builder
+			.add_linear_combination_with_offset(
+				format!("mds_out_full_{}", row),
+				log_size,
+				F::from(rc_0[row]),
+				m_init[row]
+					.iter()
+					.enumerate()
+					.filter(|(_, elem)| **elem != B64::ZERO)
+					.map(|(i, &elem)| (p_in[i], F::from(elem))),
+			)
+			.unwrap()
+	});
+
+	if let Some(witness) = builder.witness() {
+		let perm_in_data_owned: [_; STATE_SIZE] =
+			array_util::try_from_fn(|i| witness.get::<B64>(p_in[i]))?;
+		let perm_in_data: [_; STATE_SIZE] = perm_in_data_owned.map(|elem| elem.as_slice::<B64>());
+		let mut round_0_input_data: [_; STATE_SIZE] =
+			round_0_input.map(|id| witness.new_column::<B64>(id));
+		let round_0_input_64b = round_0_input_data
+			.each_mut()
+			.map(|elem| elem.as_mut_slice::<B64>());
+
+		for z in 0..1 << log_size {
+			for i in 0..STATE_SIZE {
+				let mut mds_out_curr = B64::ZERO;
+				for j in 0..STATE_SIZE {
+					mds_out_curr += m_init[i][j] * perm_in_data[j][z];
+				}
+				round_0_input_64b[i][z] = mds_out_curr + rc_0[i];
+			}
+		}
+	}
+
+	Ok(round_0_input)
+}
 #[rustfmt::skip]
 const RC: [[u64; N_ROUNDS]; STATE_SIZE] =[ 
 [0x68, 0x34, 0x1d, 0x2c, 0x1d, 0x4b, 0x32, 0x55, 0x7a, 0x79, 0x3d, 0x59, 0x1c, 0x41, 0x5f, 0x65, 0x40, 0x6b, 0x48, 0x48, 0x54, 0x8, 0x43, 0x12, 0x40, 0x56, 0x6, 0xa, 0x36, 0x76, 0x5d, 0x73, 0x7, 0x47, 0x68, 0x4b, 0x3a, 0xe, 0x23, ],
@@ -177,16 +241,16 @@ const MDS_PARTIAL: [[u64; STATE_SIZE]; STATE_SIZE] = [
 [0x1, 0x1, 0x1, 0x1, 0x1, 0x1, 0x4000, 0x1, ],
 [0x1, 0x1, 0x1, 0x1, 0x1, 0x1, 0x1, 0x40, ],
 ];
-fn x7_constraint_expr<F: TowerField>() -> Result<ArithCircuit<F>> {
-	let x = ArithExpr::Var(0);
-	let x7 = ArithExpr::Var(1);
-
-	let input_pow2 = x.clone().pow(2);
-	let input_pow4 = input_pow2.clone().pow(2);
-	let input_pow6 = input_pow2 * input_pow4;
-	let input_pow7 = input_pow6 * x.clone();
-
-	Ok((x7.clone() - input_pow7).into())
+// Constants added to the state at the start of round `round_i`: all of them in a full round, only
+// the first in a partial round, and none after the last round.
+fn round_rc(round_constants: [[u64; N_ROUNDS]; STATE_SIZE], round_i: usize) -> [B64; STATE_SIZE] {
+	array::from_fn(|i| {
+		if round_i < N_ROUNDS && (is_full_round(round_i) || i == 0) {
+			B64::new(round_constants[i][round_i])
+		} else {
+			B64::ZERO
+		}
+	})
 }
 
 fn full_round(
@@ -195,40 +259,22 @@ fn full_round(
 	round_i: usize,
 	state_in: [OracleId; STATE_SIZE],
 	round_constants: [[u64; N_ROUNDS]; STATE_SIZE],
+	gadget: SboxGadget,
 ) -> Result<[OracleId; STATE_SIZE]>
 where {
 	builder.push_namespace(format!("full round[{round_i}]"));
-	// let state_size = state_in.len();
-	let full_round_consts: [OracleId; STATE_SIZE] = array::from_fn(|i| {
-		builder
-			.add_transparent(
-				format!("full_round_const{}", i),
-				Constant::new(log_size, B64::new(round_constants[i][round_i])),
-			)
-			.unwrap()
-	});
-
-	let add_rc: [OracleId; STATE_SIZE] = array::from_fn(|row| {
-		builder
-			.add_linear_combination(
-				format!("add_rc_full_{}", row),
-				log_size,
-				[
-					(state_in[row], Field::ONE),
-					(full_round_consts[row], Field::ONE),
-				],
-			)
-			.unwrap()
-	});
+	// `state_in` already includes this round's constants; the next round's go into `mds_out`.
+	let next_rc = round_rc(round_constants, round_i + 1);
 
 	let s_box_out =
 		builder.add_committed_multiple::<STATE_SIZE>("sbox_out_full", log_size, B64::TOWER_LEVEL);
 
 	let mds_out: [OracleId; STATE_SIZE] = array::from_fn(|row| {
 		builder
-			.add_linear_combination(
+			.add_linear_combination_with_offset(
 				format!("mds_out_full_{}", row),
 				log_size,
+				F::from(next_rc[row]),
 				MDS_FULL[row]
 					.iter()
 					.enumerate()
@@ -245,15 +291,6 @@ where {
 			array_util::try_from_fn(|i| witness.get::<B64>(state_in[i]))?;
 		let state_in_u64: [_; STATE_SIZE] = state_in.map(|elem| elem.as_slice::<B64>());
 
-		let mut full_round_consts = full_round_consts.map(|id| witness.new_column::<B64>(id));
-		let full_round_consts_64b = full_round_consts
-			.each_mut()
-			.map(|elem| elem.as_mut_slice::<B64>());
-
-		let mut add_rc = add_rc.map(|id| witness.new_column::<B64>(id));
-		let add_rc_64b: [&mut [B64]; STATE_SIZE] =
-			add_rc.each_mut().map(|elem| elem.as_mut_slice());
-
 		let mut s_box_out = s_box_out.map(|id| witness.new_column::<B64>(id));
 		let s_box_out_64b: [&mut [B64]; STATE_SIZE] =
 			s_box_out.each_mut().map(|elem| elem.as_mut_slice());
@@ -262,20 +299,9 @@ where {
 		let mds_out_64b: [&mut [B64]; STATE_SIZE] =
 			mds_out.each_mut().map(|elem| elem.as_mut_slice());
 
-		// Fill in constants
-		for i in 0..STATE_SIZE {
-			full_round_consts_64b[i]
-				.iter_mut()
-				.for_each(|rc| *rc = B64::new(round_constants[i][round_i]));
-		}
-
 		for z in 0..1 << log_size {
 			for i in 0..STATE_SIZE {
-				add_rc_64b[i][z] = state_in_u64[i][z] + full_round_consts_64b[i][z];
-			}
-
-			for i in 0..STATE_SIZE {
-				s_box_out_64b[i][z] = add_rc_64b[i][z].pow(7);
+				s_box_out_64b[i][z] = state_in_u64[i][z].pow(7);
 			}
 
 			for i in 0..STATE_SIZE {
@@ -283,14 +309,21 @@ where {
 				for j in 0..STATE_SIZE {
 					mds_out_curr += B64::new(MDS_FULL[i][j] as u64) * s_box_out_64b[j][z];
 				}
-				mds_out_64b[i][z] = mds_out_curr;
+				mds_out_64b[i][z] = mds_out_curr + next_rc[i];
 			}
 		}
 	}
 
 	// zero check constraints
 	for s in 0..STATE_SIZE {
-		builder.assert_zero(format!("x7_{s}"), [add_rc[s], s_box_out[s]], x7_constraint_expr()?);
+		sbox::add_x7_constraints::<B64>(
+			builder,
+			log_size,
+			format!("full round[{round_i}] x7_{s}"),
+			state_in[s],
+			s_box_out[s],
+			gadget,
+		)?;
 	}
 
 	Ok(mds_out)
@@ -302,32 +335,21 @@ fn partial_round(
 	round_i: usize,
 	state_in: [OracleId; STATE_SIZE],
 	round_constants: [[u64; N_ROUNDS]; STATE_SIZE],
+	gadget: SboxGadget,
 ) -> Result<[OracleId; STATE_SIZE]>
 where {
 	builder.push_namespace(format!("round[{round_i}]"));
-
-	let partial_round_const: OracleId = builder
-		.add_transparent(
-			format!("partial_round_const{}", 0),
-			Constant::new(log_size, B64::new(round_constants[0][round_i])),
-		)
-		.unwrap();
-
-	let add_rc: OracleId = builder
-		.add_linear_combination(
-			format!("add_rc_partial_0"),
-			log_size,
-			[(state_in[0], Field::ONE), (partial_round_const, Field::ONE)],
-		)
-		.unwrap();
+	// `state_in[0]` already includes this round's constant; the next round's go into `mds_out`.
+	let next_rc = round_rc(round_constants, round_i + 1);
 
 	let s_box_out: OracleId = builder.add_committed("sbox_out_partial", log_size, B64::TOWER_LEVEL);
 
 	let mds_out: [OracleId; STATE_SIZE] = array::from_fn(|row| {
 		builder
-			.add_linear_combination(
+			.add_linear_combination_with_offset(
 				format!("mds_out_partial_{}", row),
 				log_size,
+				F::from(next_rc[row]),
 				MDS_PARTIAL[row].iter().enumerate().map(|(i, &elem)| {
 					if i == 0 {
 						(s_box_out, F::from(B64::new(elem as u64)))
@@ -348,12 +370,6 @@ where {
 		let state_in: [_; STATE_SIZE] =
 			array_util::try_from_fn(|i| witness.get::<B64>(state_in[i]))?;
 		let state_in_u64: [_; STATE_SIZE] = state_in.map(|elem| elem.as_slice::<B64>());
-		let mut partial_round_const = witness.new_column::<B64>(partial_round_const);
-		let partial_round_const_64b = partial_round_const.as_mut_slice::<B64>();
-
-		let mut add_rc = witness.new_column::<B64>(add_rc);
-		let add_rc_64b: &mut [B64] = add_rc.as_mut_slice();
-
 		let mut s_box_out = witness.new_column::<B64>(s_box_out);
 		let s_box_out_64b: &mut [B64] = s_box_out.as_mut_slice();
 
@@ -361,14 +377,8 @@ where {
 		let mds_out_64b: [&mut [B64]; STATE_SIZE] =
 			mds_out.each_mut().map(|elem| elem.as_mut_slice());
 
-		// Fill in constants
-		partial_round_const_64b
-			.iter_mut()
-			.for_each(|rc| *rc = B64::new(round_constants[0][round_i]));
 		for z in 0..1 << log_size {
-			add_rc_64b[z] = state_in_u64[0][z] + partial_round_const_64b[z];
-
-			s_box_out_64b[z] = add_rc_64b[z].pow(7);
+			s_box_out_64b[z] = state_in_u64[0][z].pow(7);
 
 			let mut input_mds = [B64::ZERO; STATE_SIZE];
 			input_mds[0] = s_box_out_64b[z];
@@ -383,13 +393,20 @@ where {
 				for j in 0..STATE_SIZE {
 					mds_out_curr += B64::new(MDS_PARTIAL[i][j] as u64) * input_mds[j];
 				}
-				mds_out_64b[i][z] = mds_out_curr;
+				mds_out_64b[i][z] = mds_out_curr + next_rc[i];
 			}
 		}
 	}
 
 	// zero check constraints
-	builder.assert_zero(format!("x7_0_partial"), [add_rc, s_box_out], x7_constraint_expr()?);
+	sbox::add_x7_constraints::<B64>(
+		builder,
+		log_size,
+		format!("round[{round_i}] x7_0_partial"),
+		state_in[0],
+		s_box_out,
+		gadget,
+	)?;
 
 	Ok(mds_out)
 }
@@ -399,11 +416,17 @@ mod tests {
 
 	use binius_core::oracle::OracleId;
 	use binius_field:: BinaryField64b;
+	use poseidon2b_runner::{
+		FieldOps, PlainPermutation, Poseidon2b, Poseidon2b64T8, assert_plain_equiv, params_64_t8,
+	};
 
-	use super::permutation;
+	use super::{
+		N_ROUNDS, permutation, permutation_with_initial_mds, permutation_with_sbox,
+		plain_permutation,
+	};
 	use crate::{
 		builder::test_utils::test_circuit,
-		hades::poseidon2b_x7_64_512::STATE_SIZE,
+		hades::{poseidon2b_x7_64_512::STATE_SIZE, sbox::SboxGadget},
 		unconstrained::unconstrained,
 	};
 	#[test]
@@ -418,4 +441,88 @@ mod tests {
 		})
 		.unwrap();
 	}
-}
\ No newline at end of file
+
+	struct Plain;
+
+	impl PlainPermutation<BinaryField64b> for Plain {
+		const STATE_SIZE: usize = STATE_SIZE;
+
+		fn permute_plain(state: &mut [BinaryField64b]) {
+			let state: &mut [BinaryField64b; STATE_SIZE] =
+				state.try_into().expect("state has STATE_SIZE elements");
+			plain_permutation(state, N_ROUNDS, true);
+		}
+	}
+
+	#[test]
+	fn test_plain_permutation_matches_native() {
+		assert_plain_equiv::<BinaryField64b, Plain, Poseidon2b64T8>(64);
+	}
+
+	// Pins the round schedule, initial MDS included: `plain_permutation(state, r, ..)` must stop
+	// at the same state as round `r` of the native trace, for every `r`, with and without `Minit`.
+	#[test]
+	fn test_plain_permutation_matches_native_trace() {
+		let input: [BinaryField64b; STATE_SIZE] =
+			std::array::from_fn(|i| BinaryField64b::from_u8(5 * i as u8 + 1));
+		for apply_initial_mds in [true, false] {
+			let native = Poseidon2b::new(params_64_t8()).with_initial_mds(apply_initial_mds);
+			let trace = native.permute_trace(&input);
+			assert_eq!(trace.len(), N_ROUNDS + 1);
+
+			for (r, native_state) in trace.iter().enumerate() {
+				let mut state = input;
+				plain_permutation(&mut state, r, apply_initial_mds);
+				assert_eq!(
+					&state[..],
+					&native_state[..],
+					"after {r} rounds, apply_initial_mds={apply_initial_mds}"
+				);
+			}
+		}
+	}
+
+	#[test]
+	fn test_plain_permutation_matches_native_with_and_without_initial_mds() {
+		let input: [BinaryField64b; STATE_SIZE] =
+			std::array::from_fn(|i| BinaryField64b::from_u8(3 * i as u8 + 2));
+		for apply_initial_mds in [true, false] {
+			let native = Poseidon2b::new(params_64_t8()).with_initial_mds(apply_initial_mds);
+			let mut expected = input;
+			native.permute(&mut expected);
+
+			let mut state = input;
+			plain_permutation(&mut state, N_ROUNDS, apply_initial_mds);
+			assert_eq!(state, expected, "apply_initial_mds={apply_initial_mds}");
+		}
+	}
+
+	// The debug-build witness check compares against `plain_permutation` with the flag off.
+	#[test]
+	fn test_poseidon2b_without_initial_mds() {
+		test_circuit(|builder| {
+			let log_size = 4;
+			let state_in: [OracleId; STATE_SIZE] = std::array::from_fn(|i| {
+				unconstrained::<BinaryField64b>(builder, format!("p_in[{i}]"), log_size).unwrap()
+			});
+			let _state_out =
+				permutation_with_initial_mds(builder, log_size, state_in, false).unwrap();
+			Ok(vec![])
+		})
+		.unwrap();
+	}
+
+	#[test]
+	fn test_poseidon2b_split_sbox() {
+		test_circuit(|builder| {
+			let log_size = 8;
+			let state_in: [OracleId; STATE_SIZE] = std::array::from_fn(|i| {
+				unconstrained::<BinaryField64b>(builder, format!("p_in[{i}]"), log_size).unwrap()
+			});
+			let _state_out =
+				permutation_with_sbox(builder, log_size, state_in, SboxGadget::Split).unwrap();
+			Ok(vec![])
+		})
+		.unwrap();
+	}
+}
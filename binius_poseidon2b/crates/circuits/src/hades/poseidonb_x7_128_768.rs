@@ -8,9 +8,11 @@ use anyhow::Result;
 use binius_core::{oracle::OracleId, transparent::constant::Constant};
 use binius_field::{ BinaryField128b, Field, PackedField, TowerField,
 };
-use binius_math::{ArithCircuit, ArithExpr};
 
-use crate::builder::{ConstraintSystemBuilder, types::F};
+use crate::{
+	builder::{ConstraintSystemBuilder, types::F},
+	hades::sbox::{self, SboxGadget},
+};
 
 type B128 = BinaryField128b;
 const P_ROUNDS: usize = 58;
@@ -181,18 +183,6 @@ const MDS_PARTIAL: [[u128; STATE_SIZE]; STATE_SIZE] = [
 [0x00000001, 0x00000001, 0x00000001, 0x00000001, 0x00000001, 0x4000],
 ];
 
-fn x7_constraint_expr<F: TowerField>() -> Result<ArithCircuit<F>> {
-	let x = ArithExpr::Var(0);
-	let x7 = ArithExpr::Var(1);
-
-	let input_pow2 = x.clone()*x.clone();
-	let input_pow4 = input_pow2.clone()* input_pow2.clone();
-	let input_pow6 = input_pow2 * input_pow4;
-	let input_pow7 = input_pow6 * x.clone();
-
-	Ok((x7.clone() - input_pow7).into())
-}
-
 fn full_round(
 	builder: &mut ConstraintSystemBuilder,
 	log_size: usize,
@@ -293,11 +283,14 @@ where {
 
 	// zero check constraints
 	for s in 0..STATE_SIZE {
-		builder.assert_zero(
+		sbox::add_x7_constraints::<B128>(
+			builder,
+			log_size,
 			format!("x7_{s}"),
-			[add_rc[s], s_box_out[s]],
-			x7_constraint_expr()?,
-		);
+			add_rc[s],
+			s_box_out[s],
+			SboxGadget::Direct,
+		)?;
 	}
 
 	Ok(mds_out)
@@ -400,11 +393,14 @@ where {
 	}
 
 	// zero check constraints
-	builder.assert_zero(
+	sbox::add_x7_constraints::<B128>(
+		builder,
+		log_size,
 		format!("x7_0_partial"),
-		[add_rc, s_box_out],
-		x7_constraint_expr()?,
-	);
+		add_rc,
+		s_box_out,
+		SboxGadget::Direct,
+	)?;
 
 	Ok(mds_out)
 }
\ No newline at end of file
@@ -62,3 +62,49 @@ where
 
 	Ok(fixed)
 }
+
+/// Same as `unconstrained`, but only the first `n_rows` rows are random; the remaining rows up to
+/// `1 << log_size` are zero.
+///
+/// Use this when proving a number of permutations that is not a power of two: the padding rows
+/// become the all-zero input state, a known dummy that still goes through the full permutation, so
+/// the constraints hold on them like on any other row. Nothing stops the verifier from reading
+/// them, so callers must treat rows `n_rows..` as padding and ignore their outputs.
+pub fn unconstrained_padded<FS>(
+	builder: &mut ConstraintSystemBuilder,
+	name: impl ToString,
+	log_size: usize,
+	n_rows: usize,
+) -> Result<OracleId, anyhow::Error>
+where
+	U: PackScalar<FS> + Pod,
+	F: TowerField + ExtensionField<FS>,
+	FS: TowerField,
+{
+	anyhow::ensure!(
+		n_rows <= 1 << log_size,
+		"{n_rows} rows do not fit in a column of 2^{log_size} rows"
+	);
+	let column = builder.add_committed(name, log_size, FS::TOWER_LEVEL);
+
+	if let Some(witness) = builder.witness() {
+		let random_bits = n_rows * FS::N_BITS;
+		witness
+			.new_column::<FS>(column)
+			.as_mut_slice::<u8>()
+			.into_par_iter()
+			.enumerate()
+			.for_each_init(thread_rng, |rng, (i, data)| {
+				// Fields narrower than a byte pack several rows per byte, lowest row in the lowest
+				// bits, so the byte holding the last random row is only partly random.
+				let bits = random_bits.saturating_sub(i * 8).min(8);
+				*data = if bits == 0 {
+					0
+				} else {
+					rng.r#gen::<u8>() & ((1u16 << bits) - 1) as u8
+				};
+			});
+	}
+
+	Ok(column)
+}
@@ -1,6 +1,6 @@
 // Copyright 2024-2025 Irreducible Inc.
 
-use std::iter;
+use std::{collections::HashMap, iter};
 
 use binius_maybe_rayon::prelude::*;
 use binius_utils::checked_arithmetics::checked_int_div;
@@ -85,10 +85,141 @@ pub fn powers<F: Field>(val: F) -> impl Iterator<Item = F> {
 	iter::successors(Some(F::ONE), move |&power| Some(power * val))
 }
 
+/// Raises `base` to a fixed exponent by right-to-left square-and-multiply.
+///
+/// This is the reference exponentiation; prefer [`pow_windowed`] with a precomputed
+/// [`PowWindowTable`] when the same exponent is used many times.
+#[inline]
+pub fn pow_const<F: Field>(mut base: F, mut exp: u128) -> F {
+	let mut acc = F::ONE;
+	while exp > 0 {
+		if exp & 1 == 1 {
+			acc *= base;
+		}
+		base = base.square();
+		exp >>= 1;
+	}
+	acc
+}
+
+/// A fixed exponent split into 4-bit windows, most significant window first.
+///
+/// The exponent is public, so the sequence of squarings and multiplications performed by
+/// [`pow_windowed`] depends only on the table and never on the base.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PowWindowTable {
+	exp: u128,
+	windows: Vec<u8>,
+}
+
+impl PowWindowTable {
+	/// Number of exponent bits consumed per window.
+	pub const WINDOW_BITS: usize = 4;
+
+	pub fn new(exp: u128) -> Self {
+		let n_bits = (u128::BITS - exp.leading_zeros()) as usize;
+		let n_windows = n_bits.div_ceil(Self::WINDOW_BITS);
+		let windows = (0..n_windows)
+			.rev()
+			.map(|i| ((exp >> (i * Self::WINDOW_BITS)) & 0xf) as u8)
+			.collect();
+		Self { exp, windows }
+	}
+
+	/// The exponent this table was built for.
+	pub const fn exp(&self) -> u128 {
+		self.exp
+	}
+}
+
+/// Raises `base` to the exponent described by `table` using fixed 4-bit windows.
+///
+/// Every window costs four squarings and one multiplication, including all-zero windows, so a
+/// 128-bit exponent takes at most 128 squarings and 46 multiplications.
+#[inline]
+pub fn pow_windowed<F: Field>(base: F, table: &PowWindowTable) -> F {
+	let mut powers = [F::ONE; 1 << PowWindowTable::WINDOW_BITS];
+	let mut power = F::ONE;
+	for slot in powers.iter_mut().skip(1) {
+		power *= base;
+		*slot = power;
+	}
+
+	let mut acc = F::ONE;
+	for &window in &table.windows {
+		for _ in 0..PowWindowTable::WINDOW_BITS {
+			acc = acc.square();
+		}
+		acc *= powers[window as usize];
+	}
+	acc
+}
+
+/// Whether the square matrix `m` is MDS: every square submatrix, of any size and taken from any
+/// choice of rows and columns, is nonsingular. In particular no entry may be zero.
+///
+/// Minors are computed bottom-up: each `k x k` minor is expanded along its first row into `k - 1`
+/// minors cached from the previous size, so every minor costs `k` multiplications. The cache for
+/// one size holds `C(n, k)^2` minors, which peaks near a million at `n = 12` (and passes 10^8 at
+/// `n = 16`), so `n` is limited to at most 12.
+pub fn is_mds<F: Field>(m: &[Vec<F>]) -> bool {
+	let n = m.len();
+	assert!(n > 0 && m.iter().all(|row| row.len() == n), "is_mds needs a non-empty square matrix");
+	assert!(n <= 12, "is_mds supports at most 12 rows");
+
+	// Minors keyed by (row mask, column mask); sizes 1 and 2 onwards share the layout.
+	let mut minors = HashMap::new();
+	for (r, row) in m.iter().enumerate() {
+		for (c, &v) in row.iter().enumerate() {
+			if v == F::ZERO {
+				return false;
+			}
+			minors.insert((1u16 << r, 1u16 << c), v);
+		}
+	}
+
+	for size in 2..=n as u32 {
+		let masks: Vec<u16> = (0..=u16::MAX >> (16 - n))
+			.filter(|mask| mask.count_ones() == size)
+			.collect();
+		let mut next = HashMap::with_capacity(masks.len() * masks.len());
+		for &rows in &masks {
+			let first = rows.trailing_zeros() as usize;
+			let rows_tail = rows & (rows - 1);
+			for &cols in &masks {
+				let mut det = F::ZERO;
+				let mut col_bits = cols;
+				let mut sign_negative = false;
+				while col_bits != 0 {
+					let c = col_bits.trailing_zeros();
+					col_bits &= col_bits - 1;
+					let term = m[first][c as usize] * minors[&(rows_tail, cols & !(1 << c))];
+					det = if sign_negative {
+						det - term
+					} else {
+						det + term
+					};
+					sign_negative = !sign_negative;
+				}
+				if det == F::ZERO {
+					return false;
+				}
+				next.insert((rows, cols), det);
+			}
+		}
+		minors = next;
+	}
+	true
+}
+
 #[cfg(test)]
 mod tests {
+	use rand::{SeedableRng, rngs::StdRng};
+
 	use super::*;
-	use crate::PackedBinaryField4x32b;
+	use crate::{
+		BinaryField, BinaryField32b, BinaryField64b, BinaryField128b, PackedBinaryField4x32b,
+	};
 
 	type P = PackedBinaryField4x32b;
 	type F = <P as PackedField>::Scalar;
@@ -171,4 +302,87 @@ mod tests {
 
 		assert_eq!(result, expected);
 	}
+
+	// 1 / (x_i + y_j) with all x_i, y_j distinct: every submatrix is again Cauchy, hence MDS.
+	// The points are the first 2n powers of the generator.
+	fn cauchy<F: BinaryField>(n: usize) -> Vec<Vec<F>> {
+		let points: Vec<F> = powers(F::MULTIPLICATIVE_GENERATOR).take(2 * n).collect();
+		let (xs, ys) = points.split_at(n);
+		xs.iter()
+			.map(|&x| ys.iter().map(|&y| (x + y).invert().unwrap()).collect())
+			.collect()
+	}
+
+	#[test]
+	fn test_is_mds_accepts_cauchy() {
+		for n in 1..=6 {
+			assert!(is_mds(&cauchy::<BinaryField32b>(n)), "n={n}");
+		}
+		assert!(is_mds(&cauchy::<BinaryField64b>(8)));
+	}
+
+	#[test]
+	fn test_is_mds_rejects() {
+		let f = |v: u32| BinaryField32b::new(v);
+
+		let mut identity = vec![vec![F::ZERO; 3]; 3];
+		for (i, row) in identity.iter_mut().enumerate() {
+			row[i] = F::ONE;
+		}
+		assert!(!is_mds(&identity));
+
+		// No zero entries and nonsingular, but the top-left 2x2 minor vanishes.
+		let m = vec![
+			vec![f(1), f(1), f(2)],
+			vec![f(1), f(1), f(3)],
+			vec![f(2), f(3), f(1)],
+		];
+		assert!(!is_mds(&m));
+
+		// Row 3 is the sum of rows 0..3 of a Cauchy matrix, so the full determinant vanishes
+		// even though every entry is nonzero.
+		let mut m = cauchy::<BinaryField32b>(4);
+		m[3] = (0..4).map(|c| m[0][c] + m[1][c] + m[2][c]).collect();
+		assert!(m[3].iter().all(|&v| v != F::ZERO));
+		assert!(!is_mds(&m));
+	}
+
+	#[test]
+	#[should_panic(expected = "at most 12 rows")]
+	fn test_is_mds_rejects_more_than_12_rows() {
+		is_mds(&cauchy::<BinaryField32b>(13));
+	}
+
+	fn check_pow_windowed_matches_pow_const<F: Field>(exps: &[u128]) {
+		let mut rng = StdRng::seed_from_u64(0);
+		for &exp in exps {
+			let table = PowWindowTable::new(exp);
+			assert_eq!(table.exp(), exp);
+			for _ in 0..16 {
+				let base = F::random(&mut rng);
+				assert_eq!(pow_windowed(base, &table), pow_const(base, exp));
+			}
+			assert_eq!(pow_windowed(F::ZERO, &table), pow_const(F::ZERO, exp));
+		}
+	}
+
+	#[test]
+	fn test_pow_windowed_matches_pow_const() {
+		let exps = [
+			0,
+			1,
+			7,
+			0x10,
+			0xff,
+			0x4924_9249,
+			0xdb6d_b6db_6db6_db6d,
+			u128::MAX,
+		];
+		check_pow_windowed_matches_pow_const::<BinaryField32b>(&exps);
+		check_pow_windowed_matches_pow_const::<BinaryField64b>(&exps);
+		check_pow_windowed_matches_pow_const::<BinaryField128b>(&exps);
+		check_pow_windowed_matches_pow_const::<BinaryField128b>(&[
+			0x4924_9249_2492_4924_9249_2492_4924_9249,
+		]);
+	}
 }
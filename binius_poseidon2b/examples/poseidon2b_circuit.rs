@@ -4,10 +4,16 @@
 
 #![allow(deprecated)]
 
-use std::array;
+use std::{
+	array,
+	time::{Duration, Instant},
+};
 
-use anyhow::Result;
-use binius_circuits::builder::{ConstraintSystemBuilder, types::U};
+use anyhow::{Context, Result, bail};
+use binius_circuits::{
+	builder::{ConstraintSystemBuilder, types::U},
+	hades::{self, sbox::SboxGadget},
+};
 use binius_core::{constraint_system, fiat_shamir::HasherChallenger, oracle::OracleId};
 use binius_field::{BinaryField32b, BinaryField64b, BinaryField128b, tower::CanonicalTowerFamily};
 use binius_hal::make_portable_backend;
@@ -28,14 +34,23 @@ struct Args {
 	// field size in bit, supported are: 32, 64, 128
 	#[arg(long, default_value_t = 32, value_parser = value_parser!(u32).range(32..129))]
 	n: u32,
-	// state size t
-	#[arg(long, default_value_t = 24, value_parser = value_parser!(u32).range(4..25))]
+	// state size t; widths without a circuit module (e.g. t=32) fail with the supported list
+	#[arg(long, default_value_t = 24, value_parser = value_parser!(u32).range(4..))]
 	t: u32,
+	/// Constrain x^7 through committed x^2, x^4, x^6 (degree 2) instead of one degree-7 check.
+	/// Only n=64, t=8 supports it. The proof is larger: 254.3 KiB instead of 161.8 KiB for 256
+	/// permutations.
+	#[arg(long, default_value_t = false)]
+	split_sbox: bool,
+	/// Prove and verify every supported (n, t) instance and print a table of proof sizes and
+	/// prover times. `--n` and `--t` are ignored.
+	#[arg(long, default_value_t = false)]
+	all: bool,
 }
 
-fn main() -> Result<()> {
-	const SECURITY_BITS: usize = 100;
+const SECURITY_BITS: usize = 100;
 
+fn main() -> Result<()> {
 	adjust_thread_pool()
 		.as_ref()
 		.expect("failed to init thread pool");
@@ -44,45 +59,94 @@ fn main() -> Result<()> {
 
 	let _guard = init_tracing().expect("failed to initialize tracing");
 
+	if args.all {
+		println!("Verifying {} Poseidon2b permutations per instance", args.n_permutations);
+		println!("{:>5} {:>4} {:>12} {:>12}", "n", "t", "proof size", "prove time");
+		for &(n, t) in hades::supported_instances() {
+			let (proof_size, prove_time) = prove_and_verify(&args, n, t)?;
+			println!(
+				"{n:>5} {t:>4} {:>12} {:>12}",
+				ByteSize::b(proof_size as u64).to_string(),
+				format!("{:.2?}", prove_time)
+			);
+		}
+		return Ok(());
+	}
+
+	if !hades::is_supported(args.n, args.t) {
+		bail!(
+			"unsupported combination n={}, t={}; supported (n, t): {:?}",
+			args.n,
+			args.t,
+			hades::supported_instances()
+		);
+	}
+	if args.split_sbox && (args.n, args.t) != (64, 8) {
+		bail!("--split-sbox is only supported for n=64, t=8, got n={}, t={}", args.n, args.t);
+	}
+
 	println!("Verifying {} Poseidon2b permutations", args.n_permutations);
 
-	let log_n_permutations = log2_ceil_usize(args.n_permutations as usize);
+	let (proof_size, _) = prove_and_verify(&args, args.n, args.t)?;
+	println!("Proof size: {}", ByteSize::b(proof_size as u64));
+	Ok(())
+}
+
+/// Proves and verifies `args.n_permutations` permutations of the `(n, t)` instance, returning the
+/// proof size in bytes and the prover time.
+fn prove_and_verify(args: &Args, n: u32, t: u32) -> Result<(usize, Duration)> {
+	let n_permutations = args.n_permutations as usize;
+	let log_n_permutations = log2_ceil_usize(n_permutations);
+	// The trace has 2^log_n_permutations rows; the ones past n_permutations hold the all-zero
+	// dummy state and are not part of the statement.
+	let n_padding = (1 << log_n_permutations) - n_permutations;
+	if n_padding > 0 && !args.all {
+		println!(
+			"Padding to {} rows with {n_padding} dummy permutations of the zero state",
+			1 << log_n_permutations
+		);
+	}
 
 	let allocator = bumpalo::Bump::new();
 	let mut builder = ConstraintSystemBuilder::new_with_witness(&allocator);
 
-	let trace_gen_scope = tracing::info_span!("generating trace").entered();
+	let trace_gen_scope = tracing::info_span!("generating trace", n, t).entered();
 
-	match args.n {
+	match n {
 		32 => {
-			if args.t == 16 {
-				init_8x32_512(&mut builder, log_n_permutations);
-			} else if args.t == 24 {
-				init_8x32_768(&mut builder, log_n_permutations);
+			if t == 16 {
+				init_8x32_512(&mut builder, log_n_permutations, n_permutations);
+			} else if t == 24 {
+				init_8x32_768(&mut builder, log_n_permutations, n_permutations);
 			} else {
-				println!("Unsupported combination.");
+				unreachable!("checked by is_supported");
 			}
 		}
 
 		64 => {
-			if args.t == 8 {
-				init_8x64_512(&mut builder, log_n_permutations);
-			} else if args.t == 12 {
-				init_8x64_768(&mut builder, log_n_permutations);
+			if t == 8 {
+				let gadget = if args.split_sbox {
+					SboxGadget::Split
+				} else {
+					SboxGadget::Direct
+				};
+				init_8x64_512(&mut builder, log_n_permutations, n_permutations, gadget);
+			} else if t == 12 {
+				init_8x64_768(&mut builder, log_n_permutations, n_permutations);
 			} else {
-				println!("Unsupported combination.");
+				unreachable!("checked by is_supported");
 			}
 		}
 		128 => {
-			if args.t == 4 {
-				init_8x128_512(&mut builder, log_n_permutations);
-			} else if args.t == 6 {
-				init_8x128_768(&mut builder, log_n_permutations);
+			if t == 4 {
+				init_8x128_512(&mut builder, log_n_permutations, n_permutations);
+			} else if t == 6 {
+				init_8x128_768(&mut builder, log_n_permutations, n_permutations);
 			} else {
-				println!("Unsupported combination.");
+				unreachable!("checked by is_supported");
 			}
 		}
-		_ => println!("Unsupported combination."),
+		_ => unreachable!("checked by is_supported"),
 	}
 
 	drop(trace_gen_scope);
@@ -94,6 +158,7 @@ fn main() -> Result<()> {
 
 	let backend = make_portable_backend();
 
+	let prove_start = Instant::now();
 	let proof =
 		constraint_system::prove::<
 			U,
@@ -103,8 +168,8 @@ fn main() -> Result<()> {
 			HasherChallenger<Groestl256>,
 			_,
 		>(&constraint_system, args.log_inv_rate as usize, SECURITY_BITS, &[], witness, &backend)?;
-
-	println!("Proof size: {}", ByteSize::b(proof.get_proof_size() as u64));
+	let prove_time = prove_start.elapsed();
+	let proof_size = proof.get_proof_size();
 
 	constraint_system::verify::<
 		U,
@@ -112,16 +177,18 @@ fn main() -> Result<()> {
 		Groestl256,
 		Groestl256ByteCompression,
 		HasherChallenger<Groestl256>,
-	>(&constraint_system, args.log_inv_rate as usize, SECURITY_BITS, &[], proof)?;
-	Ok(())
+	>(&constraint_system, args.log_inv_rate as usize, SECURITY_BITS, &[], proof)
+	.with_context(|| format!("n={n}, t={t} failed to verify"))?;
+	Ok((proof_size, prove_time))
 }
 
-fn init_8x32_768(mut builder: &mut ConstraintSystemBuilder, log_size: usize) {
+fn init_8x32_768(mut builder: &mut ConstraintSystemBuilder, log_size: usize, n_rows: usize) {
 	let state_in: [OracleId; 24] = array::from_fn(|i| {
-		binius_circuits::unconstrained::unconstrained::<BinaryField32b>(
+		binius_circuits::unconstrained::unconstrained_padded::<BinaryField32b>(
 			&mut builder,
 			format!("p_in_{i}"),
 			log_size,
+			n_rows,
 		)
 		.unwrap()
 	});
@@ -129,24 +196,26 @@ fn init_8x32_768(mut builder: &mut ConstraintSystemBuilder, log_size: usize) {
 		binius_circuits::hades::poseidon2b_x7_32_768::permutation(&mut builder, log_size, state_in);
 }
 
-fn init_8x32_512(mut builder: &mut ConstraintSystemBuilder, log_size: usize) {
+fn init_8x32_512(mut builder: &mut ConstraintSystemBuilder, log_size: usize, n_rows: usize) {
 	let state_in: [OracleId; 16] = array::from_fn(|i| {
-		binius_circuits::unconstrained::unconstrained::<BinaryField32b>(
+		binius_circuits::unconstrained::unconstrained_padded::<BinaryField32b>(
 			&mut builder,
 			format!("p_in_{i}"),
 			log_size,
+			n_rows,
 		)
 		.unwrap()
 	});
 	let _state_out =
 		binius_circuits::hades::poseidon2b_x7_32_512::permutation(&mut builder, log_size, state_in);
 }
-fn init_8x64_768(mut builder: &mut ConstraintSystemBuilder, log_size: usize) {
+fn init_8x64_768(mut builder: &mut ConstraintSystemBuilder, log_size: usize, n_rows: usize) {
 	let state_in: [OracleId; 12] = array::from_fn(|i| {
-		binius_circuits::unconstrained::unconstrained::<BinaryField64b>(
+		binius_circuits::unconstrained::unconstrained_padded::<BinaryField64b>(
 			&mut builder,
 			format!("p_in_{i}"),
 			log_size,
+			n_rows,
 		)
 		.unwrap()
 	});
@@ -154,24 +223,35 @@ fn init_8x64_768(mut builder: &mut ConstraintSystemBuilder, log_size: usize) {
 		binius_circuits::hades::poseidon2b_x7_64_768::permutation(&mut builder, log_size, state_in);
 }
 
-fn init_8x64_512(mut builder: &mut ConstraintSystemBuilder, log_size: usize) {
+fn init_8x64_512(
+	mut builder: &mut ConstraintSystemBuilder,
+	log_size: usize,
+	n_rows: usize,
+	gadget: SboxGadget,
+) {
 	let state_in: [OracleId; 8] = array::from_fn(|i| {
-		binius_circuits::unconstrained::unconstrained::<BinaryField64b>(
+		binius_circuits::unconstrained::unconstrained_padded::<BinaryField64b>(
 			&mut builder,
 			format!("p_in_{i}"),
 			log_size,
+			n_rows,
 		)
 		.unwrap()
 	});
-	let _state_out =
-		binius_circuits::hades::poseidon2b_x7_64_512::permutation(&mut builder, log_size, state_in);
+	let _state_out = binius_circuits::hades::poseidon2b_x7_64_512::permutation_with_sbox(
+		&mut builder,
+		log_size,
+		state_in,
+		gadget,
+	);
 }
-fn init_8x128_768(mut builder: &mut ConstraintSystemBuilder, log_size: usize) {
+fn init_8x128_768(mut builder: &mut ConstraintSystemBuilder, log_size: usize, n_rows: usize) {
 	let state_in: [OracleId; 6] = array::from_fn(|i| {
-		binius_circuits::unconstrained::unconstrained::<BinaryField128b>(
+		binius_circuits::unconstrained::unconstrained_padded::<BinaryField128b>(
 			&mut builder,
 			format!("p_in_{i}"),
 			log_size,
+			n_rows,
 		)
 		.unwrap()
 	});
@@ -181,12 +261,13 @@ fn init_8x128_768(mut builder: &mut ConstraintSystemBuilder, log_size: usize) {
 		state_in,
 	);
 }
-fn init_8x128_512(mut builder: &mut ConstraintSystemBuilder, log_size: usize) {
+fn init_8x128_512(mut builder: &mut ConstraintSystemBuilder, log_size: usize, n_rows: usize) {
 	let state_in: [OracleId; 4] = array::from_fn(|i| {
-		binius_circuits::unconstrained::unconstrained::<BinaryField128b>(
+		binius_circuits::unconstrained::unconstrained_padded::<BinaryField128b>(
 			&mut builder,
 			format!("p_in_{i}"),
 			log_size,
+			n_rows,
 		)
 		.unwrap()
 	});
@@ -0,0 +1,85 @@
+#!/usr/bin/env python3
+"""Independent reference for the native Poseidon2b permutation.
+
+Implements the binary tower field (GF(2) < GF(2^2) < ... < GF(2^128), with X_k^2 = X_k * X_{k-1} + 1
+and X_0 = 1) with plain Python integers and evaluates Poseidon2b with dense matrix products, reading
+the round constants and matrices straight from `src/params.rs`. It shares no code with the Rust
+crate, so its intermediate states are usable as known-answer tests.
+
+usage: kat_gen.py <module> <hex input elements...>
+e.g.   kat_gen.py params64_t8 0 1 2 3 4 5 6 7
+"""
+
+import re
+import sys
+from pathlib import Path
+
+
+def tower_mul(a, b, bits):
+    if bits == 1:
+        return a & b
+    half = bits // 2
+    mask = (1 << half) - 1
+    a0, a1 = a & mask, a >> half
+    b0, b1 = b & mask, b >> half
+    # X_{k-1}: the generator of the half-width subfield, i.e. its top-half unit (1 in GF(2)).
+    x_prev = 1 if half == 1 else 1 << (half // 2)
+    lo = tower_mul(a0, b0, half)
+    hi = tower_mul(a1, b1, half)
+    mid = tower_mul(a0, b1, half) ^ tower_mul(a1, b0, half)
+    # (a0 + a1 X)(b0 + b1 X) with X^2 = X * x_prev + 1
+    return (lo ^ hi) | ((mid ^ tower_mul(hi, x_prev, half)) << half)
+
+
+def load_module(name):
+    src = (Path(__file__).resolve().parent.parent / "src" / "params.rs").read_text()
+    start = src.index(f"pub mod {name} {{")
+    body = src[start : src.index("\npub mod ", start + 1) if "\npub mod " in src[start + 1 :] else len(src)]
+
+    def const(key):
+        return int(re.search(rf"pub const {key}: usize = (\d+);", body).group(1))
+
+    def table(key):
+        m = re.search(rf"pub const {key}: [^=]*=\s*\[(.*?)\];", body, re.S)
+        rows = re.findall(r"\[([^\[\]]*)\]", m.group(1))
+        return [[int(v, 16) for v in re.findall(r"0x[0-9a-fA-F]+", row)] for row in rows]
+
+    bits = int(re.search(r"\[\[u(\d+);", body).group(1))
+    return bits, const("STATE_SIZE"), const("R_F"), const("R_P"), table("RC"), table("MDS_FULL"), table("MDS_PARTIAL")
+
+
+def mat_mul(m, s, bits):
+    out = []
+    for row in m:
+        acc = 0
+        for c, x in zip(row, s):
+            acc ^= tower_mul(c, x, bits)
+        out.append(acc)
+    return out
+
+
+def pow7(x, bits):
+    x2 = tower_mul(x, x, bits)
+    x4 = tower_mul(x2, x2, bits)
+    return tower_mul(tower_mul(x, x2, bits), x4, bits)
+
+
+def main():
+    name, inputs = sys.argv[1], [int(v, 16) for v in sys.argv[2:]]
+    bits, t, rf, rp, rc, mds_full, mds_partial = load_module(name)
+    assert len(inputs) == t, f"expected {t} input elements"
+
+    state = mat_mul(mds_full, inputs, bits)
+    print("initial_mds", " ".join(hex(x) for x in state))
+    for r in range(rf + rp):
+        if r < rf // 2 or r >= rf // 2 + rp:
+            state = [pow7(x ^ rc[i][r], bits) for i, x in enumerate(state)]
+            state = mat_mul(mds_full, state, bits)
+        else:
+            state[0] = pow7(state[0] ^ rc[0][r], bits)
+            state = mat_mul(mds_partial, state, bits)
+        print(f"round_{r}", " ".join(hex(x) for x in state))
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,235 @@
+// Struct-of-arrays batch permutation.
+//
+// A `BatchState` holds N states column-wise: column `i` is element `i` of every state, so each
+// round step is a straight loop over N lanes that the compiler can vectorize, instead of N short
+// loops over `t` elements.
+
+use crate::{FieldConst, Poseidon2b};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchState<F> {
+    // cols[i][lane] is element `i` of state `lane`.
+    cols: Vec<Vec<F>>,
+    lanes: usize,
+}
+
+impl<F: FieldConst> BatchState<F> {
+    /// `lanes` all-zero states of width `t`.
+    pub fn new(t: usize, lanes: usize) -> Self {
+        Self {
+            cols: vec![vec![F::default(); lanes]; t],
+            lanes,
+        }
+    }
+
+    /// Transposes `states` (all of the same width) into columns.
+    pub fn from_states(states: &[Vec<F>]) -> Self {
+        let t = states.first().map_or(0, Vec::len);
+        assert!(states.iter().all(|s| s.len() == t), "states differ in width");
+        Self {
+            cols: (0..t).map(|i| states.iter().map(|s| s[i]).collect()).collect(),
+            lanes: states.len(),
+        }
+    }
+
+    /// Transposes back into one `Vec` per state.
+    pub fn to_states(&self) -> Vec<Vec<F>> {
+        (0..self.lanes)
+            .map(|lane| self.cols.iter().map(|col| col[lane]).collect())
+            .collect()
+    }
+
+    pub fn t(&self) -> usize {
+        self.cols.len()
+    }
+
+    pub fn lanes(&self) -> usize {
+        self.lanes
+    }
+
+    /// Element `i` of every state.
+    pub fn column(&self, i: usize) -> &[F] {
+        &self.cols[i]
+    }
+
+    pub fn column_mut(&mut self, i: usize) -> &mut [F] {
+        &mut self.cols[i]
+    }
+}
+
+impl<F: FieldConst> Poseidon2b<F> {
+    /// Permutes every state in `batch`; same result as `permute` on each of them. The linear
+    /// layers take the same path as `permute` (the table on [`Poseidon2b`]), one column at a time.
+    pub fn permute_batch_soa(&self, batch: &mut BatchState<F>) {
+        assert_eq!(batch.t(), self.t, "batch width differs from t");
+        let half_f = self.rf / 2;
+        let mut scratch = vec![vec![F::default(); batch.lanes]; self.t];
+
+        if self.apply_initial_mds {
+            self.mul_mds_full_soa(&mut batch.cols, &mut scratch);
+        }
+        for r in 0..self.rf + self.rp {
+            if r < half_f || r >= half_f + self.rp {
+                for (col, rc) in batch.cols.iter_mut().zip(&self.rc) {
+                    for x in col.iter_mut() {
+                        *x = x.add(rc[r]).pow_alpha();
+                    }
+                }
+                self.mul_mds_full_soa(&mut batch.cols, &mut scratch);
+            } else {
+                for x in batch.cols[0].iter_mut() {
+                    *x = x.add(self.rc[0][r]).pow_alpha();
+                }
+                self.mul_mds_partial_soa(&mut batch.cols, &mut scratch);
+            }
+        }
+    }
+
+    fn mul_mds_full_soa(&self, cols: &mut Vec<Vec<F>>, scratch: &mut Vec<Vec<F>>) {
+        if let Some(m4) = &self.mds4 {
+            for lane in 0..cols[0].len() {
+                let y = m4.mul([cols[0][lane], cols[1][lane], cols[2][lane], cols[3][lane]]);
+                for (col, y) in cols.iter_mut().zip(y) {
+                    col[lane] = y;
+                }
+            }
+            return;
+        }
+
+        if let Some(fast) = &self.mds_full_fast {
+            // y_j = (D + M) x_j + M S as in `mul_mds_full`, with S and then M S kept in
+            // scratch[0..4].
+            let shared = &mut scratch[..4];
+            for (i, s) in shared.iter_mut().enumerate() {
+                s.copy_from_slice(&cols[i]);
+                for col in cols[i + 4..].iter().step_by(4) {
+                    for (s, &x) in s.iter_mut().zip(col) {
+                        *s = s.add(x);
+                    }
+                }
+            }
+            for lane in 0..shared[0].len() {
+                let y = fast.off.mul([
+                    shared[0][lane],
+                    shared[1][lane],
+                    shared[2][lane],
+                    shared[3][lane],
+                ]);
+                for (s, y) in shared.iter_mut().zip(y) {
+                    s[lane] = y;
+                }
+            }
+            for block in cols.chunks_exact_mut(4) {
+                for lane in 0..block[0].len() {
+                    let y = fast.diag_plus_off.mul([
+                        block[0][lane],
+                        block[1][lane],
+                        block[2][lane],
+                        block[3][lane],
+                    ]);
+                    for ((col, y), s) in block.iter_mut().zip(y).zip(shared.iter()) {
+                        col[lane] = y.add(s[lane]);
+                    }
+                }
+            }
+            return;
+        }
+
+        mat_mul_soa(&self.mds_full, cols, scratch);
+    }
+
+    fn mul_mds_partial_soa(&self, cols: &mut Vec<Vec<F>>, scratch: &mut Vec<Vec<F>>) {
+        if self.mds_partial_dense {
+            mat_mul_soa(&self.mds_partial, cols, scratch);
+            return;
+        }
+        // y_i = sum + (mu_i - 1) * x_i, as in `mul_mds_partial`, with the sum kept in scratch[0].
+        let sum = &mut scratch[0];
+        sum.copy_from_slice(&cols[0]);
+        for col in &cols[1..] {
+            for (s, &x) in sum.iter_mut().zip(col) {
+                *s = s.add(x);
+            }
+        }
+        for (i, col) in cols.iter_mut().enumerate() {
+            let mu_minus_one = self.mds_partial[i][i].add(F::from_u8(1));
+            for (x, &s) in col.iter_mut().zip(sum.iter()) {
+                *x = s.add(mu_minus_one.mul(*x));
+            }
+        }
+    }
+}
+
+// cols <- m * cols, one output column at a time, accumulating lane-wise into scratch.
+fn mat_mul_soa<F: FieldConst>(m: &[Vec<F>], cols: &mut Vec<Vec<F>>, scratch: &mut Vec<Vec<F>>) {
+    for (out, row) in scratch.iter_mut().zip(m) {
+        out.fill(F::default());
+        for (&c, col) in row.iter().zip(cols.iter()) {
+            for (o, &x) in out.iter_mut().zip(col) {
+                *o = o.add(c.mul(x));
+            }
+        }
+    }
+    std::mem::swap(cols, scratch);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        params_128_t4, params_128_t6, params_32_t16, params_32_t24, params_64_t12, params_64_t8,
+        BinaryField32b, BinaryField64b, FieldOps, PreparedParams,
+    };
+
+    fn check_batch_matches_per_state<F: FieldConst>(params: PreparedParams<F>, lanes: usize) {
+        let perm = Poseidon2b::new(params);
+        let states: Vec<Vec<F>> = (0..lanes)
+            .map(|lane| {
+                (0..perm.t)
+                    .map(|i| F::from_u8((lane * perm.t + i) as u8))
+                    .collect()
+            })
+            .collect();
+
+        let mut batch = BatchState::from_states(&states);
+        perm.permute_batch_soa(&mut batch);
+
+        let expected: Vec<Vec<F>> = states
+            .into_iter()
+            .map(|mut s| {
+                perm.permute(&mut s);
+                s
+            })
+            .collect();
+        assert_eq!(batch.to_states(), expected);
+    }
+
+    #[test]
+    fn batch_soa_matches_per_state() {
+        check_batch_matches_per_state(params_32_t16(), 5);
+        check_batch_matches_per_state(params_32_t24(), 2);
+        check_batch_matches_per_state(params_64_t8(), 8);
+        check_batch_matches_per_state(params_64_t12(), 3);
+        check_batch_matches_per_state(params_128_t4(), 3);
+        check_batch_matches_per_state(params_128_t6(), 2);
+    }
+
+    #[test]
+    fn batch_soa_dense_paths() {
+        let mut params = params_64_t8();
+        params.mds_full[5][2] += BinaryField64b::from_u8(1);
+        check_batch_matches_per_state(params, 4);
+        check_batch_matches_per_state(params_64_t8().with_identity_mds(), 4);
+    }
+
+    #[test]
+    fn batch_state_round_trip() {
+        let states: Vec<Vec<BinaryField32b>> = (0..3)
+            .map(|lane| (0..4).map(|i| FieldOps::from_u8(10 * lane + i)).collect())
+            .collect();
+        let batch = BatchState::from_states(&states);
+        assert_eq!((batch.t(), batch.lanes()), (4, 3));
+        assert_eq!(batch.column(1), &[states[0][1], states[1][1], states[2][1]]);
+        assert_eq!(batch.to_states(), states);
+    }
+}
@@ -0,0 +1,259 @@
+// Plain-text parameter loader (and the matching writer, `to_hex_params`).
+//
+// The format is the `params.rs` tables pasted into a file under three section headers:
+//
+//   # comment
+//   rc
+//   [0x3ab6, 0x1926, ...],
+//   ...
+//   mds_full
+//   ...
+//   mds_partial
+//   ...
+//
+// Each non-empty line below a header is one table row. Brackets, commas and whitespace between
+// values are ignored, and the `0x` prefix is optional. `rc` has `t` rows of `rf + rp` values, the
+// matrices `t` rows of `t` values, as in `prep_params`.
+
+use crate::{FieldConst, PreparedParams};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HexParamsError {
+    /// Line `line` (1-based) is a table row before any section header.
+    RowOutsideSection { line: usize },
+    /// A section header appears twice.
+    DuplicateSection { section: &'static str },
+    /// A value on line `line` is not hex, does not fit the field, or is not canonical.
+    BadValue { line: usize, value: String },
+    /// A section is missing or has the wrong number of rows or columns.
+    WrongShape {
+        section: &'static str,
+        expected: (usize, usize),
+        got: (usize, usize),
+    },
+}
+
+impl std::fmt::Display for HexParamsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HexParamsError::RowOutsideSection { line } => {
+                write!(f, "line {line}: table row before any section header")
+            }
+            HexParamsError::DuplicateSection { section } => {
+                write!(f, "section {section} appears twice")
+            }
+            HexParamsError::BadValue { line, value } => {
+                write!(f, "line {line}: {value:?} is not a field element in hex")
+            }
+            HexParamsError::WrongShape {
+                section,
+                expected,
+                got,
+            } => write!(
+                f,
+                "section {} is {}x{}, expected {}x{}",
+                section, got.0, got.1, expected.0, expected.1
+            ),
+        }
+    }
+}
+
+impl std::error::Error for HexParamsError {}
+
+const SECTIONS: [&str; 3] = ["rc", "mds_full", "mds_partial"];
+
+/// Parses `text` into parameters for width `t` with `rf` full and `rp` partial rounds.
+pub fn from_hex_params<F: FieldConst>(
+    text: &str,
+    t: usize,
+    rf: usize,
+    rp: usize,
+) -> Result<PreparedParams<F>, HexParamsError> {
+    let mut tables: [Option<Vec<Vec<F>>>; 3] = [None, None, None];
+    let mut current = None;
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(s) = SECTIONS.iter().position(|&name| name == line) {
+            if tables[s].is_some() {
+                return Err(HexParamsError::DuplicateSection {
+                    section: SECTIONS[s],
+                });
+            }
+            tables[s] = Some(Vec::new());
+            current = Some(s);
+            continue;
+        }
+        let s = current.ok_or(HexParamsError::RowOutsideSection { line: i + 1 })?;
+        let row = parse_row(line).map_err(|value| HexParamsError::BadValue {
+            line: i + 1,
+            value,
+        })?;
+        tables[s].as_mut().expect("current section exists").push(row);
+    }
+
+    let [rc, mds_full, mds_partial] = tables;
+    Ok(PreparedParams {
+        t,
+        rf,
+        rp,
+        rc: check_shape(SECTIONS[0], rc, (t, rf + rp))?,
+        mds_full: check_shape(SECTIONS[1], mds_full, (t, t))?,
+        mds_partial: check_shape(SECTIONS[2], mds_partial, (t, t))?,
+    })
+}
+
+/// Writes `params` in the format `from_hex_params` reads, one `[0x.., ],` line per row.
+pub fn to_hex_params<F: FieldConst>(params: &PreparedParams<F>) -> String {
+    let tables = [&params.rc, &params.mds_full, &params.mds_partial];
+    let mut out = String::new();
+    for (name, table) in SECTIONS.iter().zip(tables) {
+        out += name;
+        out.push('\n');
+        for row in table {
+            let values: Vec<String> = row.iter().map(|&x| format!("{:#x}", to_u128(x))).collect();
+            out += &format!("[{}, ],\n", values.join(", "));
+        }
+    }
+    out
+}
+
+fn to_u128<F: FieldConst>(x: F) -> u128 {
+    let mut bytes = [0u8; 16];
+    x.write_le_bytes(&mut bytes[..F::BYTES]);
+    u128::from_le_bytes(bytes)
+}
+
+// Returns the offending token on failure.
+fn parse_row<F: FieldConst>(line: &str) -> Result<Vec<F>, String> {
+    line.split(|c: char| c == ',' || c == '[' || c == ']' || c.is_whitespace())
+        .filter(|token| !token.is_empty())
+        .map(|token| {
+            let digits = token.strip_prefix("0x").unwrap_or(token);
+            let v = u128::from_str_radix(digits, 16).map_err(|_| token.to_string())?;
+            let bytes = v.to_le_bytes();
+            if bytes[F::BYTES..].iter().any(|&b| b != 0) {
+                return Err(token.to_string());
+            }
+            F::from_le_bytes_checked(&bytes[..F::BYTES]).ok_or_else(|| token.to_string())
+        })
+        .collect()
+}
+
+fn check_shape<F>(
+    section: &'static str,
+    table: Option<Vec<Vec<F>>>,
+    expected: (usize, usize),
+) -> Result<Vec<Vec<F>>, HexParamsError> {
+    let table = table.unwrap_or_default();
+    let cols = table.first().map_or(0, Vec::len);
+    if table.len() != expected.0 || table.iter().any(|row| row.len() != expected.1) {
+        return Err(HexParamsError::WrongShape {
+            section,
+            expected,
+            got: (table.len(), cols),
+        });
+    }
+    Ok(table)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{params, params_128_t6, params_32_t16, params_64_t8, Poseidon2b};
+    use binius_field::BinaryField64b;
+
+    type F = BinaryField64b;
+
+    // Writes the rows the way `params.rs` does.
+    fn table<const N: usize>(name: &str, rows: &[[u64; N]]) -> String {
+        let mut out = format!("{name}\n");
+        for row in rows {
+            let values: Vec<String> = row.iter().map(|v| format!("{v:#x}")).collect();
+            out += &format!("[{}, ],\n", values.join(", "));
+        }
+        out
+    }
+
+    fn shipped_64_t8_text() -> String {
+        use params::params64_t8 as p;
+        format!(
+            "# params64_t8\n{}\n{}{}",
+            table("rc", &p::RC),
+            table("mds_full", &p::MDS_FULL),
+            table("mds_partial", &p::MDS_PARTIAL)
+        )
+    }
+
+    #[test]
+    fn loads_shipped_table() {
+        use params::params64_t8 as p;
+        let loaded: PreparedParams<F> =
+            from_hex_params(&shipped_64_t8_text(), p::STATE_SIZE, p::R_F, p::R_P).unwrap();
+        let baked = params_64_t8();
+        assert_eq!(loaded, baked);
+
+        let mut a: Vec<F> = (0..8).map(|i| F::new(0x1234_5678 * (i + 1))).collect();
+        let mut b = a.clone();
+        Poseidon2b::new(loaded).permute(&mut a);
+        Poseidon2b::new(baked).permute(&mut b);
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn written_params_read_back_equal() {
+        fn round_trip<F: FieldConst>(params: PreparedParams<F>) {
+            let text = to_hex_params(&params);
+            let read = from_hex_params(&text, params.t, params.rf, params.rp).unwrap();
+            assert_eq!(read, params);
+            let mut other = params.clone();
+            other.rc[0][0] += F::from_u8(1);
+            assert_ne!(read, other);
+        }
+        round_trip(params_32_t16());
+        round_trip(params_64_t8());
+        round_trip(params_128_t6());
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        let text = shipped_64_t8_text();
+        let load = |text: &str, rp: usize| from_hex_params::<F>(text, 8, 10, rp).err();
+
+        assert_eq!(
+            load(&text, 30),
+            Some(HexParamsError::WrongShape {
+                section: "rc",
+                expected: (8, 40),
+                got: (8, 39),
+            })
+        );
+        let without_partial = &text[..text.find("mds_partial").unwrap()];
+        assert_eq!(
+            load(without_partial, 29),
+            Some(HexParamsError::WrongShape {
+                section: "mds_partial",
+                expected: (8, 8),
+                got: (0, 0),
+            })
+        );
+        assert_eq!(
+            load("0x1\nrc\n", 29),
+            Some(HexParamsError::RowOutsideSection { line: 1 })
+        );
+        assert_eq!(
+            load("rc\nrc\n", 29),
+            Some(HexParamsError::DuplicateSection { section: "rc" })
+        );
+        // 65 bits do not fit BinaryField64b.
+        assert_eq!(
+            load("rc\n0x1, 0x10000000000000000\n", 29),
+            Some(HexParamsError::BadValue {
+                line: 2,
+                value: "0x10000000000000000".to_string(),
+            })
+        );
+    }
+}
@@ -0,0 +1,1960 @@
+pub mod batch;
+pub mod hex_params;
+pub mod params;
+pub mod sponge;
+pub mod transcript;
+
+use binius_field::{
+    BinaryField, BinaryField128b, BinaryField32b, BinaryField64b, BinaryField8b, ExtensionField,
+    Field,
+};
+use rand_chacha::{
+    rand_core::{RngCore, SeedableRng},
+    ChaCha20Rng,
+};
+use std::fmt::Debug;
+use std::ops::{Add, AddAssign, Mul};
+use std::sync::OnceLock;
+
+
+// The engine's field operations. Every field that embeds `BinaryField8b` gets them from the
+// blanket impl below (`.add`/`.mul` are the `binius_field::Field` operators), so the same `F` that
+// drives the circuits drives the native permutation. The trait itself only asks for the
+// arithmetic, so a test can wrap a field to watch what the engine does with it.
+pub trait FieldOps:
+    'static
+    + Copy
+    + Debug
+    + Default
+    + PartialEq
+    + Send
+    + Sync
+    + Add<Output = Self>
+    + AddAssign
+    + Mul<Output = Self>
+{
+    fn safe_square(self) -> Self;
+    fn inv(self) -> Self;
+    /// The `BinaryField8b` element `v`, i.e. underlier value `v` in the tower basis.
+    fn from_u8(v: u8) -> Self;
+    /// `x^7` as `x * x^2 * x^4`: two squarings and two multiplications. 7 = 0b111 has three set
+    /// bits and squaring only doubles the exponent, so at least two general multiplications are
+    /// needed, and 7 has no addition chain shorter than four steps; the chain is minimal.
+    #[inline(always)]
+    fn pow_alpha(self) -> Self {
+        let x2 = self.safe_square();
+        let x4 = x2.safe_square();
+        self.mul(x2).mul(x4) // x^7
+    }
+}
+
+impl<F: ExtensionField<BinaryField8b>> FieldOps for F {
+    #[inline(always)]
+    fn safe_square(self) -> Self {
+        self.square()
+    }
+    #[inline(always)]
+    fn inv(self) -> Self {
+        Field::invert(&self).unwrap()
+    }
+    #[inline(always)]
+    fn from_u8(v: u8) -> Self {
+        Self::from(BinaryField8b::new(v))
+    }
+}
+
+// Allows populating constants from "native representation" (consistent with the tower basis in binius_field).
+pub trait FieldConst: FieldOps {
+    type Raw: Copy;
+    /// Width of `Raw` in bytes.
+    const BYTES: usize;
+    fn from_raw(v: Self::Raw) -> Self;
+    /// `from_raw` for values from untrusted input: `None` unless `v` is canonical, i.e. has no
+    /// bits set above the field's `N_BITS`. The 32/64/128-bit fields fill their underlier, so for
+    /// them every value is canonical; the check matters for a narrower field in a wider `Raw`.
+    fn from_raw_checked(v: Self::Raw) -> Option<Self>;
+    /// Inverse of `from_raw`: the native representation as an integer.
+    fn to_raw(self) -> Self::Raw;
+    /// Reads the native representation from exactly `Self::BYTES` little-endian bytes.
+    fn from_le_bytes(bytes: &[u8]) -> Self;
+    /// `from_le_bytes` through `from_raw_checked`.
+    fn from_le_bytes_checked(bytes: &[u8]) -> Option<Self>;
+    /// Writes the native representation into exactly `Self::BYTES` little-endian bytes.
+    fn write_le_bytes(self, out: &mut [u8]);
+}
+
+macro_rules! impl_field_const {
+    ($ty:ty, $raw:ty) => {
+        impl FieldConst for $ty {
+            type Raw = $raw;
+            const BYTES: usize = std::mem::size_of::<$raw>();
+            #[inline(always)]
+            fn from_raw(v: Self::Raw) -> Self { Self::from(v) }
+            fn from_raw_checked(v: Self::Raw) -> Option<Self> {
+                raw_is_canonical(v as u128, <$ty as BinaryField>::N_BITS).then(|| Self::from(v))
+            }
+            #[inline(always)]
+            fn to_raw(self) -> Self::Raw { self.val() }
+            fn from_le_bytes(bytes: &[u8]) -> Self {
+                Self::from(<$raw>::from_le_bytes(bytes.try_into().expect("wrong byte length")))
+            }
+            fn from_le_bytes_checked(bytes: &[u8]) -> Option<Self> {
+                let raw = <$raw>::from_le_bytes(bytes.try_into().expect("wrong byte length"));
+                Self::from_raw_checked(raw)
+            }
+            fn write_le_bytes(self, out: &mut [u8]) {
+                out.copy_from_slice(&self.val().to_le_bytes());
+            }
+        }
+    };
+}
+
+// An underlier value is canonical for an `n_bits`-bit field if nothing above bit `n_bits` is set.
+fn raw_is_canonical(v: u128, n_bits: usize) -> bool {
+    n_bits >= 128 || v >> n_bits == 0
+}
+
+impl_field_const!(BinaryField32b, u32);
+impl_field_const!(BinaryField64b, u64);
+impl_field_const!(BinaryField128b, u128);
+
+
+// State serialization: each element is its underlier in little-endian, elements in state order.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StateBytesError {
+    WrongLength { expected: usize, got: usize },
+    /// Element `index` is not a canonical encoding (see `FieldConst::from_raw_checked`).
+    NonCanonical { index: usize },
+}
+
+impl std::fmt::Display for StateBytesError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StateBytesError::WrongLength { expected, got } => {
+                write!(f, "state encoding has {got} bytes, expected {expected}")
+            }
+            StateBytesError::NonCanonical { index } => {
+                write!(f, "state element {index} is not a canonical field encoding")
+            }
+        }
+    }
+}
+
+impl std::error::Error for StateBytesError {}
+
+pub fn state_to_bytes<F: FieldConst>(state: &[F]) -> Vec<u8> {
+    let mut out = vec![0u8; state.len() * F::BYTES];
+    for (x, chunk) in state.iter().zip(out.chunks_exact_mut(F::BYTES)) {
+        x.write_le_bytes(chunk);
+    }
+    out
+}
+
+pub fn state_from_bytes<F: FieldConst>(bytes: &[u8], t: usize) -> Result<Vec<F>, StateBytesError> {
+    let expected = t * F::BYTES;
+    if bytes.len() != expected {
+        return Err(StateBytesError::WrongLength {
+            expected,
+            got: bytes.len(),
+        });
+    }
+    bytes
+        .chunks_exact(F::BYTES)
+        .enumerate()
+        .map(|(index, chunk)| {
+            F::from_le_bytes_checked(chunk).ok_or(StateBytesError::NonCanonical { index })
+        })
+        .collect()
+}
+
+
+// Poseidon2b parameter structure
+
+/// Equality compares `t`, `rf`, `rp` and every table, so parameters loaded from different sources
+/// (baked-in, `hex_params`) can be checked with one `assert_eq!`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreparedParams<F: FieldConst> {
+    pub t: usize,
+    pub rf: usize,
+    pub rp: usize,
+    pub rc: Vec<Vec<F>>,
+    pub mds_full: Vec<Vec<F>>,
+    pub mds_partial: Vec<Vec<F>>,
+}
+
+// A 4x4 block ((A B),(B A)) of MDS_FULL, kept as the 2x2 blocks A, B and A + B.
+#[derive(Clone, Copy)]
+struct M4Block<F: FieldConst> {
+    a: [[F; 2]; 2],
+    b: [[F; 2]; 2],
+    ab: [[F; 2]; 2],
+}
+
+impl<F: FieldConst> M4Block<F> {
+    // Takes A and B from the top two rows of the block at (r0, c0); `MdsFullFast::new` checks
+    // the bottom two, the t=4 path trusts M4 to have the ((A B),(B A)) form.
+    fn read(mds_full: &[Vec<F>], r0: usize, c0: usize) -> Self {
+        let half = |c: usize| {
+            [
+                [mds_full[r0][c], mds_full[r0][c + 1]],
+                [mds_full[r0 + 1][c], mds_full[r0 + 1][c + 1]],
+            ]
+        };
+        Self::from_halves(half(c0), half(c0 + 2))
+    }
+
+    fn from_halves(a: [[F; 2]; 2], b: [[F; 2]; 2]) -> Self {
+        let ab = [
+            [a[0][0].add(b[0][0]), a[0][1].add(b[0][1])],
+            [a[1][0].add(b[1][0]), a[1][1].add(b[1][1])],
+        ];
+        Self { a, b, ab }
+    }
+
+    // Entry (i, j) of the whole 4x4 block.
+    fn entry(&self, i: usize, j: usize) -> F {
+        let half = if i / 2 == j / 2 { &self.a } else { &self.b };
+        half[i % 2][j % 2]
+    }
+
+    fn sum(&self, other: &Self) -> Self {
+        let add = |x: &[[F; 2]; 2], y: &[[F; 2]; 2]| {
+            [
+                [x[0][0].add(y[0][0]), x[0][1].add(y[0][1])],
+                [x[1][0].add(y[1][0]), x[1][1].add(y[1][1])],
+            ]
+        };
+        Self::from_halves(add(&self.a, &other.a), add(&self.b, &other.b))
+    }
+
+    // 3 block-mults: P1=A*x1, P2=B*x2, P3=(A+B)*(x1+x2); y1=P1+P2, y2=P3+y1
+    #[inline(always)]
+    fn mul(&self, x: [F; 4]) -> [F; 4] {
+        let (a, b, ab) = (&self.a, &self.b, &self.ab);
+        let s0 = x[0].add(x[2]);
+        let s1 = x[1].add(x[3]);
+
+        let y0 = a[0][0].mul(x[0]).add(a[0][1].mul(x[1]));
+        let y1 = a[1][0].mul(x[0]).add(a[1][1].mul(x[1]));
+        let y0 = y0.add(b[0][0].mul(x[2]).add(b[0][1].mul(x[3])));
+        let y1 = y1.add(b[1][0].mul(x[2]).add(b[1][1].mul(x[3])));
+
+        let y2 = ab[0][0].mul(s0).add(ab[0][1].mul(s1)).add(y0);
+        let y3 = ab[1][0].mul(s0).add(ab[1][1].mul(s1)).add(y1);
+        [y0, y1, y2, y3]
+    }
+}
+
+// The shipped t=8/12/16/24 MDS_FULL matrices repeat one block D on the diagonal (M4 with its
+// entries doubled as integers) and one block M everywhere else, both of the ((A B),(B A)) form.
+struct MdsFullFast<F: FieldConst> {
+    // D + M, applied to each block of the state
+    diag_plus_off: M4Block<F>,
+    // M, applied once to the sum of all blocks
+    off: M4Block<F>,
+}
+
+impl<F: FieldConst> MdsFullFast<F> {
+    fn new(mds_full: &[Vec<F>], t: usize) -> Option<Self> {
+        if t < 8 || t % 4 != 0 {
+            return None;
+        }
+
+        let diag = M4Block::read(mds_full, 0, 0);
+        let off = M4Block::read(mds_full, 0, 4);
+
+        // `mul_mds_full` only reads the top two rows of the first two blocks, so every entry of
+        // the matrix must match them; anything else takes the dense product.
+        for (r, row) in mds_full.iter().enumerate() {
+            for (c, &entry) in row.iter().enumerate() {
+                let block = if r / 4 == c / 4 { &diag } else { &off };
+                if entry != block.entry(r % 4, c % 4) {
+                    return None;
+                }
+            }
+        }
+
+        Some(Self {
+            diag_plus_off: diag.sum(&off),
+            off,
+        })
+    }
+}
+
+// `RF` and `RP` are the full and partial round counts; an `rc_raw` with fewer than `RF + RP`
+// columns is rejected at compile time.
+pub fn prep_params<F: FieldConst, const T: usize, const R: usize, const RF: usize, const RP: usize>(
+    rc_raw: &[[F::Raw; R]; T],
+    mds_full_raw: &[[F::Raw; T]; T],
+    mds_partial_raw: &[[F::Raw; T]; T],
+) -> PreparedParams<F> {
+    const { assert!(RF + RP <= R, "round constant table has fewer columns than RF + RP") };
+    PreparedParams {
+        t: T,
+        rf: RF,
+        rp: RP,
+        rc: rc_raw
+            .iter()
+            .map(|row| row.iter().copied().map(F::from_raw).collect())
+            .collect(),
+        mds_full: mds_full_raw
+            .iter()
+            .map(|row| row.iter().copied().map(F::from_raw).collect())
+            .collect(),
+        mds_partial: mds_partial_raw
+            .iter()
+            .map(|row| row.iter().copied().map(F::from_raw).collect())
+            .collect(),
+    }
+}
+
+impl<F: FieldConst> PreparedParams<F> {
+    /// Replaces both MDS layers by the identity, leaving only round constants and S-boxes.
+    /// Debugging aid for isolating S-box handling from the linear layers; not a secure instance.
+    pub fn with_identity_mds(mut self) -> Self {
+        let identity: Vec<Vec<F>> = (0..self.t)
+            .map(|r| {
+                (0..self.t)
+                    .map(|c| if r == c { F::from_u8(1) } else { F::default() })
+                    .collect()
+            })
+            .collect();
+        self.mds_full = identity.clone();
+        self.mds_partial = identity;
+        self
+    }
+
+    /// Sets every round constant to zero. Combined with `with_identity_mds`, a permutation is
+    /// just `x -> x^7` applied `rf + rp` times to element 0 and `rf` times to the others.
+    pub fn with_zero_rc(mut self) -> Self {
+        for lane in self.rc.iter_mut() {
+            lane.fill(F::default());
+        }
+        self
+    }
+
+    /// Changes the round counts for ablation studies, e.g. `rp = 0` for full rounds only or
+    /// `rf = 0` for partial rounds between the two MDS_FULL layers. The schedule reads the first
+    /// `rf + rp` columns of the unchanged `rc`. Not a secure instance.
+    pub fn with_rounds(mut self, rf: usize, rp: usize) -> Self {
+        assert!(rf.is_multiple_of(2), "rf={rf} must be even to split into two halves");
+        let columns = self.rc.first().map_or(0, Vec::len);
+        assert!(
+            rf + rp <= columns,
+            "rf + rp = {} exceeds the {} round constant columns",
+            rf + rp,
+            columns
+        );
+        self.rf = rf;
+        self.rp = rp;
+        self
+    }
+}
+
+// 6 instance parameters (directly reusing tables from binius_poseidon2b/hades)
+pub fn params_32_t16() -> PreparedParams<BinaryField32b> {
+    use params::params32_t16 as p;
+    prep_params::<_, { p::STATE_SIZE }, { p::R_F + p::R_P }, { p::R_F }, { p::R_P }>(&p::RC, &p::MDS_FULL, &p::MDS_PARTIAL)
+}
+pub fn params_32_t24() -> PreparedParams<BinaryField32b> {
+    use params::params32_t24 as p;
+    prep_params::<_, { p::STATE_SIZE }, { p::R_F + p::R_P }, { p::R_F }, { p::R_P }>(&p::RC, &p::MDS_FULL, &p::MDS_PARTIAL)
+}
+pub fn params_64_t8() -> PreparedParams<BinaryField64b> {
+    use params::params64_t8 as p;
+    prep_params::<_, { p::STATE_SIZE }, { p::R_F + p::R_P }, { p::R_F }, { p::R_P }>(&p::RC, &p::MDS_FULL, &p::MDS_PARTIAL)
+}
+pub fn params_64_t12() -> PreparedParams<BinaryField64b> {
+    use params::params64_t12 as p;
+    prep_params::<_, { p::STATE_SIZE }, { p::R_F + p::R_P }, { p::R_F }, { p::R_P }>(&p::RC, &p::MDS_FULL, &p::MDS_PARTIAL)
+}
+pub fn params_128_t4() -> PreparedParams<BinaryField128b> {
+    use params::params128_t4 as p;
+    prep_params::<_, { p::STATE_SIZE }, { p::R_F + p::R_P }, { p::R_F }, { p::R_P }>(&p::RC, &p::MDS_FULL, &p::MDS_PARTIAL)
+}
+pub fn params_128_t6() -> PreparedParams<BinaryField128b> {
+    use params::params128_t6 as p;
+    prep_params::<_, { p::STATE_SIZE }, { p::R_F + p::R_P }, { p::R_F }, { p::R_P }>(&p::RC, &p::MDS_FULL, &p::MDS_PARTIAL)
+}
+
+/// The six shipped instances, for code that loops over all of them. The field differs between
+/// instances, so callers match on the variant to get the typed `params_*` table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instance {
+    B32T16,
+    B32T24,
+    B64T8,
+    B64T12,
+    B128T4,
+    B128T6,
+}
+
+impl Instance {
+    pub const ALL: [Instance; 6] = [
+        Instance::B32T16,
+        Instance::B32T24,
+        Instance::B64T8,
+        Instance::B64T12,
+        Instance::B128T4,
+        Instance::B128T6,
+    ];
+
+    pub fn all() -> impl ExactSizeIterator<Item = Instance> {
+        Self::ALL.into_iter()
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Instance::B32T16 => "GF(2^32) t=16",
+            Instance::B32T24 => "GF(2^32) t=24",
+            Instance::B64T8 => "GF(2^64) t=8",
+            Instance::B64T12 => "GF(2^64) t=12",
+            Instance::B128T4 => "GF(2^128) t=4",
+            Instance::B128T6 => "GF(2^128) t=6",
+        }
+    }
+
+    /// `(t, rf, rp)` from the parameter tables.
+    pub fn shape(self) -> (usize, usize, usize) {
+        use params::*;
+        match self {
+            Instance::B32T16 => (params32_t16::STATE_SIZE, params32_t16::R_F, params32_t16::R_P),
+            Instance::B32T24 => (params32_t24::STATE_SIZE, params32_t24::R_F, params32_t24::R_P),
+            Instance::B64T8 => (params64_t8::STATE_SIZE, params64_t8::R_F, params64_t8::R_P),
+            Instance::B64T12 => (params64_t12::STATE_SIZE, params64_t12::R_F, params64_t12::R_P),
+            Instance::B128T4 => (params128_t4::STATE_SIZE, params128_t4::R_F, params128_t4::R_P),
+            Instance::B128T6 => (params128_t6::STATE_SIZE, params128_t6::R_F, params128_t6::R_P),
+        }
+    }
+}
+
+/// Seeded GF(2^32) instance with `t = 32` and caller-chosen round counts, for research on wider
+/// sponges only. No round counts have been derived for t=32, so nothing checks `rf` and `rp`
+/// against a security level, and there are no shipped tables or circuit module for it. Its
+/// `mds_full` has the D/M block form, so it takes the structured product like the shipped t=16
+/// and t=24 instances.
+pub fn poseidon2b_32_t32_with_rounds(rf: usize, rp: usize) -> Poseidon2b<BinaryField32b> {
+    Poseidon2b::from_seed(32, rf, rp, b"poseidon2b GF(2^32) t=32")
+}
+
+
+// Seeded parameters (experimentation only, no security claim)
+
+// M4 from the Poseidon2b paper, the t=4 MDS_FULL.
+const M4: [[u8; 4]; 4] = [[5, 7, 1, 3], [4, 6, 1, 1], [1, 3, 5, 7], [1, 1, 4, 6]];
+
+// M4 for t=4, circ(2*M4, M4, ..., M4) for t=4k with the 2*M4 entries doubled as integers.
+fn structured_mds_full<F: FieldConst>(t: usize) -> Vec<Vec<F>> {
+    assert!(
+        t.is_multiple_of(4),
+        "structured MDS_FULL needs t to be a multiple of 4, got t={t}"
+    );
+    (0..t)
+        .map(|r| {
+            (0..t)
+                .map(|c| {
+                    let m = M4[r % 4][c % 4];
+                    if t > 4 && r / 4 == c / 4 {
+                        F::from_u8(2 * m)
+                    } else {
+                        F::from_u8(m)
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+// det(J + diag(d_i)) = prod(d_i) * (1 + sum 1/d_i) in characteristic 2, with d_i = mu_i - 1.
+fn mds_partial_is_invertible<F: FieldConst>(diagonal: &[F]) -> bool {
+    let one = F::from_u8(1);
+    let mut sum = F::default();
+    for &mu in diagonal {
+        let d = mu.add(one);
+        if d == F::default() {
+            return false;
+        }
+        sum = sum.add(d.inv());
+    }
+    sum != one
+}
+
+/// Scratch buffers for the MDS_FULL layer, reused across permutations.
+///
+/// `permute` and `mul_mds_full` allocate their own, once per call; pass one of these to the
+/// `_with_workspace` variants instead and a loop over many permutations allocates nothing.
+/// A workspace sized for one `t` still works for another: it is resized on first use.
+#[derive(Debug, Clone, Default)]
+pub struct Poseidon2bWorkspace<F: FieldConst> {
+    scratch: Vec<F>,
+}
+
+impl<F: FieldConst> Poseidon2bWorkspace<F> {
+    /// A workspace preallocated for state width `t`.
+    pub fn with_capacity(t: usize) -> Self {
+        Self {
+            scratch: vec![F::default(); t],
+        }
+    }
+
+    #[inline(always)]
+    fn scratch(&mut self, t: usize) -> &mut [F] {
+        self.scratch.resize(t, F::default());
+        &mut self.scratch
+    }
+}
+
+// Poseidon2b Permutation
+//
+// Immutable after `new` and `Send + Sync`: one instance can be shared across threads.
+
+/// The linear layers pick their evaluation path from `t` and the matrices at construction:
+///
+/// | layer         | condition                                    | path                        |
+/// |---------------|----------------------------------------------|-----------------------------|
+/// | `mds_full`    | `t == 4`                                     | 2x2 block trick on M4       |
+/// | `mds_full`    | `t >= 8`, multiple of 4, D/M block structure | `(D + M) x_j + M sum_k x_k` |
+/// | `mds_full`    | anything else (t=6, t=10, a broken matrix)   | dense `t x t` product       |
+/// | `mds_partial` | off-diagonal entries all one                 | `sum + (mu_i - 1) * x_i`    |
+/// | `mds_partial` | anything else (`with_identity_mds`)          | dense `t x t` product       |
+///
+/// `uses_structured_mds_full` reports which of the two `t >= 8` paths `new` picked.
+pub struct Poseidon2b<F: FieldConst> {
+    pub t: usize,
+    rf: usize,
+    rp: usize,
+    rc: Vec<Vec<F>>,
+    mds_full: Vec<Vec<F>>,
+    mds_partial: Vec<Vec<F>>,
+    // A, B and A + B of a t=4 MDS_FULL, read once here rather than on every call
+    mds4: Option<M4Block<F>>,
+    mds_full_fast: Option<MdsFullFast<F>>,
+    // `mds_partial` is not all ones off the diagonal, so the diagonal-only product does not apply.
+    mds_partial_dense: bool,
+    // `Minit = MDS_FULL` before the first round; see `with_initial_mds`.
+    apply_initial_mds: bool,
+}
+
+impl<F: FieldConst> Poseidon2b<F> {
+    pub fn new(params: PreparedParams<F>) -> Self {
+        let mds4 = (params.t == 4).then(|| M4Block::read(&params.mds_full, 0, 0));
+        let mds_full_fast = MdsFullFast::new(&params.mds_full, params.t);
+        let mds_partial_dense = params.mds_partial.iter().enumerate().any(|(r, row)| {
+            row.iter()
+                .enumerate()
+                .any(|(c, &m)| r != c && m != F::from_u8(1))
+        });
+        Self {
+            t: params.t,
+            rf: params.rf,
+            rp: params.rp,
+            rc: params.rc,
+            mds_full: params.mds_full,
+            mds_partial: params.mds_partial,
+            mds4,
+            mds_full_fast,
+            mds_partial_dense,
+            apply_initial_mds: true,
+        }
+    }
+
+    /// Turns the initial `MDS_FULL` layer on or off (on by default, as in the circuits). With it
+    /// off the first full round reads the input directly, for Poseidon2-style descriptions that
+    /// fold `Minit` elsewhere; `hades::poseidon2b_x7_64_512::permutation_with_initial_mds` has the
+    /// same toggle.
+    pub fn with_initial_mds(mut self, apply: bool) -> Self {
+        self.apply_initial_mds = apply;
+        self
+    }
+
+    /// Builds an instance whose constants are expanded from `seed` instead of the shipped tables.
+    /// Intended for experiments only.
+    ///
+    /// Derivation:
+    /// 1. `seed` (at most 32 bytes) is zero-padded on the right to a 32-byte key for ChaCha20
+    ///    (`rand_chacha::ChaCha20Rng::from_seed`, nonce 0, stream position 0).
+    /// 2. Round constants are drawn round-major: for `r in 0..rf + rp`, for `i in 0..t`,
+    ///    `rc[i][r]` is the next `F::BYTES` keystream bytes read as a little-endian native value.
+    ///    All `t` lanes are drawn for partial rounds too, so the table has the same shape as `RC`.
+    /// 3. The `mds_partial` diagonal `mu_0, ..., mu_{t-1}` is drawn next from the same stream, one
+    ///    element per lane read as in step 2, and the whole diagonal is drawn again until
+    ///    `J + diag(mu_i - 1)` is invertible (`mu_i != 1` and `sum 1/(mu_i - 1) != 1`). The
+    ///    off-diagonal entries are one. Invertibility is the only property checked; the shipped
+    ///    `MDS_PARTIAL` diagonals are fixed tables and are not reproduced by this.
+    ///
+    /// `mds_full` is not seeded: it is M4 for `t = 4` and `circ(2*M4, M4, ..., M4)` for `t = 4k`,
+    /// the shipped `MDS_FULL` at every shipped `t` that is a multiple of 4. Panics for other `t`.
+    pub fn from_seed(t: usize, rf: usize, rp: usize, seed: &[u8]) -> Self {
+        assert!(seed.len() <= 32, "seed must be at most 32 bytes");
+        let mut key = [0u8; 32];
+        key[..seed.len()].copy_from_slice(seed);
+        let mut rng = ChaCha20Rng::from_seed(key);
+        let mut bytes = vec![0u8; F::BYTES];
+        let mut draw = || {
+            rng.fill_bytes(&mut bytes);
+            F::from_le_bytes(&bytes)
+        };
+
+        let mut rc = vec![vec![F::default(); rf + rp]; t];
+        for r in 0..rf + rp {
+            for lane in rc.iter_mut() {
+                lane[r] = draw();
+            }
+        }
+
+        let diagonal = loop {
+            let diagonal: Vec<F> = (0..t).map(|_| draw()).collect();
+            if mds_partial_is_invertible(&diagonal) {
+                break diagonal;
+            }
+        };
+        let mds_partial = (0..t)
+            .map(|r| {
+                (0..t)
+                    .map(|c| if r == c { diagonal[r] } else { F::from_u8(1) })
+                    .collect()
+            })
+            .collect();
+
+        Self::new(PreparedParams {
+            t,
+            rf,
+            rp,
+            rc,
+            mds_full: structured_mds_full(t),
+            mds_partial,
+        })
+    }
+
+    /// Whether `mul_mds_full` uses the D/M block product for this `t >= 8` matrix; `false` means
+    /// the dense product (always the case for t=6 and any other width the block form misses).
+    pub fn uses_structured_mds_full(&self) -> bool {
+        self.mds_full_fast.is_some()
+    }
+
+    /// General field multiplications one `permute` performs, on the `mds_full` path `new` picked
+    /// (the table on [`Poseidon2b`]): 2 per S-box, 12 for the t=4 block trick, `12 + 3t` for the
+    /// `D + M` product (one M4 block product per block of the state plus one on the block sum),
+    /// `t * t` for the dense product, and `t` for the partial layer (`t * t` when it is dense).
+    /// Squarings (two per S-box) are not counted; they are linear over GF(2^n).
+    pub fn mul_count(&self) -> usize {
+        let t = self.t;
+        let mds_full = if self.mds4.is_some() {
+            12
+        } else if self.mds_full_fast.is_some() {
+            12 + 3 * t
+        } else {
+            t * t
+        };
+        let mds_partial = if self.mds_partial_dense { t * t } else { t };
+        let initial = if self.apply_initial_mds { mds_full } else { 0 };
+        let full_rounds = 2 * (self.rf / 2);
+        initial + full_rounds * (2 * t + mds_full) + self.rp * (2 + mds_partial)
+    }
+
+    /// Generic over `F` but monomorphized per field like any Rust generic, with every field op on
+    /// the hot path `#[inline(always)]`. Per-field wrappers measured no faster, so call this (or
+    /// `permute_with_workspace`) directly.
+    ///
+    /// Takes anything that derefs mutably to `[F]`, so `&mut [F; T]`, `&mut Vec<F>` and
+    /// `&mut Box<[F]>` need no `&mut x[..]`. Panics if the state is not `t` elements long.
+    pub fn permute(&self, mut state: impl AsMut<[F]>) {
+        let state = state.as_mut();
+        assert_eq!(state.len(), self.t, "state has {} elements, t={}", state.len(), self.t);
+        self.permute_with_workspace(state, &mut Poseidon2bWorkspace::default());
+    }
+
+    /// `permute` with its scratch taken from `ws`; allocates nothing once `ws` is sized.
+    pub fn permute_with_workspace(&self, state: &mut [F], ws: &mut Poseidon2bWorkspace<F>) {
+        debug_assert_eq!(state.len(), self.t);
+        let half_f = self.rf / 2;
+        let mut round = 0usize;
+
+        // Minit = MDS_FULL
+        if self.apply_initial_mds {
+            self.mul_mds_full_with_workspace(state, ws);
+        }
+
+        // First half of full rounds
+        for _ in 0..half_f {
+            self.round_full(state, round, ws);
+            round += 1;
+        }
+
+        // Partial rounds
+        for _ in 0..self.rp {
+            self.round_partial(state, round);
+            round += 1;
+        }
+
+        // Second half of full rounds
+        for _ in 0..half_f {
+            self.round_full(state, round, ws);
+            round += 1;
+        }
+    }
+
+    /// The state after each step of `permute`: entry 0 follows the initial `MDS_FULL` (or is the
+    /// input, without `Minit`) and entry `r + 1` follows round `r`, so there are `rf + rp + 1`
+    /// entries and the last is the output.
+    ///
+    /// Round `r` uses column `r` of `rc` and is full when `r < rf / 2 || r >= rf / 2 + rp`,
+    /// partial otherwise; the circuits' `plain_permutation(state, r, ..)` ends at entry `r`.
+    pub fn permute_trace(&self, state: &[F]) -> Vec<Vec<F>> {
+        assert_eq!(state.len(), self.t, "state has {} elements, t={}", state.len(), self.t);
+        let half_f = self.rf / 2;
+        let mut ws = Poseidon2bWorkspace::with_capacity(self.t);
+        let mut state = state.to_vec();
+        if self.apply_initial_mds {
+            self.mul_mds_full_with_workspace(&mut state, &mut ws);
+        }
+        let mut trace = vec![state.clone()];
+        for r in 0..self.rf + self.rp {
+            if r < half_f || r >= half_f + self.rp {
+                self.round_full(&mut state, r, &mut ws);
+            } else {
+                self.round_partial(&mut state, r);
+            }
+            trace.push(state.clone());
+        }
+        trace
+    }
+
+    /// `hash_var`-style digest over native integers: `data` goes through `from_raw`, a `Pad10`
+    /// sponge returns one rate (`t - 1` elements) of output, and that comes back through `to_raw`.
+    /// The first element is `hash_var` of the converted input. Empty `data` is allowed and padded
+    /// like any other message, as in `hash_var`.
+    #[must_use]
+    pub fn hash_raw(&self, data: &[F::Raw]) -> Vec<F::Raw> {
+        let input: Vec<F> = data.iter().copied().map(F::from_raw).collect();
+        let mut sponge = sponge::Poseidon2bSponge::new(self, sponge::Padding::Pad10);
+        sponge.absorb(&input);
+        let rate = sponge.rate();
+        sponge
+            .finalize(rate)
+            .expect("Pad10 accepts any length")
+            .into_iter()
+            .map(F::to_raw)
+            .collect()
+    }
+
+    /// Permutes two states; same result as two `permute` calls.
+    pub fn permute_pair(&self, a: &mut [F], b: &mut [F]) {
+        self.permute_interleaved([a, b]);
+    }
+
+    /// Permutes four states; same result as four `permute` calls.
+    pub fn permute_quad(&self, a: &mut [F], b: &mut [F], c: &mut [F], d: &mut [F]) {
+        self.permute_interleaved([a, b, c, d]);
+    }
+
+    // Runs the round schedule of `permute` once for all `N` states, stepping them in lock-step so
+    // the independent S-box chains can overlap in the pipeline (no threads involved).
+    fn permute_interleaved<const N: usize>(&self, mut states: [&mut [F]; N]) {
+        let mut ws = Poseidon2bWorkspace::with_capacity(self.t);
+        let half_f = self.rf / 2;
+        for state in states.iter_mut() {
+            debug_assert_eq!(state.len(), self.t);
+            if self.apply_initial_mds {
+                self.mul_mds_full_with_workspace(state, &mut ws);
+            }
+        }
+
+        for r in 0..self.rf + self.rp {
+            if r < half_f || r >= half_f + self.rp {
+                for (i, lane) in self.rc.iter().enumerate() {
+                    for state in states.iter_mut() {
+                        state[i] = state[i].add(lane[r]).pow_alpha();
+                    }
+                }
+                for state in states.iter_mut() {
+                    self.mul_mds_full_with_workspace(state, &mut ws);
+                }
+            } else {
+                for state in states.iter_mut() {
+                    state[0] = state[0].add(self.rc[0][r]).pow_alpha();
+                }
+                for state in states.iter_mut() {
+                    self.mul_mds_partial(state);
+                }
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn round_full(&self, state: &mut [F], r: usize, ws: &mut Poseidon2bWorkspace<F>) {
+        // Round-constant add and S-box in one pass over the state.
+        for (x, lane) in state.iter_mut().zip(&self.rc) {
+            *x = x.add(lane[r]).pow_alpha();
+        }
+        self.mul_mds_full_with_workspace(state, ws);
+    }
+
+    #[inline(always)]
+    fn round_partial(&self, state: &mut [F], r: usize) {
+        state[0] = state[0].add(self.rc[0][r]);
+        state[0] = state[0].pow_alpha();
+        self.mul_mds_partial(state);
+    }
+
+    /// Two-to-one compression: permutes `left || right` and returns the first `t / 2` elements,
+    /// with no feed-forward. The `hades::merkle` circuit gadget uses the same layout (with
+    /// `params_32_t16`), so Merkle roots computed here match the circuit.
+    #[must_use]
+    pub fn compress(&self, left: &[F], right: &[F]) -> Vec<F> {
+        assert!(self.t.is_multiple_of(2), "compress needs an even t, got t={}", self.t);
+        let half = self.t / 2;
+        assert_eq!(left.len(), half, "left input must have t/2 elements");
+        assert_eq!(right.len(), half, "right input must have t/2 elements");
+
+        let mut state = Vec::with_capacity(self.t);
+        state.extend_from_slice(left);
+        state.extend_from_slice(right);
+        self.permute(&mut state);
+        state.truncate(half);
+        state
+    }
+
+    /// The `MDS_FULL` layer alone (also the initial `Minit`), on whichever path `new` picked.
+    pub fn mul_mds_full(&self, state: &mut [F]) {
+        self.mul_mds_full_with_workspace(state, &mut Poseidon2bWorkspace::default());
+    }
+
+    /// `mul_mds_full` with its scratch taken from `ws`.
+    #[inline(always)]
+    pub fn mul_mds_full_with_workspace(&self, state: &mut [F], ws: &mut Poseidon2bWorkspace<F>) {
+        // t=4: fast algorithm using the ((A B),(B,A)) structure of M4（12 times muls over GF instead of naive 16 times muls over GF）
+        if let Some(m4) = &self.mds4 {
+            let y = m4.mul([state[0], state[1], state[2], state[3]]);
+            state.copy_from_slice(&y);
+            return;
+        }
+
+        if self.t == 6 {
+
+            // t=6: naive O(n^2) matrix multiplication
+
+            let res = ws.scratch(self.t);
+            for (r, row) in self.mds_full.iter().enumerate() {
+                let mut acc = F::default();
+                for c in 0..self.t {
+                    acc = acc.add(row[c].mul(state[c]));
+                }
+                res[r] = acc;
+            }
+            state.copy_from_slice(res);
+            return;
+        }
+
+        if let Some(fast) = &self.mds_full_fast {
+
+            // t=8/12/16/24: D on the diagonal and M elsewhere, so with S the sum of all blocks
+            // y_j = D x_j + M (S - x_j) = (D + M) x_j + M S
+
+            let mut sum = [F::default(); 4];
+            for block in state.chunks_exact(4) {
+                for (s, &x) in sum.iter_mut().zip(block) {
+                    *s = s.add(x);
+                }
+            }
+            let shared = fast.off.mul(sum);
+
+            for block in state.chunks_exact_mut(4) {
+                let y = fast
+                    .diag_plus_off
+                    .mul([block[0], block[1], block[2], block[3]]);
+                for ((x, y), s) in block.iter_mut().zip(y).zip(shared) {
+                    *x = y.add(s);
+                }
+            }
+            return;
+        }
+
+        // Other t, or an MDS_FULL without the block structure: naive O(n^2) matrix multiplication
+
+        let res = ws.scratch(self.t);
+        for (r, row) in self.mds_full.iter().enumerate() {
+            let mut acc = F::default();
+            for c in 0..self.t {
+                acc = acc.add(row[c].mul(state[c]));
+            }
+            res[r] = acc;
+        }
+        state.copy_from_slice(res);
+    }
+
+    //$$y_i = (\mu_i - 1)x_i + \sum_{j=0}^{t-1} x_j$$
+    #[inline(always)]
+    fn mul_mds_partial(&self, state: &mut [F]) {
+        if self.mds_partial_dense {
+            let input = state.to_vec();
+            for (y, row) in state.iter_mut().zip(&self.mds_partial) {
+                *y = row
+                    .iter()
+                    .zip(&input)
+                    .fold(F::default(), |acc, (&m, &x)| acc.add(m.mul(x)));
+            }
+            return;
+        }
+        let mut sum = F::default();
+        for &x in state.iter() {
+            sum = sum.add(x);
+        }
+        for (i, (x, row)) in state.iter_mut().zip(&self.mds_partial).enumerate() {
+            let mu_minus_one = row[i].add(F::from_u8(1));
+            *x = sum.add(mu_minus_one.mul(*x));
+        }
+    }
+}
+
+
+// Plain (out-of-circuit) permutation shared with the `hades` circuit modules, so that the native
+// code and a circuit's `plain_permutation` can be checked against each other.
+
+pub trait PlainPermutation<F> {
+    const STATE_SIZE: usize;
+    fn permute_plain(state: &mut [F]);
+}
+
+/// Native GF(2^64) t=8 instance, the counterpart of `hades::poseidon2b_x7_64_512`.
+pub struct Poseidon2b64T8;
+
+impl PlainPermutation<BinaryField64b> for Poseidon2b64T8 {
+    const STATE_SIZE: usize = params::params64_t8::STATE_SIZE;
+
+    fn permute_plain(state: &mut [BinaryField64b]) {
+        static INSTANCE: OnceLock<Poseidon2b<BinaryField64b>> = OnceLock::new();
+        INSTANCE
+            .get_or_init(|| Poseidon2b::new(params_64_t8()))
+            .permute(state);
+    }
+}
+
+/// `t` independent uniform elements of `F`, each read from `F::BYTES` bytes of `rng`.
+#[cfg(feature = "rand")]
+pub fn random_state<F: FieldConst, R: RngCore>(t: usize, rng: &mut R) -> Vec<F> {
+    let mut bytes = vec![0u8; F::BYTES];
+    (0..t)
+        .map(|_| {
+            rng.fill_bytes(&mut bytes);
+            F::from_le_bytes(&bytes)
+        })
+        .collect()
+}
+
+/// Runs `n` pseudo-random states (fixed seed) through `A` and `B` and panics on the first mismatch.
+pub fn assert_plain_equiv<F, A, B>(n: usize)
+where
+    F: FieldConst,
+    A: PlainPermutation<F>,
+    B: PlainPermutation<F>,
+{
+    assert_eq!(A::STATE_SIZE, B::STATE_SIZE, "state sizes differ");
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    let mut bytes = vec![0u8; F::BYTES];
+    for _ in 0..n {
+        let input: Vec<F> = (0..A::STATE_SIZE)
+            .map(|_| {
+                rng.fill_bytes(&mut bytes);
+                F::from_le_bytes(&bytes)
+            })
+            .collect();
+        let mut a = input.clone();
+        let mut b = input.clone();
+        A::permute_plain(&mut a);
+        B::permute_plain(&mut b);
+        assert_eq!(a, b, "outputs differ for input {input:?}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{distributions::Standard, prelude::Distribution, rngs::StdRng, Rng, SeedableRng};
+
+    // `mul_mds_full` (t=4 block trick, `MdsFullFast` or dense fallback) against a plain product.
+    fn check_mds_full_matches_dense<F: FieldConst>(name: &str, params: PreparedParams<F>)
+    where
+        Standard: Distribution<F::Raw>,
+    {
+        let perm = Poseidon2b::new(params);
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..16 {
+            let input: Vec<F> = (0..perm.t).map(|_| F::from_raw(rng.gen())).collect();
+            let expected: Vec<F> = perm
+                .mds_full
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .zip(&input)
+                        .fold(F::default(), |acc, (&m, &x)| acc.add(m.mul(x)))
+                })
+                .collect();
+            let mut state = input.clone();
+            perm.mul_mds_full(&mut state);
+            assert_eq!(state, expected, "{name}: input {input:?}");
+        }
+    }
+
+    // The native tables are the ones in the `hades::poseidon2b_x7_*` circuit modules, so this
+    // covers every shipped MDS_FULL.
+    #[test]
+    fn structured_mds_full_matches_dense_for_all_instances() {
+        check_mds_full_matches_dense("32_t16", params_32_t16());
+        check_mds_full_matches_dense("32_t24", params_32_t24());
+        check_mds_full_matches_dense("64_t8", params_64_t8());
+        check_mds_full_matches_dense("64_t12", params_64_t12());
+        check_mds_full_matches_dense("128_t4", params_128_t4());
+        check_mds_full_matches_dense("128_t6", params_128_t6());
+    }
+
+    // Regression: the old fast path assumed C⊗M4 and got the shipped t=8..24 products wrong.
+    // Multiplying the unit vectors reads back every column of MDS_FULL.
+    #[test]
+    fn mds_full_fast_reproduces_every_column() {
+        fn check<F: FieldConst>(params: PreparedParams<F>) {
+            let perm = Poseidon2b::new(params);
+            assert!(perm.mds_full_fast.is_some(), "t={} takes the dense path", perm.t);
+            for c in 0..perm.t {
+                let mut state = vec![F::default(); perm.t];
+                state[c] = F::from_u8(1);
+                perm.mul_mds_full(&mut state);
+                let column: Vec<F> = perm.mds_full.iter().map(|row| row[c]).collect();
+                assert_eq!(state, column, "t={} column {}", perm.t, c);
+            }
+        }
+        check(params_64_t8());
+        check(params_64_t12());
+        check(params_32_t16());
+        check(params_32_t24());
+    }
+
+    fn check_state_bytes_round_trip<F: FieldConst>(t: usize) {
+        let state: Vec<F> = (0..t)
+            .map(|i| F::from_u8(i as u8).mul(F::from_u8(0x53)).add(F::from_u8(0xff)))
+            .collect();
+        let bytes = state_to_bytes(&state);
+        assert_eq!(bytes.len(), t * F::BYTES);
+        assert_eq!(state_from_bytes::<F>(&bytes, t), Ok(state));
+        assert_eq!(
+            state_from_bytes::<F>(&bytes[1..], t),
+            Err(StateBytesError::WrongLength {
+                expected: t * F::BYTES,
+                got: t * F::BYTES - 1
+            })
+        );
+    }
+
+    #[test]
+    fn state_bytes_round_trip() {
+        check_state_bytes_round_trip::<BinaryField32b>(16);
+        check_state_bytes_round_trip::<BinaryField64b>(8);
+        check_state_bytes_round_trip::<BinaryField128b>(4);
+    }
+
+    // x^7 = (x * x^2)^2 * x, a different minimal chain than `pow_alpha`'s.
+    fn pow_alpha_via_cube<F: FieldOps>(x: F) -> F {
+        let x3 = x.mul(x.safe_square());
+        x3.safe_square().mul(x)
+    }
+
+    fn check_pow_alpha_chains<F: FieldConst + Field>() {
+        let mut rng = ChaCha20Rng::from_seed([7; 32]);
+        let mut bytes = vec![0u8; F::BYTES];
+        let edge = [F::default(), F::from_u8(1), F::from_u8(2)];
+        let random = (0..256).map(|_| {
+            rng.fill_bytes(&mut bytes);
+            F::from_le_bytes(&bytes)
+        });
+        for x in edge.into_iter().chain(random) {
+            let y = x.pow_alpha();
+            assert_eq!(y, pow_alpha_via_cube(x), "x={x:?}");
+            assert_eq!(y, Field::pow(&x, [7u64]), "x={x:?}");
+        }
+    }
+
+    #[test]
+    fn pow_alpha_matches_other_chains() {
+        check_pow_alpha_chains::<BinaryField32b>();
+        check_pow_alpha_chains::<BinaryField64b>();
+        check_pow_alpha_chains::<BinaryField128b>();
+    }
+
+    #[test]
+    fn state_bytes_are_little_endian() {
+        let bytes = state_to_bytes(&[BinaryField32b::from_raw(0x0403_0201)]);
+        assert_eq!(bytes, [1, 2, 3, 4]);
+    }
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn permutation_is_send_sync() {
+        assert_send_sync::<Poseidon2b<BinaryField32b>>();
+        assert_send_sync::<Poseidon2b<BinaryField64b>>();
+        assert_send_sync::<Poseidon2b<BinaryField128b>>();
+        assert_send_sync::<batch::BatchState<BinaryField64b>>();
+        assert_send_sync::<sponge::BatchSponge<'static, BinaryField64b>>();
+        assert_send_sync::<transcript::Transcript<'static, BinaryField64b>>();
+    }
+
+    // One workspace, reused across permutations and across widths, gives the same outputs as the
+    // allocating path.
+    #[test]
+    fn workspace_permute_matches_permute() {
+        fn check<F: FieldConst>(params: PreparedParams<F>, ws: &mut Poseidon2bWorkspace<F>) {
+            let perm = Poseidon2b::new(params);
+            let mut expected: Vec<F> = (0..perm.t).map(|i| F::from_u8(i as u8 + 1)).collect();
+            let mut state = expected.clone();
+            for _ in 0..4 {
+                perm.permute(&mut expected);
+                perm.permute_with_workspace(&mut state, ws);
+                assert_eq!(state, expected, "t={}", perm.t);
+            }
+        }
+        let mut ws = Poseidon2bWorkspace::with_capacity(16);
+        check(params_32_t16(), &mut ws);
+        check(params_32_t24(), &mut ws);
+        let mut ws = Poseidon2bWorkspace::default();
+        check(params_64_t8(), &mut ws);
+        check(params_64_t12(), &mut ws);
+        let mut ws = Poseidon2bWorkspace::with_capacity(4);
+        check(params_128_t4(), &mut ws);
+        check(params_128_t6(), &mut ws);
+    }
+
+    #[test]
+    fn shipped_mds_full_uses_block_product() {
+        assert!(Poseidon2b::new(params_32_t16()).uses_structured_mds_full());
+        assert!(Poseidon2b::new(params_32_t24()).uses_structured_mds_full());
+        assert!(Poseidon2b::new(params_64_t8()).uses_structured_mds_full());
+        assert!(Poseidon2b::new(params_64_t12()).uses_structured_mds_full());
+        assert!(!Poseidon2b::new(params_128_t6()).uses_structured_mds_full());
+    }
+
+    // A structured-size `t` whose matrix breaks the block structure in a single entry must be
+    // detected in `new` and still multiply correctly.
+    #[test]
+    fn unstructured_mds_full_falls_back_to_dense() {
+        let mut params = params_64_t8();
+        params.mds_full[5][2] += BinaryField64b::from_u8(1);
+        assert!(MdsFullFast::new(&params.mds_full, params.t).is_none());
+        check_mds_full_matches_dense("64_t8 perturbed", params);
+    }
+
+    // With only the upper-right block zeroed the off-diagonal blocks no longer agree: `new`
+    // returns `None` and the permutation runs on the dense product instead of panicking.
+    #[test]
+    fn zero_upper_right_block_has_no_fast_path() {
+        let zeroed = || {
+            let mut params = params_64_t8();
+            for row in &mut params.mds_full[..4] {
+                row[4..8].fill(BinaryField64b::default());
+            }
+            params
+        };
+        let params = zeroed();
+        assert!(MdsFullFast::new(&params.mds_full, params.t).is_none());
+        check_mds_full_matches_dense("64_t8 zero block", params);
+
+        let perm = Poseidon2b::new(zeroed());
+        assert!(!perm.uses_structured_mds_full());
+        assert!(Poseidon2b::new(params_64_t8()).uses_structured_mds_full());
+        let mut state: Vec<BinaryField64b> = (0..8).map(BinaryField64b::from_u8).collect();
+        perm.permute(&mut state);
+    }
+
+    #[test]
+    fn from_seed_is_deterministic() {
+        let a = Poseidon2b::<BinaryField64b>::from_seed(8, 10, 29, b"poseidon2b");
+        let b = Poseidon2b::<BinaryField64b>::from_seed(8, 10, 29, b"poseidon2b");
+        assert_eq!(a.rc, b.rc);
+        assert_eq!(a.mds_full, b.mds_full);
+        assert_eq!(a.mds_partial, b.mds_partial);
+
+        let c = Poseidon2b::<BinaryField64b>::from_seed(8, 10, 29, b"poseidon2c");
+        assert_ne!(a.rc, c.rc);
+        assert_ne!(a.mds_partial, c.mds_partial);
+
+        let mut sa = [BinaryField64b::default(); 8];
+        let mut sb = sa;
+        a.permute(&mut sa);
+        b.permute(&mut sb);
+        assert_eq!(sa, sb);
+    }
+
+    #[test]
+    fn from_seed_mds_full_matches_shipped() {
+        fn check<F: FieldConst>(params: PreparedParams<F>) {
+            let seeded = Poseidon2b::<F>::from_seed(params.t, params.rf, params.rp, &[]);
+            let t = params.t;
+            assert_eq!(seeded.mds_full, params.mds_full, "t={t}");
+            assert_eq!(seeded.uses_structured_mds_full(), t >= 8, "t={t}");
+        }
+        check(params_32_t16());
+        check(params_32_t24());
+        check(params_64_t8());
+        check(params_64_t12());
+        check(params_128_t4());
+    }
+
+    #[test]
+    fn from_seed_partial_mds_is_invertible() {
+        for t in [4, 8, 12, 16, 24, 32] {
+            let p = Poseidon2b::<BinaryField32b>::from_seed(t, 8, 22, &[]);
+            let diagonal: Vec<_> = (0..t).map(|i| p.mds_partial[i][i]).collect();
+            assert!(mds_partial_is_invertible(&diagonal), "t={t}");
+        }
+        // mu_i = 1 zeroes a row of J + diag(mu_i - 1).
+        let (one, two) = (BinaryField32b::from_u8(1), BinaryField32b::from_u8(2));
+        assert!(!mds_partial_is_invertible(&[two, one]));
+        // mu_i = 0: J + I in characteristic 2, singular for odd t.
+        let zero = BinaryField32b::default();
+        assert!(!mds_partial_is_invertible(&[zero; 3]));
+        assert!(mds_partial_is_invertible(&[zero; 4]));
+    }
+
+    #[test]
+    fn compress_permutes_left_then_right() {
+        let perm = Poseidon2b::new(params_32_t16());
+        let left: Vec<BinaryField32b> = (0..8).map(|i| FieldOps::from_u8(i + 1)).collect();
+        let right: Vec<BinaryField32b> = (0..8).map(|i| FieldOps::from_u8(i + 0x41)).collect();
+
+        let mut state = [left.clone(), right.clone()].concat();
+        perm.permute(&mut state);
+        assert_eq!(perm.compress(&left, &right), state[..8]);
+        assert_ne!(perm.compress(&left, &right), perm.compress(&right, &left));
+    }
+
+    // Dense evaluation of the whole schedule straight from `PreparedParams`.
+    fn naive_permute<F: FieldConst>(p: &PreparedParams<F>, state: &mut [F]) {
+        let mat_mul = |m: &[Vec<F>], s: &mut [F]| {
+            let input = s.to_vec();
+            for (out, row) in s.iter_mut().zip(m) {
+                *out = row
+                    .iter()
+                    .zip(&input)
+                    .fold(F::default(), |acc, (&c, &x)| acc.add(c.mul(x)));
+            }
+        };
+        mat_mul(&p.mds_full, state);
+        for r in 0..p.rf + p.rp {
+            if !(p.rf / 2..p.rf / 2 + p.rp).contains(&r) {
+                for (x, lane) in state.iter_mut().zip(&p.rc) {
+                    *x = x.add(lane[r]).pow_alpha();
+                }
+                mat_mul(&p.mds_full, state);
+            } else {
+                state[0] = state[0].add(p.rc[0][r]).pow_alpha();
+                mat_mul(&p.mds_partial, state);
+            }
+        }
+    }
+
+    struct Naive64T8;
+
+    impl PlainPermutation<BinaryField64b> for Naive64T8 {
+        const STATE_SIZE: usize = params::params64_t8::STATE_SIZE;
+
+        fn permute_plain(state: &mut [BinaryField64b]) {
+            naive_permute(&params_64_t8(), state);
+        }
+    }
+
+    #[test]
+    fn native_matches_naive_plain_permutation() {
+        assert_plain_equiv::<BinaryField64b, Poseidon2b64T8, Naive64T8>(16);
+    }
+
+    // t=10 is not a multiple of 4: MDS_FULL takes the dense product, MDS_PARTIAL its usual
+    // diagonal-plus-sum form, and the whole permutation still matches the dense schedule.
+    #[test]
+    fn t10_uses_dense_full_mds() {
+        let t = 10;
+        let mut rng = StdRng::seed_from_u64(10);
+        let mut random_state = |n: usize| -> Vec<BinaryField64b> {
+            (0..n).map(|_| BinaryField64b::new(rng.gen())).collect()
+        };
+        let rc = (0..t).map(|_| random_state(8 + 20)).collect();
+        let mds_full = (0..t)
+            .map(|r| {
+                (0..t)
+                    .map(|c| FieldOps::from_u8(((c + t - r) % t) as u8 + 1))
+                    .collect()
+            })
+            .collect();
+        let mds_partial = (0..t)
+            .map(|r| {
+                (0..t)
+                    .map(|c| FieldOps::from_u8(if r == c { 2 + r as u8 } else { 1 }))
+                    .collect()
+            })
+            .collect();
+        let params = PreparedParams {
+            t,
+            rf: 8,
+            rp: 20,
+            rc,
+            mds_full,
+            mds_partial,
+        };
+        let mut expected = random_state(t);
+        let mut state = expected.clone();
+
+        naive_permute(&params, &mut expected);
+        let perm = Poseidon2b::new(params);
+        assert!(!perm.uses_structured_mds_full());
+        perm.permute(&mut state);
+        assert_eq!(state, expected);
+    }
+
+    // Without `Minit`, permuting `MDS_FULL * x` gives what the default permutation gives on `x`.
+    #[test]
+    fn initial_mds_off_reads_input_directly() {
+        let with = Poseidon2b::new(params_64_t8());
+        let without = Poseidon2b::new(params_64_t8()).with_initial_mds(false);
+        let x: Vec<BinaryField64b> = (0..8).map(|i| FieldOps::from_u8(3 * i + 1)).collect();
+        let mut mx = x.clone();
+        with.mul_mds_full(&mut mx);
+
+        let mut expected = x.clone();
+        with.permute(&mut expected);
+        let mut out = mx.clone();
+        without.permute(&mut out);
+        assert_eq!(out, expected);
+
+        let mut unshifted = x;
+        without.permute(&mut unshifted);
+        assert_ne!(unshifted, expected);
+
+        let mut batch = batch::BatchState::from_states(&[mx]);
+        without.permute_batch_soa(&mut batch);
+        assert_eq!(batch.to_states(), [expected]);
+    }
+
+    // The t=24 round counts, only to exercise the t=32 shape.
+    #[test]
+    fn t32_matches_naive_dense() {
+        let perm = poseidon2b_32_t32_with_rounds(10, 15);
+        assert_eq!(perm.t, 32);
+        assert!(perm.uses_structured_mds_full());
+        let params = PreparedParams {
+            t: perm.t,
+            rf: perm.rf,
+            rp: perm.rp,
+            rc: perm.rc.clone(),
+            mds_full: perm.mds_full.clone(),
+            mds_partial: perm.mds_partial.clone(),
+        };
+
+        let mut rng = StdRng::seed_from_u64(32);
+        for _ in 0..4 {
+            let mut expected: Vec<BinaryField32b> =
+                (0..32).map(|_| BinaryField32b::new(rng.gen())).collect();
+            let mut state = expected.clone();
+            naive_permute(&params, &mut expected);
+            perm.permute(&mut state);
+            assert_eq!(state, expected);
+        }
+    }
+
+    fn check_interleaved_matches_sequential<F: FieldConst>(params: PreparedParams<F>) {
+        let p = Poseidon2b::new(params);
+        let states: Vec<Vec<F>> = (0..4u8)
+            .map(|k| (0..p.t).map(|i| F::from_u8(k * 31 + i as u8)).collect())
+            .collect();
+        let mut expected = states.clone();
+        for s in expected.iter_mut() {
+            p.permute(s);
+        }
+
+        let mut pair = states[..2].to_vec();
+        if let [a, b] = &mut pair[..] {
+            p.permute_pair(a, b);
+        }
+        assert_eq!(pair, expected[..2]);
+
+        let mut quad = states.clone();
+        if let [a, b, c, d] = &mut quad[..] {
+            p.permute_quad(a, b, c, d);
+        }
+        assert_eq!(quad, expected);
+    }
+
+    #[test]
+    fn interleaved_permute_matches_sequential() {
+        check_interleaved_matches_sequential(params_128_t4());
+        check_interleaved_matches_sequential(params_128_t6());
+        check_interleaved_matches_sequential(params_64_t8());
+        check_interleaved_matches_sequential(params_32_t16());
+    }
+
+    // `round_full` adds the round constants and applies the S-box in one pass; each round must
+    // match the three separate passes, and the whole permutation the dense schedule.
+    #[test]
+    fn fused_full_round_matches_three_passes() {
+        fn check(params: fn() -> PreparedParams<BinaryField32b>) {
+            let perm = Poseidon2b::new(params());
+            let input: Vec<BinaryField32b> =
+                (0..perm.t).map(|i| BinaryField32b::from_u8(i as u8 + 1)).collect();
+            let mut ws = Poseidon2bWorkspace::default();
+            let mut fused = input.clone();
+            let mut expected = input.clone();
+            for r in 0..perm.rf / 2 {
+                perm.round_full(&mut fused, r, &mut ws);
+                for (x, lane) in expected.iter_mut().zip(&perm.rc) {
+                    *x += lane[r];
+                }
+                for x in expected.iter_mut() {
+                    *x = x.pow_alpha();
+                }
+                perm.mul_mds_full(&mut expected);
+                assert_eq!(fused, expected, "t={} round {r}", perm.t);
+            }
+
+            let mut state = input.clone();
+            let mut expected = input;
+            perm.permute(&mut state);
+            naive_permute(&params(), &mut expected);
+            assert_eq!(state, expected, "t={}", perm.t);
+        }
+        check(params_32_t16);
+        check(params_32_t24);
+    }
+
+    #[test]
+    fn from_raw_checked_accepts_canonical_values() {
+        assert_eq!(
+            BinaryField32b::from_raw_checked(u32::MAX),
+            Some(BinaryField32b::from_raw(u32::MAX))
+        );
+        assert_eq!(
+            BinaryField64b::from_raw_checked(u64::MAX),
+            Some(BinaryField64b::from_raw(u64::MAX))
+        );
+        assert_eq!(
+            BinaryField128b::from_raw_checked(u128::MAX),
+            Some(BinaryField128b::from_raw(u128::MAX))
+        );
+        assert_eq!(BinaryField32b::from_raw_checked(0), Some(BinaryField32b::default()));
+    }
+
+    // None of the shipped fields is narrower than its underlier, so the rejecting side is
+    // exercised on the range check directly.
+    #[test]
+    fn raw_is_canonical_rejects_bits_above_the_field() {
+        assert!(raw_is_canonical(0xffff, 16));
+        assert!(!raw_is_canonical(0x1_0000, 16));
+        assert!(!raw_is_canonical(1 << 63, 32));
+        assert!(raw_is_canonical(u64::MAX as u128, 64));
+        assert!(raw_is_canonical(u128::MAX, 128));
+    }
+
+    thread_local! {
+        static MULS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+    }
+
+    fn count_mul() {
+        MULS.with(|n| n.set(n.get() + 1));
+    }
+
+    // A field that counts its general multiplications, so `mul_count` can be checked against
+    // what `permute` actually does.
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    struct Counting<F>(F);
+
+    impl<F: FieldOps> std::ops::Add for Counting<F> {
+        type Output = Self;
+        fn add(self, rhs: Self) -> Self {
+            Self(self.0 + rhs.0)
+        }
+    }
+
+    impl<F: FieldOps> std::ops::AddAssign for Counting<F> {
+        fn add_assign(&mut self, rhs: Self) {
+            self.0 += rhs.0;
+        }
+    }
+
+    impl<F: FieldOps> std::ops::Mul for Counting<F> {
+        type Output = Self;
+        fn mul(self, rhs: Self) -> Self {
+            count_mul();
+            Self(self.0 * rhs.0)
+        }
+    }
+
+    impl<F: FieldOps> FieldOps for Counting<F> {
+        fn safe_square(self) -> Self {
+            Self(self.0.safe_square())
+        }
+        fn inv(self) -> Self {
+            Self(self.0.inv())
+        }
+        fn from_u8(v: u8) -> Self {
+            Self(F::from_u8(v))
+        }
+    }
+
+    impl<F: FieldConst> FieldConst for Counting<F> {
+        type Raw = F::Raw;
+        const BYTES: usize = F::BYTES;
+        fn from_raw(v: Self::Raw) -> Self {
+            Self(F::from_raw(v))
+        }
+        fn from_raw_checked(v: Self::Raw) -> Option<Self> {
+            F::from_raw_checked(v).map(Self)
+        }
+        fn to_raw(self) -> Self::Raw {
+            self.0.to_raw()
+        }
+        fn from_le_bytes(bytes: &[u8]) -> Self {
+            Self(F::from_le_bytes(bytes))
+        }
+        fn from_le_bytes_checked(bytes: &[u8]) -> Option<Self> {
+            F::from_le_bytes_checked(bytes).map(Self)
+        }
+        fn write_le_bytes(self, out: &mut [u8]) {
+            self.0.write_le_bytes(out);
+        }
+    }
+
+    fn counting<F: FieldConst>(p: PreparedParams<F>) -> PreparedParams<Counting<F>> {
+        let wrap = |m: Vec<Vec<F>>| -> Vec<Vec<Counting<F>>> {
+            m.into_iter()
+                .map(|row| row.into_iter().map(Counting).collect())
+                .collect()
+        };
+        PreparedParams {
+            t: p.t,
+            rf: p.rf,
+            rp: p.rp,
+            rc: wrap(p.rc),
+            mds_full: wrap(p.mds_full),
+            mds_partial: wrap(p.mds_partial),
+        }
+    }
+
+    fn check_mul_count<F: FieldConst>(perm: Poseidon2b<Counting<F>>) -> usize {
+        let mut state: Vec<_> = (0..perm.t).map(|i| Counting::from_u8(i as u8 + 1)).collect();
+        MULS.with(|n| n.set(0));
+        perm.permute(&mut state);
+        let counted = MULS.with(|n| n.get());
+        assert_eq!(perm.mul_count(), counted, "t={}", perm.t);
+        counted
+    }
+
+    // Intermediate states of params_64_t8 on input `0x0123456789abcdef + i`, produced by
+    // scripts/kat_gen.py, which shares no code with this crate: its own tower-field multiplication
+    // and dense matrix products, reading the tables from src/params.rs.
+    const KAT_64_T8_INITIAL_MDS: [u64; 8] = [
+        0x00000000000000a3, 0x06bd17ac249f3562, 0x0000000000000023, 0x06bd17ac249f35c9,
+        0x000000000000005e, 0x06bd17ac249f35aa, 0x0000000000000017, 0x06bd17ac249f35fc,
+    ];
+    const KAT_64_T8_FIRST_FULL: [u64; 8] = [
+        0x94b4252bc769be9e, 0x6bf118faf107f9cd, 0x1a99e645d225c28b, 0xcce730c4450b2235,
+        0x36279b77e038e450, 0xf32a3352bfc46683, 0x4ef2fac3433a1e80, 0x86eba777392dcf89,
+    ];
+    const KAT_64_T8_LAST_PARTIAL: [u64; 8] = [
+        0x29f315eacc75dffd, 0x2681451ae956041f, 0x730714b79fe0c7ff, 0x1ae7d658567371b4,
+        0x383b6acb6d015736, 0xb7f81979a4c4f490, 0xbab6cb036a15a9cf, 0x5b1d34cfed9021b1,
+    ];
+    const KAT_64_T8_OUTPUT: [u64; 8] = [
+        0xe85a40ec2765fbdb, 0x56eadfffc0c5ce84, 0xe9e76c20fc13923e, 0x14b5817055ca77bf,
+        0xc652d62baef0ba82, 0xaaa979c164c35c51, 0x0d8d960ea9e2b456, 0x1b0c7bbcf445a970,
+    ];
+
+    fn kat_64_t8(raw: [u64; 8]) -> Vec<BinaryField64b> {
+        raw.map(BinaryField64b::from_raw).to_vec()
+    }
+
+    #[test]
+    fn params_64_t8_matches_reference_kats() {
+        let perm = Poseidon2b::new(params_64_t8());
+        let (half_f, rp) = (perm.rf / 2, perm.rp);
+        let input: Vec<BinaryField64b> = (0..8)
+            .map(|i| BinaryField64b::from_raw(0x0123_4567_89ab_cdef + i))
+            .collect();
+        let mut ws = Poseidon2bWorkspace::default();
+
+        let mut state = input.clone();
+        perm.mul_mds_full(&mut state);
+        assert_eq!(state, kat_64_t8(KAT_64_T8_INITIAL_MDS), "after initial MDS");
+        perm.round_full(&mut state, 0, &mut ws);
+        assert_eq!(state, kat_64_t8(KAT_64_T8_FIRST_FULL), "after first full round");
+        for r in 1..half_f {
+            perm.round_full(&mut state, r, &mut ws);
+        }
+        for r in half_f..half_f + rp {
+            perm.round_partial(&mut state, r);
+        }
+        assert_eq!(state, kat_64_t8(KAT_64_T8_LAST_PARTIAL), "after last partial round");
+
+        let mut whole = input;
+        perm.permute(&mut whole);
+        assert_eq!(whole, kat_64_t8(KAT_64_T8_OUTPUT), "permutation output");
+    }
+
+    #[test]
+    fn hash_raw_matches_field_sponge() {
+        let perm = Poseidon2b::new(params_64_t8());
+        for len in [0, 1, 7, 12] {
+            let raw: Vec<u64> = (0..len as u64)
+                .map(|i| 0x0123_4567_89ab_cdef ^ (i << 40))
+                .collect();
+            let input: Vec<BinaryField64b> =
+                raw.iter().map(|&v| BinaryField64b::from_raw(v)).collect();
+
+            let digest = perm.hash_raw(&raw);
+            let mut sponge = sponge::Poseidon2bSponge::new(&perm, sponge::Padding::Pad10);
+            sponge.absorb(&input);
+            let expected: Vec<u64> = sponge
+                .finalize(7)
+                .unwrap()
+                .into_iter()
+                .map(FieldConst::to_raw)
+                .collect();
+            assert_eq!(digest, expected, "len={len}");
+            assert_eq!(
+                digest[0],
+                sponge::hash_var(&perm, &input).to_raw(),
+                "len={len}"
+            );
+        }
+    }
+
+    #[test]
+    fn permute_trace_follows_the_round_schedule() {
+        let perm = Poseidon2b::new(params_64_t8());
+        let (half_f, rp) = (perm.rf / 2, perm.rp);
+        let input: Vec<BinaryField64b> = (0..8)
+            .map(|i| BinaryField64b::from_raw(0x0123_4567_89ab_cdef + i))
+            .collect();
+        let trace = perm.permute_trace(&input);
+        assert_eq!(trace.len(), perm.rf + perm.rp + 1);
+        assert_eq!(trace[0], kat_64_t8(KAT_64_T8_INITIAL_MDS));
+        assert_eq!(trace[1], kat_64_t8(KAT_64_T8_FIRST_FULL));
+        assert_eq!(trace[half_f + rp], kat_64_t8(KAT_64_T8_LAST_PARTIAL));
+        assert_eq!(trace.last(), Some(&kat_64_t8(KAT_64_T8_OUTPUT)));
+
+        let without = Poseidon2b::new(params_64_t8()).with_initial_mds(false);
+        let mut output = input.clone();
+        without.permute(&mut output);
+        let trace = without.permute_trace(&input);
+        assert_eq!(trace[0], input);
+        assert_eq!(trace.last(), Some(&output));
+    }
+
+    #[test]
+    fn instances_are_the_six_tables() {
+        let instances = Instance::all();
+        assert_eq!(instances.len(), 6);
+        let names: Vec<&str> = instances.map(Instance::name).collect();
+        for (i, name) in names.iter().enumerate() {
+            assert!(!names[..i].contains(name), "duplicate name {name}");
+        }
+
+        fn shape_of<F: FieldConst>(p: PreparedParams<F>) -> (usize, usize, usize) {
+            (p.t, p.rf, p.rp)
+        }
+        let expected = [
+            shape_of(params_32_t16()),
+            shape_of(params_32_t24()),
+            shape_of(params_64_t8()),
+            shape_of(params_64_t12()),
+            shape_of(params_128_t4()),
+            shape_of(params_128_t6()),
+        ];
+        for (inst, shape) in Instance::all().zip(expected) {
+            assert_eq!(inst.shape(), shape, "{}", inst.name());
+            assert!(inst.name().ends_with(&format!("t={}", shape.0)));
+        }
+    }
+
+    #[test]
+    fn permute_accepts_arrays_vecs_and_boxes() {
+        let perm = Poseidon2b::new(params_128_t4());
+        let input: [BinaryField128b; 4] = std::array::from_fn(|i| FieldOps::from_u8(i as u8 + 1));
+        let mut expected = input.to_vec();
+        perm.permute(&mut expected[..]);
+
+        let mut array = input;
+        perm.permute(&mut array);
+        let mut vec = input.to_vec();
+        perm.permute(&mut vec);
+        let mut boxed: Box<[BinaryField128b]> = Box::new(input);
+        perm.permute(&mut boxed);
+
+        assert_eq!(array.to_vec(), expected);
+        assert_eq!(vec, expected);
+        assert_eq!(boxed.to_vec(), expected);
+    }
+
+    #[test]
+    #[should_panic(expected = "state has 3 elements, t=4")]
+    fn permute_rejects_wrong_length() {
+        let perm = Poseidon2b::new(params_128_t4());
+        perm.permute([BinaryField128b::default(); 3]);
+    }
+
+    // Flips each round constant in turn, every lane and every column, and checks that the output
+    // changes exactly when the schedule reads that constant: every lane in full rounds, lane 0 in
+    // partial rounds, nothing past `rf + rp`. Catches skipped or reused rounds in the running
+    // `round` counter.
+    fn check_every_round_constant_is_used<F: FieldConst>(params: fn() -> PreparedParams<F>) {
+        let reference = Poseidon2b::new(params());
+        let (t, half_f, rp) = (reference.t, reference.rf / 2, reference.rp);
+        let input: Vec<F> = (0..t).map(|i| F::from_u8(i as u8 + 1)).collect();
+        let mut expected = input.clone();
+        reference.permute(&mut expected);
+
+        for lane in 0..t {
+            for r in 0..reference.rc[lane].len() {
+                let mut perturbed = params();
+                perturbed.rc[lane][r] += F::from_u8(1);
+                let mut state = input.clone();
+                Poseidon2b::new(perturbed).permute(&mut state);
+
+                let partial = r >= half_f && r < half_f + rp;
+                let used = r < 2 * half_f + rp && (lane == 0 || !partial);
+                assert_eq!(state != expected, used, "t={t} rc[{lane}][{r}]");
+            }
+        }
+    }
+
+    // Split by field so the slow 128-bit instances run alongside the others.
+    #[test]
+    fn every_round_constant_is_used_32() {
+        check_every_round_constant_is_used(params_32_t16);
+        check_every_round_constant_is_used(params_32_t24);
+    }
+
+    #[test]
+    fn every_round_constant_is_used_64() {
+        check_every_round_constant_is_used(params_64_t8);
+        check_every_round_constant_is_used(params_64_t12);
+    }
+
+    #[test]
+    fn every_round_constant_is_used_128() {
+        check_every_round_constant_is_used(params_128_t4);
+        check_every_round_constant_is_used(params_128_t6);
+    }
+
+    // An ablated schedule must stay in bounds and remain a permutation: distinct inputs give
+    // distinct outputs, and the interleaved and batch paths agree with `permute`.
+    fn check_ablated_rounds<F: FieldConst>(params: PreparedParams<F>) {
+        let p = Poseidon2b::new(params);
+        let inputs: Vec<Vec<F>> = (0..16u8)
+            .map(|k| (0..p.t).map(|i| F::from_u8(k.wrapping_mul(17) ^ i as u8)).collect())
+            .collect();
+        let outputs: Vec<Vec<F>> = inputs
+            .iter()
+            .map(|input| {
+                let mut state = input.clone();
+                p.permute(&mut state);
+                state
+            })
+            .collect();
+
+        for (i, out) in outputs.iter().enumerate() {
+            assert_ne!(out, &inputs[i], "rf={} rp={} input {i} unchanged", p.rf, p.rp);
+            assert!(!outputs[..i].contains(out), "rf={} rp={} collision", p.rf, p.rp);
+        }
+
+        let mut batch = batch::BatchState::from_states(&inputs);
+        p.permute_batch_soa(&mut batch);
+        assert_eq!(batch.to_states(), outputs);
+    }
+
+    #[test]
+    fn full_rounds_only() {
+        check_ablated_rounds(params_64_t8().with_rounds(8, 0));
+        check_ablated_rounds(params_32_t16().with_rounds(2, 0));
+    }
+
+    #[test]
+    fn partial_rounds_only() {
+        check_ablated_rounds(params_64_t8().with_rounds(0, 29));
+        check_ablated_rounds(params_32_t16().with_rounds(0, 3));
+    }
+
+    #[test]
+    #[should_panic(expected = "exceeds the 25 round constant columns")]
+    fn with_rounds_rejects_more_rounds_than_constants() {
+        params_32_t16().with_rounds(10, 16);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn random_state_differs_across_calls() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let states: Vec<Vec<BinaryField64b>> = (0..8).map(|_| random_state(8, &mut rng)).collect();
+        for (i, a) in states.iter().enumerate() {
+            assert_eq!(a.len(), 8);
+            for b in &states[i + 1..] {
+                assert_ne!(a, b);
+            }
+        }
+    }
+
+    fn check_identity_mds_is_pure_sbox<F: FieldConst>(params: PreparedParams<F>) {
+        let (t, rf, rp) = (params.t, params.rf, params.rp);
+        let perm = Poseidon2b::new(params.with_identity_mds().with_zero_rc());
+        let input: Vec<F> = (0..t).map(|i| F::from_u8(3 * i as u8 + 2)).collect();
+
+        let mut state = input.clone();
+        perm.permute(&mut state);
+
+        for (i, (&x, &y)) in input.iter().zip(&state).enumerate() {
+            let n = if i == 0 { rf + rp } else { rf };
+            let expected = (0..n).fold(x, |acc, _| acc.pow_alpha());
+            assert_eq!(y, expected, "t={t} lane {i}");
+        }
+    }
+
+    #[test]
+    fn identity_mds_zero_rc_is_pure_sbox() {
+        check_identity_mds_is_pure_sbox(params_32_t16());
+        check_identity_mds_is_pure_sbox(params_64_t8());
+        check_identity_mds_is_pure_sbox(params_128_t4());
+    }
+
+    #[test]
+    fn identity_mds_keeps_round_constants() {
+        let params = params_64_t8();
+        let (rf, rp, rc) = (params.rf, params.rp, params.rc.clone());
+        let perm = Poseidon2b::new(params.with_identity_mds());
+        let mut state: Vec<BinaryField64b> =
+            (0..perm.t).map(|i| FieldOps::from_u8(i as u8)).collect();
+        let mut expected = state.clone();
+        perm.permute(&mut state);
+
+        for r in 0..rf + rp {
+            let full = !(rf / 2..rf / 2 + rp).contains(&r);
+            for (i, (x, lane)) in expected.iter_mut().zip(&rc).enumerate() {
+                if full || i == 0 {
+                    *x = (*x + lane[r]).pow_alpha();
+                }
+            }
+        }
+        assert_eq!(state, expected);
+    }
+
+    #[test]
+    fn mul_count_matches_counted_muls() {
+        // t=4 block trick, with and without Minit.
+        let block = check_mul_count(Poseidon2b::new(counting(params_128_t4())));
+        let no_minit =
+            check_mul_count(Poseidon2b::new(counting(params_128_t4())).with_initial_mds(false));
+        assert_eq!(block - no_minit, 12);
+
+        // `D + M` product at every shipped width that has it.
+        for perm in [
+            Poseidon2b::new(counting(params_64_t8())),
+            Poseidon2b::new(counting(params_64_t12())),
+        ] {
+            assert!(perm.uses_structured_mds_full());
+            check_mul_count(perm);
+        }
+        for perm in [
+            Poseidon2b::new(counting(params_32_t16())),
+            Poseidon2b::new(counting(params_32_t24())),
+        ] {
+            assert!(perm.uses_structured_mds_full());
+            check_mul_count(perm);
+        }
+
+        // Dense product: t=6, and a t=8 matrix that breaks the block structure.
+        let dense_t6 = Poseidon2b::new(counting(params_128_t6()));
+        assert!(!dense_t6.uses_structured_mds_full());
+        check_mul_count(dense_t6);
+        let structured = check_mul_count(Poseidon2b::new(counting(params_64_t8())));
+        let mut params = params_64_t8();
+        params.mds_full[5][2] += BinaryField64b::from_u8(1);
+        let dense_t8 = Poseidon2b::new(counting(params));
+        assert!(!dense_t8.uses_structured_mds_full());
+        let (rf, t) = (dense_t8.rf, dense_t8.t);
+        let dense = check_mul_count(dense_t8);
+        assert_eq!(dense - structured, (rf + 1) * (t * t - (12 + 3 * t)));
+
+        // Dense partial layer: the identity is not all ones off the diagonal.
+        let identity = Poseidon2b::new(counting(params_128_t4().with_identity_mds()));
+        let (rp, t) = (identity.rp, identity.t);
+        let dense_partial = check_mul_count(identity);
+        assert_eq!(dense_partial - block, rp * (t * t - t));
+    }
+
+    // Gauss-Jordan; `None` if `m` is singular.
+    fn invert_matrix(m: &[Vec<BinaryField32b>]) -> Option<Vec<Vec<BinaryField32b>>> {
+        let t = m.len();
+        let mut a: Vec<Vec<BinaryField32b>> = m.to_vec();
+        let mut inv: Vec<Vec<BinaryField32b>> = (0..t)
+            .map(|r| (0..t).map(|c| BinaryField32b::from_u8((r == c) as u8)).collect())
+            .collect();
+        for col in 0..t {
+            let pivot = (col..t).find(|&r| a[r][col] != BinaryField32b::ZERO)?;
+            a.swap(col, pivot);
+            inv.swap(col, pivot);
+            let scale = a[col][col].invert().unwrap();
+            for c in 0..t {
+                a[col][c] *= scale;
+                inv[col][c] *= scale;
+            }
+            for r in (0..t).filter(|&r| r != col) {
+                let k = a[r][col];
+                for c in 0..t {
+                    let (x, y) = (a[col][c], inv[col][c]);
+                    a[r][c] -= k * x;
+                    inv[r][c] -= k * y;
+                }
+            }
+        }
+        Some(inv)
+    }
+
+    // `permute` run backwards: inverse MDS layers, x^(1/7) and the round constants added again.
+    fn inverse_permute_32(p: &Poseidon2b<BinaryField32b>, state: &mut [BinaryField32b]) {
+        let full_inv = invert_matrix(&p.mds_full).expect("mds_full is invertible");
+        let partial_inv = invert_matrix(&p.mds_partial).expect("mds_partial is invertible");
+        let mat_mul = |m: &[Vec<BinaryField32b>], s: &mut [BinaryField32b]| {
+            let input = s.to_vec();
+            for (out, row) in s.iter_mut().zip(m) {
+                *out = row.iter().zip(&input).map(|(&c, &x)| c * x).sum();
+            }
+        };
+        // 7 * alpha_inv = 1 mod 2^32 - 1
+        let order = u32::MAX as u64;
+        let alpha_inv = (1..7).map(|k| k * order + 1).find(|v| v % 7 == 0).unwrap() / 7;
+        let sbox_inv = |x: BinaryField32b| Field::pow(&x, [alpha_inv]);
+
+        for r in (0..p.rf + p.rp).rev() {
+            if !(p.rf / 2..p.rf / 2 + p.rp).contains(&r) {
+                mat_mul(&full_inv, state);
+                for (x, lane) in state.iter_mut().zip(&p.rc) {
+                    *x = sbox_inv(*x) - lane[r];
+                }
+            } else {
+                mat_mul(&partial_inv, state);
+                state[0] = sbox_inv(state[0]) - p.rc[0][r];
+            }
+        }
+        mat_mul(&full_inv, state);
+    }
+
+    #[test]
+    fn t32_round_trip() {
+        let perm = poseidon2b_32_t32_with_rounds(10, 15);
+        for seed in 0..4u32 {
+            let input: Vec<BinaryField32b> = (0..32)
+                .map(|i| BinaryField32b::new(0x9e37_79b9u32.wrapping_mul(seed * 32 + i + 1)))
+                .collect();
+            let mut state = input.clone();
+            perm.permute(&mut state);
+            assert_ne!(state, input);
+            inverse_permute_32(&perm, &mut state);
+            assert_eq!(state, input, "seed {seed}");
+        }
+    }
+}
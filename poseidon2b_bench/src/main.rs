@@ -1,473 +1,475 @@
-mod params;
-
-use binius_field::{
-    BinaryField128b, BinaryField32b, BinaryField64b, Field, PackedField,
+use poseidon2b_runner::{
+    batch::BatchState,
+    params_128_t4, params_128_t6, params_32_t16, params_32_t24, params_64_t12, params_64_t8,
+    random_state,
+    sponge::{hash2, hash_var},
+    FieldConst, Instance, Poseidon2b, Poseidon2bWorkspace, PreparedParams,
 };
-use std::fmt::Debug;
-use std::time::Instant;
-
-
-pub trait FieldOps:
-    'static + Copy + Clone + Debug + Default + PartialEq + Send + Sync
-{
-    fn add(self, rhs: Self) -> Self;
-    fn mul(self, rhs: Self) -> Self;
-    fn safe_square(self) -> Self;
-    fn inv(self) -> Self;
-    fn from_u8(v: u8) -> Self;
-    fn pow_alpha(self) -> Self {
-        let x2 = self.safe_square();
-        let x4 = x2.safe_square();
-        self.mul(x2).mul(x4) // x^7
-    }
-}
+use rand::{rngs::StdRng, SeedableRng};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+// Benchmark
 
-// Allows populating constants from "native representation" (consistent with the tower basis in binius_field).
-pub trait FieldConst: FieldOps {
-    type Raw: Copy;
-    fn from_raw(v: Self::Raw) -> Self;
+// Text is the default; `--output-format json` (or BENCH_OUTPUT_FORMAT=json) prints one JSON array
+// of `{instance, t, ns_per_op, perms_per_sec, independent_ns_per_op}` objects instead, and
+// `--output-format text-int` prints the chained figures from `IntTiming` with no `f64` involved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    Text,
+    TextInt,
+    Json,
 }
 
-macro_rules! impl_field_ops {
-    ($ty:ty, $raw:ty) => {
-        impl FieldOps for $ty {
-            #[inline(always)]
-            fn add(self, rhs: Self) -> Self { self + rhs }
-            #[inline(always)]
-            fn mul(self, rhs: Self) -> Self { self * rhs }
-            #[inline(always)]
-            fn safe_square(self) -> Self { self.square() }
-            #[inline(always)]
-            fn inv(self) -> Self { Field::invert(&self).unwrap() }
-            #[inline(always)]
-            fn from_u8(v: u8) -> Self { Self::from(v as $raw) }
-        }
-        impl FieldConst for $ty {
-            type Raw = $raw;
-            #[inline(always)]
-            fn from_raw(v: Self::Raw) -> Self { Self::from(v) }
+impl OutputFormat {
+    fn from_args() -> Self {
+        let args: Vec<String> = std::env::args().collect();
+        let flag = args
+            .iter()
+            .position(|a| a == "--output-format")
+            .and_then(|i| args.get(i + 1).cloned());
+        match flag
+            .or_else(|| std::env::var("BENCH_OUTPUT_FORMAT").ok())
+            .as_deref()
+        {
+            Some("json") => OutputFormat::Json,
+            Some("text") | None => OutputFormat::Text,
+            Some("text-int") => OutputFormat::TextInt,
+            Some(other) => {
+                panic!("unknown output format {other:?} (expected text, text-int or json)")
+            }
         }
-    };
+    }
+
+    fn is_text(self) -> bool {
+        self != OutputFormat::Json
+    }
 }
 
-impl_field_ops!(BinaryField32b, u32);
-impl_field_ops!(BinaryField64b, u64);
-impl_field_ops!(BinaryField128b, u128);
+// Whole nanoseconds summed in a `u128`, with the rates as integer divisions (rounded down), so the
+// bench loop can be reused where there is no floating point.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct IntTiming {
+    total_ns: u128,
+    iterations: u128,
+}
 
+impl IntTiming {
+    fn add(&mut self, elapsed: Duration, iterations: usize) {
+        self.total_ns += elapsed.as_nanos();
+        self.iterations += iterations as u128;
+    }
 
-// Poseidon2b parameter structure
+    fn ns_per_op(&self) -> u128 {
+        self.total_ns / self.iterations.max(1)
+    }
 
-struct PreparedParams<F: FieldConst> {
-    t: usize,
-    rf: usize,
-    rp: usize,
-    rc: Vec<Vec<F>>,
-    mds_full: Vec<Vec<F>>,
-    mds_partial: Vec<Vec<F>>,
+    fn perms_per_sec(&self) -> u128 {
+        self.iterations * 1_000_000_000 / self.total_ns.max(1)
+    }
 }
 
-struct MdsFullFast<F: FieldConst> {
-    k: usize,
-    x_plus_one: F,
+// Default iteration count for t <= 4, divided by t/4 for larger states but never below the floor.
+const BASE_ITERATIONS: usize = 100_000;
+const MIN_ITERATIONS: usize = 20_000;
+const _: () = assert!(BASE_ITERATIONS >= MIN_ITERATIONS);
+
+// Calibration stops doubling here even if the budget is not reached, so it always terminates.
+const MAX_CALIBRATION_ITERATIONS: usize = 1 << 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Iterations {
+    /// `BASE_ITERATIONS / (t/4)`, at least `MIN_ITERATIONS`.
+    Scaled,
+    /// Set with `--iterations <n>`.
+    Fixed(usize),
+    /// Enough iterations to fill roughly this much wall-clock time per instance, so numbers stay
+    /// comparable across machines. Set with `--time-budget-ms <ms>` (or BENCH_TIME_BUDGET_MS).
+    TimeBudget(Duration),
 }
 
-impl<F: FieldConst> MdsFullFast<F> {
-    fn new(mds_full: &[Vec<F>], t: usize) -> Option<Self> {
-        if t < 8 || t % 4 != 0 {
-            return None;
+impl Iterations {
+    fn from_args() -> Self {
+        let args: Vec<String> = std::env::args().collect();
+        let value_of = |name: &str| {
+            args.iter()
+                .position(|a| a == name)
+                .and_then(|i| args.get(i + 1).cloned())
+        };
+        if let Some(n) = value_of("--iterations") {
+            let n = n
+                .parse()
+                .unwrap_or_else(|_| panic!("invalid iteration count {n:?}"));
+            return Iterations::Fixed(n);
         }
-
-        // extract X for C from the ratio of the upper-left block to the upper-right M4
-        // (mds_full[r][c] / mds_full[r][c + 4]);
-
-        let k = t / 4;
-        let mut x = None;
-        'find_x: for r in 0..4 {
-            for c in 0..4 {
-                let m = mds_full[r][c + 4];
-                if m != F::default() {
-                    x = Some(mds_full[r][c].mul(m.inv()));
-                    break 'find_x;
-                }
+        match value_of("--time-budget-ms").or_else(|| std::env::var("BENCH_TIME_BUDGET_MS").ok()) {
+            Some(ms) => {
+                let ms: u64 = ms
+                    .parse()
+                    .unwrap_or_else(|_| panic!("invalid time budget {ms:?} (expected milliseconds)"));
+                Iterations::TimeBudget(Duration::from_millis(ms))
             }
+            None => Iterations::Scaled,
         }
-
-        let x = x?;
-        let x_plus_one = x.add(F::from_u8(1));
-        Some(Self { k, x_plus_one })
     }
 }
 
-fn prep_params<F: FieldConst, const T: usize, const R: usize>(
-    rc_raw: &[[F::Raw; R]; T],
-    mds_full_raw: &[[F::Raw; T]; T],
-    mds_partial_raw: &[[F::Raw; T]; T],
-    rf: usize,
-    rp: usize,
-) -> PreparedParams<F> {
-    PreparedParams {
-        t: T,
-        rf,
-        rp,
-        rc: rc_raw
-            .iter()
-            .map(|row| row.iter().copied().map(F::from_raw).collect())
-            .collect(),
-        mds_full: mds_full_raw
-            .iter()
-            .map(|row| row.iter().copied().map(F::from_raw).collect())
-            .collect(),
-        mds_partial: mds_partial_raw
-            .iter()
-            .map(|row| row.iter().copied().map(F::from_raw).collect())
-            .collect(),
+// Doubles a trial run until it takes at least a tenth of `budget`, then scales the count up to the
+// whole budget. Integer nanoseconds throughout, so `TextInt` stays `f64`-free.
+fn calibrate_iterations(budget: Duration, mut run: impl FnMut(usize)) -> usize {
+    let mut n = 1usize;
+    loop {
+        let start = Instant::now();
+        run(n);
+        let elapsed = start.elapsed();
+        if elapsed * 10 >= budget || n >= MAX_CALIBRATION_ITERATIONS {
+            if elapsed.is_zero() {
+                return n;
+            }
+            let target = budget.as_nanos() * n as u128 / elapsed.as_nanos();
+            return target.clamp(1, MAX_CALIBRATION_ITERATIONS as u128) as usize;
+        }
+        n *= 2;
     }
 }
 
-// 6 instance parameters (directly reusing tables from binius_poseidon2b/hades)
-fn params_32_t16() -> PreparedParams<BinaryField32b> {
-    use params::params32_t16 as p;
-    prep_params(
-        &p::RC,
-        &p::MDS_FULL,
-        &p::MDS_PARTIAL,
-        p::R_F,
-        p::R_P,
-    )
-}
-fn params_32_t24() -> PreparedParams<BinaryField32b> {
-    use params::params32_t24 as p;
-    prep_params(
-        &p::RC,
-        &p::MDS_FULL,
-        &p::MDS_PARTIAL,
-        p::R_F,
-        p::R_P,
-    )
-}
-fn params_64_t8() -> PreparedParams<BinaryField64b> {
-    use params::params64_t8 as p;
-    prep_params(
-        &p::RC,
-        &p::MDS_FULL,
-        &p::MDS_PARTIAL,
-        p::R_F,
-        p::R_P,
-    )
-}
-fn params_64_t12() -> PreparedParams<BinaryField64b> {
-    use params::params64_t12 as p;
-    prep_params(
-        &p::RC,
-        &p::MDS_FULL,
-        &p::MDS_PARTIAL,
-        p::R_F,
-        p::R_P,
-    )
-}
-fn params_128_t4() -> PreparedParams<BinaryField128b> {
-    use params::params128_t4 as p;
-    prep_params(
-        &p::RC,
-        &p::MDS_FULL,
-        &p::MDS_PARTIAL,
-        p::R_F,
-        p::R_P,
-    )
-}
-fn params_128_t6() -> PreparedParams<BinaryField128b> {
-    use params::params128_t6 as p;
-    prep_params(
-        &p::RC,
-        &p::MDS_FULL,
-        &p::MDS_PARTIAL,
-        p::R_F,
-        p::R_P,
-    )
+// Only measured for `Text` and `Json`; `TextInt` never touches `f64`.
+struct FloatTiming {
+    ns_per_op: f64,
+    perms_per_sec: f64,
+    independent_ns_per_op: f64,
 }
 
-
-// Poseidon2b Permutation
-
-struct Poseidon2b<F: FieldConst> {
+struct BenchResult {
+    instance: String,
     t: usize,
-    rf: usize,
-    rp: usize,
-    rc: Vec<Vec<F>>,
-    mds_full: Vec<Vec<F>>,
-    mds_partial: Vec<Vec<F>>,
-    mds_full_fast: Option<MdsFullFast<F>>,
+    chained: IntTiming,
+    float: Option<FloatTiming>,
 }
 
-impl<F: FieldConst> Poseidon2b<F> {
-    fn new(params: PreparedParams<F>) -> Self {
-        let mds_full_fast = MdsFullFast::new(&params.mds_full, params.t);
-        Self {
-            t: params.t,
-            rf: params.rf,
-            rp: params.rp,
-            rc: params.rc,
-            mds_full: params.mds_full,
-            mds_partial: params.mds_partial,
-            mds_full_fast,
-        }
+fn results_to_json(results: &[BenchResult]) -> String {
+    let rows: Vec<serde_json::Value> = results
+        .iter()
+        .map(|r| {
+            let float = r.float.as_ref().expect("JSON results carry float timings");
+            serde_json::json!({
+                "instance": r.instance,
+                "t": r.t,
+                "ns_per_op": float.ns_per_op,
+                "perms_per_sec": float.perms_per_sec,
+                "independent_ns_per_op": float.independent_ns_per_op,
+            })
+        })
+        .collect();
+    serde_json::to_string_pretty(&rows).expect("bench results serialize")
+}
+
+fn run_poseidon_bench<F: FieldConst>(
+    title: &str,
+    params: PreparedParams<F>,
+    format: OutputFormat,
+    iterations: Iterations,
+) -> BenchResult {
+    if format.is_text() {
+        println!("--------------------------------------------------");
+        println!(
+            "{} | t={} | rf={} | rp={}",
+            title, params.t, params.rf, params.rp
+        );
     }
 
-    fn permute(&self, state: &mut [F]) {
-        debug_assert_eq!(state.len(), self.t);
-        let half_f = self.rf / 2;
-        let mut round = 0usize;
+    let poseidon = Poseidon2b::new(params);
+    if format.is_text() && poseidon.t >= 8 {
+        let path = if poseidon.uses_structured_mds_full() {
+            "D/M blocks"
+        } else {
+            "dense"
+        };
+        println!("MDS_FULL:      {path}");
+    }
 
-        // Minit = MDS_FULL
-        self.mul_mds_full(state);
+    let mut state: Vec<F> = random_state(poseidon.t, &mut StdRng::seed_from_u64(0));
 
-        // First half of full rounds
-        for _ in 0..half_f {
-            self.round_full(state, round);
-            round += 1;
+    let iterations = match iterations {
+        Iterations::Fixed(n) => n,
+        Iterations::Scaled => {
+            // Larger states (larger 't') are slower, so we reduce iterations to keep runtime
+            // reasonable, but keep at least MIN_ITERATIONS for statistical significance.
+            let scale = (poseidon.t / 4).max(1);
+            (BASE_ITERATIONS / scale).max(MIN_ITERATIONS)
         }
+        Iterations::TimeBudget(budget) => calibrate_iterations(budget, |n| {
+            for _ in 0..n {
+                poseidon.permute(&mut state);
+            }
+        }),
+    };
 
-        // Partial rounds
-        for _ in 0..self.rp {
-            self.round_partial(state, round);
-            round += 1;
+    // Chained: each output is the next input, as in a long sponge or up a Merkle path.
+    let start = Instant::now();
+    for _ in 0..iterations {
+        poseidon.permute(&mut state);
+    }
+    let elapsed = start.elapsed();
+    let mut chained = IntTiming::default();
+    chained.add(elapsed, iterations);
+
+    let float = (format != OutputFormat::TextInt).then(|| {
+        // Independent: the same input every time, so consecutive permutations share no data
+        // dependency and can overlap in the pipeline. Includes one `t`-element copy per call.
+        let input = state.clone();
+        let start = Instant::now();
+        for _ in 0..iterations {
+            state.copy_from_slice(black_box(&input));
+            poseidon.permute(&mut state);
+            black_box(&state);
         }
-
-        // Second half of full rounds
-        for _ in 0..half_f {
-            self.round_full(state, round);
-            round += 1;
+        FloatTiming {
+            ns_per_op: elapsed.as_nanos() as f64 / iterations as f64,
+            perms_per_sec: iterations as f64 / elapsed.as_secs_f64(),
+            independent_ns_per_op: start.elapsed().as_nanos() as f64 / iterations as f64,
         }
-    }
+    });
 
-    #[inline(always)]
-    fn round_full(&self, state: &mut [F], r: usize) {
-        for i in 0..self.t {
-            state[i] = state[i].add(self.rc[i][r]);
+    let result = BenchResult {
+        instance: title.to_string(),
+        t: poseidon.t,
+        chained,
+        float,
+    };
+    match (format, &result.float) {
+        (OutputFormat::Text, Some(float)) => {
+            println!("Time per perm: {:.2} ns (chained)", float.ns_per_op);
+            println!("Throughput:    {:.2} perms/sec (chained)", float.perms_per_sec);
+            println!("Independent:   {:.2} ns/perm", float.independent_ns_per_op);
         }
-        for x in state.iter_mut() {
-            *x = x.pow_alpha();
+        (OutputFormat::TextInt, _) => {
+            println!("Time per perm: {} ns (chained)", result.chained.ns_per_op());
+            println!("Throughput:    {} perms/sec (chained)", result.chained.perms_per_sec());
         }
-        self.mul_mds_full(state);
-    }
-
-    #[inline(always)]
-    fn round_partial(&self, state: &mut [F], r: usize) {
-        state[0] = state[0].add(self.rc[0][r]);
-        state[0] = state[0].pow_alpha();
-        self.mul_mds_partial(state);
+        _ => {}
     }
+    result
+}
 
-    fn mul_mds_full(&self, state: &mut [F]) {
-        // t=4: fast algorithm using the ((A B),(B,A)) structure of M4（12 times muls over GF instead of naive 16 times muls over GF）
-        if self.t == 4 {
-            // 2x2 decomposition for M4 
-            let x0 = state[0];
-            let x1 = state[1];
-            let x2 = state[2];
-            let x3 = state[3];
-
-            let a00 = self.mds_full[0][0];
-            let a01 = self.mds_full[0][1];
-            let a10 = self.mds_full[1][0];
-            let a11 = self.mds_full[1][1];
-
-            let b00 = self.mds_full[0][2];
-            let b01 = self.mds_full[0][3];
-            let b10 = self.mds_full[1][2];
-            let b11 = self.mds_full[1][3];
-
-            // 3 block multiplications：
-            // P1 = A * x1, P2 = B * x2, P3 = (A+B)*(x1+x2); y1 = P1+P2, y2 = P3+y1
-            let s0 = x0.add(x2);
-            let s1 = x1.add(x3);
-
-            let p1_0 = a00.mul(x0).add(a01.mul(x1));
-            let p1_1 = a10.mul(x0).add(a11.mul(x1));
-
-            let p2_0 = b00.mul(x2).add(b01.mul(x3));
-            let p2_1 = b10.mul(x2).add(b11.mul(x3));
-
-            let ab00 = a00.add(b00);
-            let ab01 = a01.add(b01);
-            let ab10 = a10.add(b10);
-            let ab11 = a11.add(b11);
-
-            let p3_0 = ab00.mul(s0).add(ab01.mul(s1));
-            let p3_1 = ab10.mul(s0).add(ab11.mul(s1));
-
-            let y0 = p1_0.add(p2_0);
-            let y1 = p1_1.add(p2_1);
-            let y2 = p3_0.add(y0);
-            let y3 = p3_1.add(y1);
-
-            state[0] = y0;
-            state[1] = y1;
-            state[2] = y2;
-            state[3] = y3;
-            return;
-        }
+fn run_all(format: OutputFormat, iterations: Iterations) -> Vec<BenchResult> {
+    let (f, n) = (format, iterations);
+    Instance::all()
+        .map(|inst| {
+            let title = format!("{} (Poseidon2b)", inst.name());
+            match inst {
+                Instance::B32T16 => run_poseidon_bench(&title, params_32_t16(), f, n),
+                Instance::B32T24 => run_poseidon_bench(&title, params_32_t24(), f, n),
+                Instance::B64T8 => run_poseidon_bench(&title, params_64_t8(), f, n),
+                Instance::B64T12 => run_poseidon_bench(&title, params_64_t12(), f, n),
+                Instance::B128T4 => run_poseidon_bench(&title, params_128_t4(), f, n),
+                Instance::B128T6 => run_poseidon_bench(&title, params_128_t6(), f, n),
+            }
+        })
+        .collect()
+}
 
-        if self.t == 6 {
+// `permute` (scratch allocated per call) against `permute_with_workspace` (one preallocated
+// workspace), same state chain for both.
+fn run_workspace_bench<F: FieldConst>(title: &str, params: PreparedParams<F>, iterations: usize) {
+    println!("--------------------------------------------------");
+    println!("{title} | permute vs permute_with_workspace");
 
-            // t=6: naive O(n^2) matrix multiplication
+    let poseidon = Poseidon2b::new(params);
+    let init: Vec<F> = random_state(poseidon.t, &mut StdRng::seed_from_u64(0));
 
-            let mut res = vec![F::default(); self.t];
-            for (r, row) in self.mds_full.iter().enumerate() {
-                let mut acc = F::default();
-                for c in 0..self.t {
-                    acc = acc.add(row[c].mul(state[c]));
-                }
-                res[r] = acc;
-            }
-            state.copy_from_slice(&res);
-            return;
-        }
+    let mut state = init.clone();
+    let start = Instant::now();
+    for _ in 0..iterations {
+        poseidon.permute(&mut state);
+    }
+    let allocating = start.elapsed().as_nanos() as f64 / iterations as f64;
 
-        if matches!(self.t, 8 | 12 | 16 | 24) {
-
-            // t=8/12/16/24: use a MDS_FULL algorithm expoiting its special structure of C⊗M4
-
-            let fast = self.mds_full_fast.as_ref().expect("mds_full_fast missing");
-            let mut tmp = vec![F::default(); self.t];
-            let mut sum = [F::default(); 4];
-
-            // apply M4 to each 4-element block and accumulate by row
-            // reuse the ((A B),(B,A)) structure of M4
-
-            let a00 = self.mds_full[0][4];
-            let a01 = self.mds_full[0][5];
-            let a10 = self.mds_full[1][4];
-            let a11 = self.mds_full[1][5];
-
-            let b00 = self.mds_full[0][6];
-            let b01 = self.mds_full[0][7];
-            let b10 = self.mds_full[1][6];
-            let b11 = self.mds_full[1][7];
-
-            for block in 0..fast.k {
-                let base = block * 4;
-                let x0 = state[base];
-                let x1 = state[base + 1];
-                let x2 = state[base + 2];
-                let x3 = state[base + 3];
-
-                // 3 block-mults: P1=A*x1, P2=B*x2, P3=(A+B)*(x1+x2); y1=P1+P2, y2=P3+y1
-                let s0 = x0.add(x2);
-                let s1 = x1.add(x3);
-
-                let p1_0 = a00.mul(x0).add(a01.mul(x1));
-                let p1_1 = a10.mul(x0).add(a11.mul(x1));
-
-                let p2_0 = b00.mul(x2).add(b01.mul(x3));
-                let p2_1 = b10.mul(x2).add(b11.mul(x3));
-
-                let ab00 = a00.add(b00);
-                let ab01 = a01.add(b01);
-                let ab10 = a10.add(b10);
-                let ab11 = a11.add(b11);
-
-                let p3_0 = ab00.mul(s0).add(ab01.mul(s1));
-                let p3_1 = ab10.mul(s0).add(ab11.mul(s1));
-
-                let y0 = p1_0.add(p2_0);
-                let y1 = p1_1.add(p2_1);
-                let y2 = p3_0.add(y0);
-                let y3 = p3_1.add(y1);
-
-                tmp[base] = y0;
-                tmp[base + 1] = y1;
-                tmp[base + 2] = y2;
-                tmp[base + 3] = y3;
-                sum[0] = sum[0].add(y0);
-                sum[1] = sum[1].add(y1);
-                sum[2] = sum[2].add(y2);
-                sum[3] = sum[3].add(y3);
-            }
+    let mut ws_state = init;
+    let mut ws = Poseidon2bWorkspace::with_capacity(poseidon.t);
+    let start = Instant::now();
+    for _ in 0..iterations {
+        poseidon.permute_with_workspace(&mut ws_state, &mut ws);
+    }
+    let workspace = start.elapsed().as_nanos() as f64 / iterations as f64;
+    assert_eq!(
+        state, ws_state,
+        "workspace and allocating permutations differ"
+    );
 
-            // combine results using C = (X-1)I + J, where J is all-ones matrix
+    println!("Allocating: {allocating:.2} ns/perm");
+    println!("Workspace:  {workspace:.2} ns/perm");
+}
 
-            for block in 0..fast.k {
-                let base = block * 4;
-                for i in 0..4 {
-                    state[base + i] = sum[i].add(tmp[base + i].mul(fast.x_plus_one));
-                }
-            }
-            return;
-        }
+// `hash2` against the general `hash_var` sponge path on the same two elements, per digest.
+fn run_hash2_bench<F: FieldConst>(title: &str, params: PreparedParams<F>, iterations: usize) {
+    println!("--------------------------------------------------");
+    println!("{title} | hash2 vs hash_var");
 
-        // Other t: naive O(n^2) matrix multiplication
+    let poseidon = Poseidon2b::new(params);
+    let input: Vec<F> = random_state(2, &mut StdRng::seed_from_u64(0));
+    let (a, b) = (input[0], input[1]);
+    assert_eq!(hash2(&poseidon, a, b), hash_var(&poseidon, &input), "hash2 and hash_var differ");
 
-        let mut res = vec![F::default(); self.t];
-        for (r, row) in self.mds_full.iter().enumerate() {
-            let mut acc = F::default();
-            for c in 0..self.t {
-                acc = acc.add(row[c].mul(state[c]));
-            }
-            res[r] = acc;
-        }
-        state.copy_from_slice(&res);
+    let start = Instant::now();
+    for _ in 0..iterations {
+        black_box(hash_var(&poseidon, black_box(&input)));
     }
+    let general = start.elapsed().as_nanos() as f64 / iterations as f64;
 
-    //$$y_i = (\mu_i - 1)x_i + \sum_{j=0}^{t-1} x_j$$
-    
-    fn mul_mds_partial(&self, state: &mut [F]) {
-        let mut sum = F::default();
-        for &x in state.iter() {
-            sum = sum.add(x);
-        }
-        for i in 0..self.t {
-            let mu = self.mds_partial[i][i];
-            let mu_minus_one = mu.add(F::from_u8(1));
-            state[i] = sum.add(mu_minus_one.mul(state[i]));
-        }
+    let start = Instant::now();
+    for _ in 0..iterations {
+        black_box(hash2(&poseidon, black_box(a), black_box(b)));
     }
+    let fixed = start.elapsed().as_nanos() as f64 / iterations as f64;
+
+    println!("hash_var: {general:.2} ns/digest");
+    println!("hash2:    {fixed:.2} ns/digest");
 }
 
+// Two sequential `permute` calls against one `permute_pair`, timed per pair of states.
+fn run_pair_bench<F: FieldConst>(title: &str, params: PreparedParams<F>, iterations: usize) {
+    println!("--------------------------------------------------");
+    println!("{title} | permute_pair vs 2x permute");
 
-// Benchmark
+    let poseidon = Poseidon2b::new(params);
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut a: Vec<F> = random_state(poseidon.t, &mut rng);
+    let mut b: Vec<F> = random_state(poseidon.t, &mut rng);
+
+    let start = Instant::now();
+    for _ in 0..iterations {
+        poseidon.permute(&mut a);
+        poseidon.permute(&mut b);
+    }
+    let sequential = start.elapsed().as_nanos() as f64 / iterations as f64;
 
+    let start = Instant::now();
+    for _ in 0..iterations {
+        poseidon.permute_pair(&mut a, &mut b);
+    }
+    let paired = start.elapsed().as_nanos() as f64 / iterations as f64;
+
+    println!("Sequential:    {sequential:.2} ns/pair");
+    println!("permute_pair:  {paired:.2} ns/pair");
+}
 
-fn run_poseidon_bench<F: FieldConst>(title: &str, params: PreparedParams<F>) {
+// `lanes` states permuted one `permute` call at a time against one `permute_batch_soa`, timed per
+// state. Results are checked equal after timing.
+fn run_batch_bench<F: FieldConst>(
+    title: &str,
+    params: PreparedParams<F>,
+    lanes: usize,
+    iterations: usize,
+) {
     println!("--------------------------------------------------");
-    println!(
-        "{} | t={} | rf={} | rp={}",
-        title, params.t, params.rf, params.rp
-    );
+    println!("{title} | permute_batch_soa ({lanes} lanes) vs per-state permute");
 
     let poseidon = Poseidon2b::new(params);
-
-    let mut state: Vec<F> = (0..poseidon.t)
-        .map(|i| F::from_u8((i as u8).wrapping_add(1)))
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut states: Vec<Vec<F>> = (0..lanes)
+        .map(|_| random_state(poseidon.t, &mut rng))
         .collect();
+    let mut batch = BatchState::from_states(&states);
 
-    let base_iter = 100000usize;
-    // Calculate a scaling factor based on state size 't'.
-    // Larger states (larger 't') are slower, so we reduce iterations to keep runtime reasonable.
-    let scale = (poseidon.t / 4).max(1);
-    // Ensure we run at least 20,000 iterations for statistical significance.
-    let iterations = (base_iter / scale).max(20_000);
+    let start = Instant::now();
+    for _ in 0..iterations {
+        for state in states.iter_mut() {
+            poseidon.permute(state);
+        }
+    }
+    let per_state = start.elapsed().as_nanos() as f64 / (iterations * lanes) as f64;
 
     let start = Instant::now();
     for _ in 0..iterations {
-        poseidon.permute(&mut state);
+        poseidon.permute_batch_soa(&mut batch);
     }
-    let elapsed = start.elapsed();
-    let ns_per_op = elapsed.as_nanos() as f64 / iterations as f64;
-    let ops_per_sec = iterations as f64 / elapsed.as_secs_f64();
+    let soa = start.elapsed().as_nanos() as f64 / (iterations * lanes) as f64;
+    assert_eq!(batch.to_states(), states, "batch and per-state results differ");
 
-    println!("Time per perm: {:.2} ns", ns_per_op);
-    println!("Throughput:    {:.2} perms/sec", ops_per_sec);
-    
+    println!("Per-state:         {per_state:.2} ns/state");
+    println!("permute_batch_soa: {soa:.2} ns/state");
 }
 
 fn main() {
+    let format = OutputFormat::from_args();
+    let iterations = Iterations::from_args();
+    if format == OutputFormat::Json {
+        println!("{}", results_to_json(&run_all(format, iterations)));
+        return;
+    }
+
     println!("=== Poseidon2b Benchmark ===");
+    run_all(format, iterations);
+
+    run_workspace_bench("GF(2^32) t=16", params_32_t16(), 100_000);
+    run_pair_bench("GF(2^128) t=4", params_128_t4(), 20_000);
+    run_hash2_bench("GF(2^64) t=8", params_64_t8(), 20_000);
+    run_batch_bench("GF(2^64) t=8", params_64_t8(), 64, 100);
+    run_batch_bench("GF(2^32) t=16", params_32_t16(), 64, 100);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_output_lists_all_instances() {
+        let json = results_to_json(&run_all(OutputFormat::Json, Iterations::Fixed(1)));
+        let rows: Vec<serde_json::Value> = serde_json::from_str(&json).unwrap();
+        assert_eq!(rows.len(), 6);
+        for (row, t) in rows.iter().zip([16, 24, 8, 12, 4, 6]) {
+            assert!(row["instance"]
+                .as_str()
+                .unwrap()
+                .contains(&format!("t={t}")));
+            assert_eq!(row["t"], t);
+            assert!(row["ns_per_op"].is_number());
+            assert!(row["perms_per_sec"].is_number());
+            assert!(row["independent_ns_per_op"].is_number());
+        }
+    }
+
+    #[test]
+    fn integer_timing_matches_float() {
+        // Integer division truncates, so the float lies in [int, int + 1) up to rounding.
+        const EPS: f64 = 1e-6;
+        for r in run_all(OutputFormat::Json, Iterations::Fixed(50)) {
+            let float = r.float.as_ref().unwrap();
+            let ns = r.chained.ns_per_op() as f64;
+            assert!(
+                ns - EPS <= float.ns_per_op && float.ns_per_op < ns + 1.0 + EPS,
+                "{}",
+                r.instance
+            );
+            let per_sec = r.chained.perms_per_sec() as f64;
+            let tol = EPS * per_sec.max(1.0);
+            assert!(
+                per_sec - tol <= float.perms_per_sec && float.perms_per_sec < per_sec + 1.0 + tol,
+                "{}",
+                r.instance
+            );
+        }
+        for r in run_all(OutputFormat::TextInt, Iterations::Fixed(1)) {
+            assert!(r.float.is_none(), "{}", r.instance);
+        }
 
-    run_poseidon_bench("GF(2^32) t=16 (Poseidon2b)", params_32_t16());
-    run_poseidon_bench("GF(2^32) t=24 (Poseidon2b)", params_32_t24());
-    run_poseidon_bench("GF(2^64) t=8 (Poseidon2b)", params_64_t8());
-    run_poseidon_bench("GF(2^64) t=12 (Poseidon2b)", params_64_t12());
-    run_poseidon_bench("GF(2^128) t=4 (Poseidon2b)", params_128_t4());
-    run_poseidon_bench("GF(2^128) t=6 (Poseidon2b)", params_128_t6());
+        let mut timing = IntTiming::default();
+        timing.add(Duration::from_nanos(2_500), 2);
+        timing.add(Duration::from_nanos(500), 1);
+        assert_eq!((timing.ns_per_op(), timing.perms_per_sec()), (1_000, 1_000_000));
+    }
+
+    #[test]
+    fn calibration_terminates() {
+        // A no-op run never reaches the budget and must stop at the doubling cap.
+        let n = calibrate_iterations(Duration::from_secs(3600), |_| {});
+        assert!((1..=MAX_CALIBRATION_ITERATIONS).contains(&n));
+
+        let budget = Duration::from_millis(20);
+        let n = calibrate_iterations(budget, |n| {
+            std::thread::sleep(Duration::from_micros(100) * n as u32)
+        });
+        assert!((1..=200).contains(&n), "n={n}");
+    }
 }
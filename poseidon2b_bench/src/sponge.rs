@@ -0,0 +1,743 @@
+// Sponge over a native permutation.
+//
+// Layout: the first `rate = t - capacity` state elements are the rate, the last `capacity` ones
+// the capacity (one element unless `Sponge::with_capacity` says otherwise). The state starts at
+// zero, except that the last element starts at `capacity - 1` so that sponges with different
+// capacities never share a state. Input blocks are added into the rate and followed by one
+// permutation; squeezing reads the rate and permutes again whenever more output is needed.
+
+use crate::batch::BatchState;
+use crate::{FieldConst, FieldOps, Poseidon2b};
+
+/// A fixed-width permutation to build sponges on.
+pub trait Permutation {
+    type Field: FieldConst;
+    /// The state width `t`.
+    fn width(&self) -> usize;
+    fn permute(&self, state: &mut [Self::Field]);
+}
+
+impl<F: FieldConst> Permutation for Poseidon2b<F> {
+    type Field = F;
+
+    fn width(&self) -> usize {
+        self.t
+    }
+
+    fn permute(&self, state: &mut [F]) {
+        Poseidon2b::permute(self, state);
+    }
+}
+
+/// `Sponge` over the Poseidon2b permutation.
+pub type Poseidon2bSponge<'a, F> = Sponge<'a, Poseidon2b<F>>;
+
+/// How the message is padded to a whole number of rate-sized blocks before the first squeeze.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Padding {
+    /// `10*`: append a one, then zeros up to the next rate multiple. Always adds at least one
+    /// element, so a message of exactly `k * rate` elements gets a whole extra block.
+    Pad10,
+    /// `10*1`: append a one, zeros, and a closing one in the last rate slot. Needs at least two
+    /// free slots, so with a single slot left the closing one goes into an extra block.
+    Pad10Star1,
+    /// No padding, for fixed-length inputs. The message length must be a non-zero multiple of
+    /// the rate.
+    None,
+}
+
+impl Padding {
+    /// The elements this padding appends to a message of `absorbed` elements, `pending` of them
+    /// in the current block.
+    pub fn tail<F: FieldOps>(
+        self,
+        absorbed: usize,
+        pending: usize,
+        rate: usize,
+    ) -> Result<Vec<F>, SpongeError> {
+        let (one, zero) = (F::from_u8(1), F::default());
+        let mut tail = Vec::new();
+        match self {
+            Padding::Pad10 => {
+                tail.push(one);
+                while !(pending + tail.len()).is_multiple_of(rate) {
+                    tail.push(zero);
+                }
+            }
+            Padding::Pad10Star1 => {
+                tail.push(one);
+                while (pending + tail.len()) % rate != rate - 1 {
+                    tail.push(zero);
+                }
+                tail.push(one);
+            }
+            Padding::None => {
+                if absorbed == 0 || pending != 0 {
+                    return Err(SpongeError::UnpaddedLength {
+                        len: absorbed,
+                        rate,
+                    });
+                }
+            }
+        }
+        Ok(tail)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpongeError {
+    /// `Padding::None` was used with a message that is not a non-zero multiple of the rate.
+    UnpaddedLength { len: usize, rate: usize },
+}
+
+impl std::fmt::Display for SpongeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SpongeError::UnpaddedLength { len, rate } => write!(
+                f,
+                "unpadded sponge input has {len} elements, expected a non-zero multiple of the \
+                 rate {rate}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SpongeError {}
+
+#[must_use = "a sponge produces nothing until it is finalized or squeezed"]
+pub struct Sponge<'a, P: Permutation> {
+    perm: &'a P,
+    padding: Padding,
+    rate: usize,
+    state: Vec<P::Field>,
+    // Elements of the current, not yet permuted, input block.
+    pending: usize,
+    absorbed: usize,
+    // `Some(pos)` once squeezing started: index of the next rate element to output.
+    squeeze_pos: Option<usize>,
+}
+
+// Not derived: the derive would require `P: Clone`, and only the reference is copied.
+impl<P: Permutation> Clone for Sponge<'_, P> {
+    fn clone(&self) -> Self {
+        Self {
+            perm: self.perm,
+            padding: self.padding,
+            rate: self.rate,
+            state: self.state.clone(),
+            pending: self.pending,
+            absorbed: self.absorbed,
+            squeeze_pos: self.squeeze_pos,
+        }
+    }
+}
+
+impl<'a, P: Permutation> Sponge<'a, P> {
+    /// A sponge with a one-element capacity.
+    pub fn new(perm: &'a P, padding: Padding) -> Self {
+        Self::with_capacity(perm, padding, 1)
+    }
+
+    /// A sponge keeping `capacity` elements out of the rate, `1 <= capacity < t`.
+    ///
+    /// Generic sponge attacks cost about `2^(c/2)` permutations for a `c`-bit capacity, so each
+    /// extra element adds `n/2` bits of security over an `n`-bit field, and costs one rate
+    /// element: more permutations per absorbed element.
+    pub fn with_capacity(perm: &'a P, padding: Padding, capacity: usize) -> Self {
+        let t = perm.width();
+        assert!(
+            capacity >= 1 && capacity < t,
+            "capacity {capacity} must be in 1..{t} to leave a non-empty rate"
+        );
+        let mut state = vec![P::Field::default(); t];
+        state[t - 1] = P::Field::from_u8((capacity - 1) as u8);
+        Self {
+            perm,
+            padding,
+            rate: t - capacity,
+            state,
+            pending: 0,
+            absorbed: 0,
+            squeeze_pos: None,
+        }
+    }
+
+    pub fn rate(&self) -> usize {
+        self.rate
+    }
+
+    pub fn absorb(&mut self, input: &[P::Field]) {
+        assert!(self.squeeze_pos.is_none(), "absorb called after squeeze");
+        for &x in input {
+            self.absorb_one(x);
+        }
+        self.absorbed += input.len();
+    }
+
+    /// Pads the message on the first call, then returns the next `n` output elements.
+    #[must_use = "squeezing advances the sponge; dropping the output loses it"]
+    pub fn squeeze(&mut self, n: usize) -> Result<Vec<P::Field>, SpongeError> {
+        if self.squeeze_pos.is_none() {
+            self.finish_absorb()?;
+            self.squeeze_pos = Some(0);
+        }
+
+        let mut out = Vec::with_capacity(n);
+        let mut pos = self.squeeze_pos.unwrap_or(0);
+        while out.len() < n {
+            if pos == self.rate {
+                self.perm.permute(&mut self.state);
+                pos = 0;
+            }
+            out.push(self.state[pos]);
+            pos += 1;
+        }
+        self.squeeze_pos = Some(pos);
+        Ok(out)
+    }
+
+    /// Pads the message and returns its first `n` output elements. Takes the sponge by value, so
+    /// nothing can be absorbed into a finished sponge by mistake; `squeeze` keeps it for more
+    /// output instead.
+    #[must_use = "finalize consumes the sponge; the digest is all that is left of it"]
+    pub fn finalize(mut self, n: usize) -> Result<Vec<P::Field>, SpongeError> {
+        self.squeeze(n)
+    }
+
+    fn absorb_one(&mut self, x: P::Field) {
+        self.state[self.pending] += x;
+        self.pending += 1;
+        if self.pending == self.rate {
+            self.perm.permute(&mut self.state);
+            self.pending = 0;
+        }
+    }
+
+    fn finish_absorb(&mut self) -> Result<(), SpongeError> {
+        for x in self.padding.tail(self.absorbed, self.pending, self.rate)? {
+            self.absorb_one(x);
+        }
+        Ok(())
+    }
+}
+
+/// One-element digest of `input`: a `Pad10` sponge, first squeezed element. The empty input is
+/// padded to `[1, 0, ...]` like any other, so its digest is a full permutation output rather than
+/// the zero IV.
+#[must_use]
+pub fn hash_var<P: Permutation>(perm: &P, input: &[P::Field]) -> P::Field {
+    let mut sponge = Sponge::new(perm, Padding::Pad10);
+    sponge.absorb(input);
+    sponge.finalize(1).expect("Pad10 accepts any length")[0]
+}
+
+/// `hash_var(perm, &[a, b])` as a single permutation of `[a, b, 1, 0, ...]`.
+#[must_use]
+pub fn hash2<P: Permutation>(perm: &P, a: P::Field, b: P::Field) -> P::Field {
+    hash_fixed(perm, [a, b])
+}
+
+/// `hash_var(perm, &[a, b, c])` as a single permutation of `[a, b, c, 1, 0, ...]`.
+#[must_use]
+pub fn hash3<P: Permutation>(perm: &P, a: P::Field, b: P::Field, c: P::Field) -> P::Field {
+    hash_fixed(perm, [a, b, c])
+}
+
+// The padded message fits one rate block when N < rate; otherwise (e.g. hash3 with t=4) the
+// sponge needs a second block and there is nothing to skip.
+fn hash_fixed<P: Permutation, const N: usize>(perm: &P, input: [P::Field; N]) -> P::Field {
+    let t = perm.width();
+    if N >= t - 1 {
+        return hash_var(perm, &input);
+    }
+    let mut state = vec![P::Field::default(); t];
+    state[..N].copy_from_slice(&input);
+    state[N] = P::Field::from_u8(1);
+    perm.permute(&mut state);
+    state[0]
+}
+
+/// Duplex construction: each `duplex` call absorbs one input block and squeezes one output block
+/// with a single permutation, and the state carries over between calls.
+///
+/// Rate handling: the rate is `t - 1` as in a default `Sponge`, but every block is `10*`-padded to
+/// the full rate, so one call takes at most `rate - 1` elements (possibly none). The padded block
+/// is added into the rate, the state is permuted, and the whole rate is returned.
+pub struct Duplex<'a, P: Permutation> {
+    perm: &'a P,
+    rate: usize,
+    state: Vec<P::Field>,
+}
+
+impl<P: Permutation> Clone for Duplex<'_, P> {
+    fn clone(&self) -> Self {
+        Self {
+            perm: self.perm,
+            rate: self.rate,
+            state: self.state.clone(),
+        }
+    }
+}
+
+/// `Duplex` over the Poseidon2b permutation.
+pub type Poseidon2bDuplex<'a, F> = Duplex<'a, Poseidon2b<F>>;
+
+impl<'a, P: Permutation> Duplex<'a, P> {
+    pub fn new(perm: &'a P) -> Self {
+        let t = perm.width();
+        assert!(t >= 3, "a padded duplex needs a rate of at least two elements");
+        Self {
+            perm,
+            rate: t - 1,
+            state: vec![P::Field::default(); t],
+        }
+    }
+
+    /// The most elements one `duplex` call accepts: `rate - 1`, leaving room for the padding.
+    pub fn max_input(&self) -> usize {
+        self.rate - 1
+    }
+
+    pub fn duplex(&mut self, input: &[P::Field]) -> Vec<P::Field> {
+        assert!(
+            input.len() <= self.max_input(),
+            "duplex input has {} elements, at most {} fit with padding",
+            input.len(),
+            self.max_input()
+        );
+        for (s, &x) in self.state.iter_mut().zip(input) {
+            *s += x;
+        }
+        self.state[input.len()] += P::Field::from_u8(1);
+        self.perm.permute(&mut self.state);
+        self.state[..self.rate].to_vec()
+    }
+}
+
+/// `lanes` independent `Poseidon2bSponge`s over the same permutation, stepped together so each
+/// permutation runs once for all of them through `permute_batch_soa`.
+///
+/// Every `absorb` call takes one input per lane, all of the same length, so the lanes stay on the
+/// same block boundary; messages of different lengths need their own sponges. Lane `i` produces
+/// exactly what a `Poseidon2bSponge` fed lane `i`'s inputs would.
+#[derive(Clone)]
+pub struct BatchSponge<'a, F: FieldConst> {
+    perm: &'a Poseidon2b<F>,
+    padding: Padding,
+    rate: usize,
+    state: BatchState<F>,
+    pending: usize,
+    absorbed: usize,
+    squeeze_pos: Option<usize>,
+}
+
+impl<'a, F: FieldConst> BatchSponge<'a, F> {
+    pub fn new(perm: &'a Poseidon2b<F>, padding: Padding, lanes: usize) -> Self {
+        assert!(perm.t >= 2, "a sponge needs at least one rate and one capacity element");
+        Self {
+            perm,
+            padding,
+            rate: perm.t - 1,
+            state: BatchState::new(perm.t, lanes),
+            pending: 0,
+            absorbed: 0,
+            squeeze_pos: None,
+        }
+    }
+
+    pub fn lanes(&self) -> usize {
+        self.state.lanes()
+    }
+
+    /// Absorbs `inputs[lane]` into each lane; all inputs must have the same length.
+    pub fn absorb<I: AsRef<[F]>>(&mut self, inputs: &[I]) {
+        assert!(self.squeeze_pos.is_none(), "absorb called after squeeze");
+        assert_eq!(inputs.len(), self.lanes(), "need one input per lane");
+        let len = inputs.first().map_or(0, |input| input.as_ref().len());
+        assert!(
+            inputs.iter().all(|input| input.as_ref().len() == len),
+            "lane inputs differ in length"
+        );
+        for k in 0..len {
+            self.absorb_one(|lane| inputs[lane].as_ref()[k]);
+        }
+        self.absorbed += len;
+    }
+
+    /// Pads on the first call, then returns the next `n` output elements of every lane.
+    #[must_use = "squeezing advances the sponges; dropping the output loses it"]
+    pub fn squeeze(&mut self, n: usize) -> Result<Vec<Vec<F>>, SpongeError> {
+        if self.squeeze_pos.is_none() {
+            for x in self.padding.tail(self.absorbed, self.pending, self.rate)? {
+                self.absorb_one(|_| x);
+            }
+            self.squeeze_pos = Some(0);
+        }
+
+        let mut out = vec![Vec::with_capacity(n); self.lanes()];
+        let mut pos = self.squeeze_pos.unwrap_or(0);
+        for _ in 0..n {
+            if pos == self.rate {
+                self.perm.permute_batch_soa(&mut self.state);
+                pos = 0;
+            }
+            for (lane_out, &x) in out.iter_mut().zip(self.state.column(pos)) {
+                lane_out.push(x);
+            }
+            pos += 1;
+        }
+        self.squeeze_pos = Some(pos);
+        Ok(out)
+    }
+
+    // Adds `value(lane)` at the next rate position of every lane.
+    fn absorb_one(&mut self, value: impl Fn(usize) -> F) {
+        for (lane, s) in self.state.column_mut(self.pending).iter_mut().enumerate() {
+            *s = s.add(value(lane));
+        }
+        self.pending += 1;
+        if self.pending == self.rate {
+            self.perm.permute_batch_soa(&mut self.state);
+            self.pending = 0;
+        }
+    }
+}
+
+/// Rolling digests over a stream: after each element, yields the `Pad10` digest (one rate of
+/// output) of everything absorbed so far.
+///
+/// Absorption itself is never interrupted. Each digest is squeezed from a copy of the sponge, so
+/// the squeeze and its padding do not feed back into the running state, and the `n`-th item equals
+/// hashing the first `n + 1` elements from scratch. Squeezing a copy costs one extra permutation
+/// per element on top of one per full block.
+pub fn poseidon2b_scan<'a, F, I>(
+    perm: &'a Poseidon2b<F>,
+    iter: I,
+) -> impl Iterator<Item = Vec<F>> + 'a
+where
+    F: FieldConst,
+    I: IntoIterator<Item = F>,
+    I::IntoIter: 'a,
+{
+    iter.into_iter()
+        .scan(Poseidon2bSponge::new(perm, Padding::Pad10), |sponge, x| {
+            sponge.absorb(&[x]);
+            let finished = sponge.clone();
+            let rate = finished.rate();
+            Some(finished.finalize(rate).expect("Pad10 accepts any length"))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::params_64_t8;
+    use binius_field::BinaryField64b;
+
+    type F = BinaryField64b;
+
+    fn message(len: usize) -> Vec<F> {
+        (0..len).map(|i| F::from_u8(i as u8 + 3)).collect()
+    }
+
+    // Absorbs already padded blocks by hand and returns the first rate of output.
+    fn manual_digest(perm: &Poseidon2b<F>, padded: &[F]) -> Vec<F> {
+        let rate = perm.t - 1;
+        assert_eq!(padded.len() % rate, 0);
+        let mut state = vec![F::default(); perm.t];
+        for block in padded.chunks(rate) {
+            for (s, &x) in state.iter_mut().zip(block) {
+                *s += x;
+            }
+            perm.permute(&mut state);
+        }
+        state[..rate].to_vec()
+    }
+
+    fn digest(perm: &Poseidon2b<F>, padding: Padding, msg: &[F]) -> Result<Vec<F>, SpongeError> {
+        let mut sponge = Poseidon2bSponge::new(perm, padding);
+        sponge.absorb(msg);
+        sponge.squeeze(sponge.rate())
+    }
+
+    #[test]
+    fn pad10_boundary_lengths() {
+        let perm = Poseidon2b::new(params_64_t8());
+        let rate = perm.t - 1;
+        let (one, zero) = (F::from_u8(1), F::default());
+        for len in [0, rate - 1, rate, rate + 1] {
+            let mut padded = message(len);
+            padded.push(one);
+            while !padded.len().is_multiple_of(rate) {
+                padded.push(zero);
+            }
+            assert_eq!(
+                digest(&perm, Padding::Pad10, &message(len)),
+                Ok(manual_digest(&perm, &padded)),
+                "len={len}"
+            );
+        }
+    }
+
+    #[test]
+    fn pad10star1_boundary_lengths() {
+        let perm = Poseidon2b::new(params_64_t8());
+        let rate = perm.t - 1;
+        let (one, zero) = (F::from_u8(1), F::default());
+        for len in [0, rate - 2, rate - 1, rate, rate + 1] {
+            let mut padded = message(len);
+            padded.push(one);
+            while padded.len() % rate != rate - 1 {
+                padded.push(zero);
+            }
+            padded.push(one);
+            assert_eq!(
+                digest(&perm, Padding::Pad10Star1, &message(len)),
+                Ok(manual_digest(&perm, &padded)),
+                "len={len}"
+            );
+        }
+    }
+
+    #[test]
+    fn no_padding_requires_rate_multiple() {
+        let perm = Poseidon2b::new(params_64_t8());
+        let rate = perm.t - 1;
+        for len in [rate, 2 * rate] {
+            assert_eq!(
+                digest(&perm, Padding::None, &message(len)),
+                Ok(manual_digest(&perm, &message(len)))
+            );
+        }
+        for len in [0, 1, rate - 1, rate + 1] {
+            assert_eq!(
+                digest(&perm, Padding::None, &message(len)),
+                Err(SpongeError::UnpaddedLength { len, rate })
+            );
+        }
+    }
+
+    #[test]
+    fn paddings_give_different_digests() {
+        let perm = Poseidon2b::new(params_64_t8());
+        let msg = message(3);
+        assert_ne!(
+            digest(&perm, Padding::Pad10, &msg),
+            digest(&perm, Padding::Pad10Star1, &msg)
+        );
+    }
+
+    #[test]
+    fn squeeze_continues_across_calls() {
+        let perm = Poseidon2b::new(params_64_t8());
+        let mut one_call = Poseidon2bSponge::new(&perm, Padding::Pad10);
+        one_call.absorb(&message(5));
+        let all = one_call.squeeze(20).unwrap();
+
+        let mut split = Poseidon2bSponge::new(&perm, Padding::Pad10);
+        split.absorb(&message(5));
+        let mut parts = split.squeeze(3).unwrap();
+        parts.extend(split.squeeze(17).unwrap());
+        assert_eq!(parts, all);
+    }
+
+    #[test]
+    fn finalize_consumes_and_matches_squeeze() {
+        let perm = Poseidon2b::new(params_64_t8());
+        let mut sponge = Poseidon2bSponge::new(&perm, Padding::Pad10);
+        sponge.absorb(&message(9));
+        let mut kept = sponge.clone();
+        // `sponge` is moved here; using it afterwards is a compile error (tests/sponge_must_use.rs).
+        let digest = sponge.finalize(10).unwrap();
+        assert_eq!(digest, kept.squeeze(10).unwrap());
+
+        let mut unpadded = Poseidon2bSponge::new(&perm, Padding::None);
+        unpadded.absorb(&message(3));
+        assert_eq!(
+            unpadded.finalize(1),
+            Err(SpongeError::UnpaddedLength { len: 3, rate: 7 })
+        );
+    }
+
+    #[test]
+    fn scan_yields_prefix_digests() {
+        let perm = Poseidon2b::new(params_64_t8());
+        let msg = message(2 * (perm.t - 1) + 1);
+        let digests: Vec<Vec<F>> = poseidon2b_scan(&perm, msg.iter().copied()).collect();
+        assert_eq!(digests.len(), msg.len());
+        for (n, d) in digests.iter().enumerate() {
+            assert_eq!(Ok(d.clone()), digest(&perm, Padding::Pad10, &msg[..=n]), "prefix {n}");
+        }
+    }
+
+    #[test]
+    fn scan_of_empty_stream_is_empty() {
+        let perm = Poseidon2b::new(params_64_t8());
+        assert_eq!(poseidon2b_scan(&perm, Vec::<F>::new()).count(), 0);
+    }
+
+    #[test]
+    fn duplex_matches_manual_permutations() {
+        let perm = Poseidon2b::new(params_64_t8());
+        let rate = perm.t - 1;
+        let blocks = [message(0), message(3), message(rate - 1)];
+
+        let mut duplex = Poseidon2bDuplex::new(&perm);
+        let outputs: Vec<Vec<F>> = blocks.iter().map(|b| duplex.duplex(b)).collect();
+
+        let mut state = vec![F::default(); perm.t];
+        for (block, out) in blocks.iter().zip(&outputs) {
+            let mut padded = block.clone();
+            padded.push(F::from_u8(1));
+            for (s, &x) in state.iter_mut().zip(&padded) {
+                *s += x;
+            }
+            perm.permute(&mut state);
+            assert_eq!(out, &state[..rate]);
+        }
+    }
+
+    #[test]
+    fn duplex_is_reproducible_and_input_dependent() {
+        let perm = Poseidon2b::new(params_64_t8());
+        let run = |second: &[F]| {
+            let mut duplex = Poseidon2bDuplex::new(&perm);
+            [duplex.duplex(&message(2)), duplex.duplex(second), duplex.duplex(&[])]
+        };
+        let a = run(&message(4));
+        assert_eq!(a, run(&message(4)));
+
+        let b = run(&message(3));
+        assert_eq!(a[0], b[0]);
+        assert_ne!(a[1], b[1]);
+        // The state carries over, so later outputs differ too.
+        assert_ne!(a[2], b[2]);
+    }
+
+    #[test]
+    fn empty_input_is_padded_and_permuted() {
+        let perm = Poseidon2b::new(params_64_t8());
+        let rate = perm.t - 1;
+        let one = F::from_u8(1);
+
+        let mut sponge = Poseidon2bSponge::new(&perm, Padding::Pad10);
+        sponge.absorb(&[]);
+        let mut padded = vec![F::default(); rate];
+        padded[0] = one;
+        assert_eq!(sponge.squeeze(rate), Ok(manual_digest(&perm, &padded)));
+        padded[rate - 1] = one;
+        assert_eq!(digest(&perm, Padding::Pad10Star1, &[]), Ok(manual_digest(&perm, &padded)));
+
+        let empty = hash_var(&perm, &[]);
+        assert_eq!(empty, hash_var(&perm, &[]));
+        assert_ne!(empty, F::default());
+        assert_ne!(empty, hash_var(&perm, &[F::default()]));
+    }
+
+    #[test]
+    fn batch_sponge_lanes_match_single_sponges() {
+        let perm = Poseidon2b::new(params_64_t8());
+        let rate = perm.t - 1;
+        // Lane 2 repeats lane 0 to check that equal lanes stay equal.
+        let lane_msg = |lane: usize, len: usize| -> Vec<F> {
+            (0..len).map(|i| F::from_u8((lane % 2 * 50 + i) as u8)).collect()
+        };
+        for padding in [Padding::Pad10, Padding::Pad10Star1] {
+            for len in [0, rate - 1, rate, 2 * rate + 3] {
+                let msgs: Vec<Vec<F>> = (0..3).map(|lane| lane_msg(lane, len)).collect();
+                let mut batch = BatchSponge::new(&perm, padding, msgs.len());
+                // Two absorb calls, split off the block boundary.
+                let split = len / 3;
+                let heads: Vec<&[F]> = msgs.iter().map(|m| &m[..split]).collect();
+                let tails: Vec<&[F]> = msgs.iter().map(|m| &m[split..]).collect();
+                batch.absorb(&heads);
+                batch.absorb(&tails);
+                let mut out = batch.squeeze(3).unwrap();
+                for (lane, more) in batch.squeeze(rate).unwrap().into_iter().enumerate() {
+                    out[lane].extend(more);
+                }
+
+                for (lane, msg) in msgs.iter().enumerate() {
+                    let mut sponge = Poseidon2bSponge::new(&perm, padding);
+                    sponge.absorb(msg);
+                    assert_eq!(out[lane], sponge.squeeze(3 + rate).unwrap(), "len={len}");
+                }
+                assert_eq!(out[0], out[2]);
+                assert_eq!(out[0] == out[1], len == 0);
+            }
+        }
+    }
+
+    #[test]
+    fn batch_sponge_unpadded_length_error() {
+        let perm = Poseidon2b::new(params_64_t8());
+        let mut batch = BatchSponge::new(&perm, Padding::None, 2);
+        batch.absorb(&[message(3), message(3)]);
+        assert_eq!(
+            batch.squeeze(1),
+            Err(SpongeError::UnpaddedLength { len: 3, rate: 7 })
+        );
+    }
+
+    #[test]
+    fn capacity_sets_rate_and_separates_digests() {
+        let perm = Poseidon2b::new(params_64_t8());
+        let msg = message(11);
+        let mut digests = Vec::new();
+        for capacity in 1..=3 {
+            let mut sponge = Poseidon2bSponge::with_capacity(&perm, Padding::Pad10, capacity);
+            assert_eq!(sponge.rate(), perm.t - capacity);
+            sponge.absorb(&msg);
+            let digest = sponge.squeeze(4).unwrap();
+
+            // Same digest by hand: 11 elements plus padding take two blocks of a rate of 7 or 6,
+            // but three blocks of a rate of 5.
+            let rate = perm.t - capacity;
+            let mut padded = msg.clone();
+            padded.push(F::from_u8(1));
+            while !padded.len().is_multiple_of(rate) {
+                padded.push(F::default());
+            }
+            assert_eq!(padded.len() / rate, if capacity < 3 { 2 } else { 3 });
+            let mut state = vec![F::default(); perm.t];
+            state[perm.t - 1] = F::from_u8(capacity as u8 - 1);
+            for block in padded.chunks(rate) {
+                for (s, &x) in state.iter_mut().zip(block) {
+                    *s += x;
+                }
+                perm.permute(&mut state);
+            }
+            assert_eq!(digest, state[..4]);
+
+            assert!(!digests.contains(&digest), "capacity {capacity}");
+            digests.push(digest);
+        }
+        // The capacity is separated even when the padded message fits one block of either rate.
+        assert_ne!(
+            Poseidon2bSponge::with_capacity(&perm, Padding::Pad10, 1).squeeze(4),
+            Poseidon2bSponge::with_capacity(&perm, Padding::Pad10, 2).squeeze(4)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "capacity 8 must be in 1..8")]
+    fn capacity_must_leave_a_rate() {
+        let perm = Poseidon2b::new(params_64_t8());
+        let _ = Poseidon2bSponge::with_capacity(&perm, Padding::Pad10, 8);
+    }
+
+    #[test]
+    fn fixed_hashes_match_hash_var() {
+        let perm = Poseidon2b::new(params_64_t8());
+        let [a, b, c] = [F::from_u8(7), F::from_u8(8), F::from_u8(9)];
+        assert_eq!(hash2(&perm, a, b), hash_var(&perm, &[a, b]));
+        assert_eq!(hash3(&perm, a, b, c), hash_var(&perm, &[a, b, c]));
+        assert_ne!(hash2(&perm, a, b), hash2(&perm, b, a));
+
+        // t=4 has rate 3, so hash3 needs the two-block sponge path.
+        let perm = Poseidon2b::new(crate::params_128_t4());
+        let [a, b, c] = [1, 2, 3].map(binius_field::BinaryField128b::from_u8);
+        assert_eq!(hash2(&perm, a, b), hash_var(&perm, &[a, b]));
+        assert_eq!(hash3(&perm, a, b, c), hash_var(&perm, &[a, b, c]));
+    }
+}
@@ -0,0 +1,126 @@
+// Fiat-Shamir transcript over the Poseidon2b sponge.
+//
+// Every message goes through one running `Pad10` sponge, so a challenge depends on everything
+// absorbed before it. Absorb order, with `bytes(..)` packing bytes little-endian into
+// `F::BYTES`-wide elements (last one zero-padded) and `len(..)` a `u64` length packed the same way:
+//
+//   new(protocol):        TAG_PROTOCOL, len(protocol), bytes(protocol)
+//   append(label, data):  TAG_APPEND,   len(label),    bytes(label), len(data), data
+//   challenge():          TAG_CHALLENGE, then the returned challenge itself
+//
+// The tags and length prefixes keep the encoding injective, so moving elements between appends or
+// changing a label changes every later challenge. A challenge is squeezed from a copy of the
+// sponge, as in `sponge::poseidon2b_scan`, and then absorbed back, so absorbing can continue
+// afterwards.
+
+use crate::sponge::{Padding, Poseidon2bSponge};
+use crate::{FieldConst, Poseidon2b};
+
+const TAG_PROTOCOL: u8 = 0;
+const TAG_APPEND: u8 = 1;
+const TAG_CHALLENGE: u8 = 2;
+
+#[derive(Clone)]
+pub struct Transcript<'a, F: FieldConst> {
+    sponge: Poseidon2bSponge<'a, F>,
+}
+
+impl<'a, F: FieldConst> Transcript<'a, F> {
+    /// Starts a transcript bound to `protocol`, so two protocols never share challenges.
+    pub fn new(perm: &'a Poseidon2b<F>, protocol: &[u8]) -> Self {
+        let mut transcript = Self {
+            sponge: Poseidon2bSponge::new(perm, Padding::Pad10),
+        };
+        transcript.absorb_tag(TAG_PROTOCOL);
+        transcript.absorb_bytes(protocol);
+        transcript
+    }
+
+    pub fn append(&mut self, label: &[u8], data: &[F]) {
+        self.absorb_tag(TAG_APPEND);
+        self.absorb_bytes(label);
+        self.absorb_len(data.len());
+        self.sponge.absorb(data);
+    }
+
+    pub fn challenge(&mut self) -> F {
+        self.absorb_tag(TAG_CHALLENGE);
+        let finished = self.sponge.clone();
+        let c = finished.finalize(1).expect("Pad10 accepts any length")[0];
+        self.sponge.absorb(&[c]);
+        c
+    }
+
+    fn absorb_tag(&mut self, tag: u8) {
+        self.sponge.absorb(&[F::from_u8(tag)]);
+    }
+
+    fn absorb_len(&mut self, len: usize) {
+        self.sponge.absorb(&pack_bytes(&(len as u64).to_le_bytes()));
+    }
+
+    fn absorb_bytes(&mut self, bytes: &[u8]) {
+        self.absorb_len(bytes.len());
+        self.sponge.absorb(&pack_bytes(bytes));
+    }
+}
+
+fn pack_bytes<F: FieldConst>(bytes: &[u8]) -> Vec<F> {
+    bytes
+        .chunks(F::BYTES)
+        .map(|chunk| {
+            let mut buf = vec![0u8; F::BYTES];
+            buf[..chunk.len()].copy_from_slice(chunk);
+            F::from_le_bytes(&buf)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{params_32_t16, FieldOps};
+    use binius_field::BinaryField32b;
+
+    type F = BinaryField32b;
+
+    fn elems(vals: &[u8]) -> Vec<F> {
+        vals.iter().map(|&v| F::from_u8(v)).collect()
+    }
+
+    // append("commit", 1 2 3), challenge, append("eval", 4), challenge, challenge
+    fn run(perm: &Poseidon2b<F>, protocol: &[u8]) -> Vec<F> {
+        let mut t = Transcript::new(perm, protocol);
+        t.append(b"commit", &elems(&[1, 2, 3]));
+        let c0 = t.challenge();
+        t.append(b"eval", &elems(&[4]));
+        vec![c0, t.challenge(), t.challenge()]
+    }
+
+    #[test]
+    fn same_sequence_same_challenges() {
+        let perm = Poseidon2b::new(params_32_t16());
+        let challenges = run(&perm, b"test protocol");
+        assert_eq!(challenges, run(&perm, b"test protocol"));
+        assert_ne!(challenges[1], challenges[2], "back-to-back challenges must differ");
+        assert_ne!(challenges, run(&perm, b"other protocol"));
+    }
+
+    #[test]
+    fn labels_and_message_boundaries_separate() {
+        let perm = Poseidon2b::new(params_32_t16());
+        let challenge_after = |appends: &[(&[u8], &[u8])]| {
+            let mut t = Transcript::new(&perm, b"p");
+            for &(label, data) in appends {
+                t.append(label, &elems(data));
+            }
+            t.challenge()
+        };
+
+        let base = challenge_after(&[(b"a", &[1, 2])]);
+        assert_ne!(base, challenge_after(&[(b"b", &[1, 2])]));
+        assert_ne!(base, challenge_after(&[(b"a", &[1]), (b"a", &[2])]));
+        // Label bytes must not be confused with data elements.
+        assert_ne!(base, challenge_after(&[(b"", &[b'a', 1, 2])]));
+    }
+}
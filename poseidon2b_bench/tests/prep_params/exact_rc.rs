@@ -0,0 +1,9 @@
+// 4 + 3 rounds with a 7-column round constant table.
+use poseidon2b_runner::prep_params;
+
+fn main() {
+    let rc = [[0u64; 7]; 2];
+    let mds = [[1u64; 2]; 2];
+    let p = prep_params::<binius_field::BinaryField64b, 2, 7, 4, 3>(&rc, &mds, &mds);
+    assert_eq!((p.rf, p.rp), (4, 3));
+}
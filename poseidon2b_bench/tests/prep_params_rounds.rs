@@ -0,0 +1,9 @@
+// `prep_params` rejects a round constant table shorter than RF + RP at compile time. A passing
+// case is included so trybuild runs `cargo build`, which is where the const assertion fires.
+
+#[test]
+fn prep_params_rounds() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/prep_params/exact_rc.rs");
+    t.compile_fail("tests/prep_params/short_rc.rs");
+}
@@ -0,0 +1,12 @@
+// Absorbing and then dropping the digest: the sponge never produced anything.
+#![deny(unused_must_use)]
+
+use poseidon2b_runner::{params_64_t8, sponge::{Padding, Poseidon2bSponge}, Poseidon2b};
+
+fn main() {
+    let perm = Poseidon2b::new(params_64_t8());
+    let mut sponge = Poseidon2bSponge::new(&perm, Padding::Pad10);
+    sponge.absorb(&[Default::default(); 3]);
+    sponge.finalize(1);
+    Poseidon2bSponge::new(&perm, Padding::Pad10);
+}
@@ -0,0 +1,10 @@
+// A finalized sponge is gone; absorbing into it again does not compile.
+use poseidon2b_runner::{params_64_t8, sponge::{Padding, Poseidon2bSponge}, Poseidon2b};
+
+fn main() {
+    let perm = Poseidon2b::new(params_64_t8());
+    let mut sponge = Poseidon2bSponge::new(&perm, Padding::Pad10);
+    sponge.absorb(&[Default::default(); 3]);
+    let _digest = sponge.finalize(1);
+    sponge.absorb(&[Default::default(); 3]);
+}
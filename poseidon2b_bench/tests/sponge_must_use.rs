@@ -0,0 +1,9 @@
+// `Poseidon2bSponge::finalize` takes the sponge by value and its digest is `#[must_use]`: dropping
+// the digest warns (an error under `deny`), and so does touching the sponge after finalizing.
+
+#[test]
+fn sponge_must_use() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/sponge/unused_digest.rs");
+    t.compile_fail("tests/sponge/use_after_finalize.rs");
+}
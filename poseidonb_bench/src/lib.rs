@@ -0,0 +1,264 @@
+pub mod params;
+
+use binius_field::{BinaryField128b, BinaryField32b, BinaryField64b, PackedField};
+use std::fmt::Debug;
+
+
+pub trait FieldOps:
+    'static + Copy + Clone + Debug + Default + PartialEq + Send + Sync
+{
+    fn add(self, rhs: Self) -> Self;
+    fn mul(self, rhs: Self) -> Self;
+    fn safe_square(self) -> Self;
+    fn from_u8(v: u8) -> Self;
+    fn pow_alpha(self) -> Self {
+        let x2 = self.safe_square();
+        let x4 = x2.safe_square();
+        self.mul(x2).mul(x4) // x^7
+    }
+}
+
+// Allows populating constants from "native representation" (consistent with the tower basis in binius_field).
+pub trait FieldConst: FieldOps {
+    type Raw: Copy;
+    fn from_raw(v: Self::Raw) -> Self;
+}
+
+macro_rules! impl_field_ops {
+    ($ty:ty, $raw:ty) => {
+        impl FieldOps for $ty {
+            #[inline(always)]
+            fn add(self, rhs: Self) -> Self { self + rhs }
+            #[inline(always)]
+            fn mul(self, rhs: Self) -> Self { self * rhs }
+            #[inline(always)]
+            fn safe_square(self) -> Self { self.square() }
+            #[inline(always)]
+            fn from_u8(v: u8) -> Self { Self::from(v as $raw) }
+        }
+        impl FieldConst for $ty {
+            type Raw = $raw;
+            #[inline(always)]
+            fn from_raw(v: Self::Raw) -> Self { Self::from(v) }
+        }
+    };
+}
+
+impl_field_ops!(BinaryField32b, u32);
+impl_field_ops!(BinaryField64b, u64);
+impl_field_ops!(BinaryField128b, u128);
+
+
+// Poseidonb parameter structure
+
+pub struct PreparedParams<F: FieldConst> {
+    pub t: usize,
+    pub rf: usize,
+    pub rp: usize,
+    pub rc: Vec<Vec<F>>,
+    pub mds_full: Vec<Vec<F>>,
+    pub mds_partial: Vec<Vec<F>>,
+}
+
+/// Converts raw tables into field elements. `RF` and `RP` are the full and partial round counts;
+/// an `rc_raw` with fewer than `RF + RP` columns is rejected at compile time.
+pub fn prep_params<
+    F: FieldConst,
+    const T: usize,
+    const R: usize,
+    const RF: usize,
+    const RP: usize,
+>(
+    rc_raw: &[[F::Raw; R]; T],
+    mds_full_raw: &[[F::Raw; T]; T],
+    mds_partial_raw: &[[F::Raw; T]; T],
+) -> PreparedParams<F> {
+    const { assert!(RF + RP <= R, "round constant table has fewer columns than RF + RP") };
+    PreparedParams {
+        t: T,
+        rf: RF,
+        rp: RP,
+        rc: rc_raw
+            .iter()
+            .map(|row| row.iter().copied().map(F::from_raw).collect())
+            .collect(),
+        mds_full: mds_full_raw
+            .iter()
+            .map(|row| row.iter().copied().map(F::from_raw).collect())
+            .collect(),
+        mds_partial: mds_partial_raw
+            .iter()
+            .map(|row| row.iter().copied().map(F::from_raw).collect())
+            .collect(),
+    }
+}
+
+// 6 instance parameters (directly reusing tables from binius_poseidonb/hades)
+pub fn params_32_t16() -> PreparedParams<BinaryField32b> {
+    use params::params32_t16 as p;
+    prep_params::<_, { p::STATE_SIZE }, { p::R_F + p::R_P }, { p::R_F }, { p::R_P }>(&p::RC, &p::MDS_FULL, &p::MDS_PARTIAL)
+}
+pub fn params_32_t24() -> PreparedParams<BinaryField32b> {
+    use params::params32_t24 as p;
+    prep_params::<_, { p::STATE_SIZE }, { p::R_F + p::R_P }, { p::R_F }, { p::R_P }>(&p::RC, &p::MDS_FULL, &p::MDS_PARTIAL)
+}
+pub fn params_64_t8() -> PreparedParams<BinaryField64b> {
+    use params::params64_t8 as p;
+    prep_params::<_, { p::STATE_SIZE }, { p::R_F + p::R_P }, { p::R_F }, { p::R_P }>(&p::RC, &p::MDS_FULL, &p::MDS_PARTIAL)
+}
+pub fn params_64_t12() -> PreparedParams<BinaryField64b> {
+    use params::params64_t12 as p;
+    prep_params::<_, { p::STATE_SIZE }, { p::R_F + p::R_P }, { p::R_F }, { p::R_P }>(&p::RC, &p::MDS_FULL, &p::MDS_PARTIAL)
+}
+pub fn params_128_t4() -> PreparedParams<BinaryField128b> {
+    use params::params128_t4 as p;
+    prep_params::<_, { p::STATE_SIZE }, { p::R_F + p::R_P }, { p::R_F }, { p::R_P }>(&p::RC, &p::MDS_FULL, &p::MDS_PARTIAL)
+}
+pub fn params_128_t6() -> PreparedParams<BinaryField128b> {
+    use params::params128_t6 as p;
+    prep_params::<_, { p::STATE_SIZE }, { p::R_F + p::R_P }, { p::R_F }, { p::R_P }>(&p::RC, &p::MDS_FULL, &p::MDS_PARTIAL)
+}
+
+
+
+// Poseidonb Permutation
+
+/// Native Poseidonb permutation.
+pub struct Poseidonb<F: FieldConst> {
+    pub t: usize,
+    rf: usize,
+    rp: usize,
+    rc: Vec<Vec<F>>,
+    mds_full: Vec<Vec<F>>,
+    mds_partial: Vec<Vec<F>>,
+}
+
+impl<F: FieldConst> Poseidonb<F> {
+    pub fn new(params: PreparedParams<F>) -> Self {
+        Self {
+            t: params.t,
+            rf: params.rf,
+            rp: params.rp,
+            rc: params.rc,
+            mds_full: params.mds_full,
+            mds_partial: params.mds_partial,
+        }
+    }
+
+    pub fn permute(&self, state: &mut [F]) {
+        debug_assert_eq!(state.len(), self.t);
+        let half_f = self.rf / 2;
+        let mut round = 0usize;
+
+        // Minit = MDS_FULL
+        self.mul_mds_full(state);
+
+        // First half of full rounds
+        for _ in 0..half_f {
+            self.round_full(state, round);
+            round += 1;
+        }
+
+        // Partial rounds
+        for _ in 0..self.rp {
+            self.round_partial(state, round);
+            round += 1;
+        }
+
+        // Second half of full rounds
+        for _ in 0..half_f {
+            self.round_full(state, round);
+            round += 1;
+        }
+    }
+
+    #[inline(always)]
+    fn round_full(&self, state: &mut [F], r: usize) {
+        for (x, rc) in state.iter_mut().zip(&self.rc) {
+            *x = x.add(rc[r]);
+        }
+        for x in state.iter_mut() {
+            *x = x.pow_alpha();
+        }
+        self.mul_mds_full(state);
+    }
+
+    #[inline(always)]
+    fn round_partial(&self, state: &mut [F], r: usize) {
+        state[0] = state[0].add(self.rc[0][r]);
+        state[0] = state[0].pow_alpha();
+        self.mul_mds_partial(state);
+    }
+
+    fn mul_mds_full(&self, state: &mut [F]) {
+
+        // t=4: fast algorithm using the ((A B),(B,A)) structure of M4（12 times muls over GF instead of naive 16 times muls over GF）
+        // 3 block-mults in GF(2^n): P1=A*x1, P2=B*x2, P3=(A+B)*(x1+x2); y1=P1+P2, y2=P3+y1
+        if self.t == 4 {
+            let x0 = state[0];
+            let x1 = state[1];
+            let x2 = state[2];
+            let x3 = state[3];
+
+            let a00 = self.mds_full[0][0];
+            let a01 = self.mds_full[0][1];
+            let a10 = self.mds_full[1][0];
+            let a11 = self.mds_full[1][1];
+
+            let b00 = self.mds_full[0][2];
+            let b01 = self.mds_full[0][3];
+            let b10 = self.mds_full[1][2];
+            let b11 = self.mds_full[1][3];
+
+            let s0 = x0.add(x2);
+            let s1 = x1.add(x3);
+
+            let p1_0 = a00.mul(x0).add(a01.mul(x1));
+            let p1_1 = a10.mul(x0).add(a11.mul(x1));
+
+            let p2_0 = b00.mul(x2).add(b01.mul(x3));
+            let p2_1 = b10.mul(x2).add(b11.mul(x3));
+
+            let ab00 = a00.add(b00);
+            let ab01 = a01.add(b01);
+            let ab10 = a10.add(b10);
+            let ab11 = a11.add(b11);
+
+            let p3_0 = ab00.mul(s0).add(ab01.mul(s1));
+            let p3_1 = ab10.mul(s0).add(ab11.mul(s1));
+
+            let y0 = p1_0.add(p2_0);
+            let y1 = p1_1.add(p2_1);
+            let y2 = p3_0.add(y0);
+            let y3 = p3_1.add(y1);
+
+            state[0] = y0;
+            state[1] = y1;
+            state[2] = y2;
+            state[3] = y3;
+            return;
+        }
+
+        let mut res = vec![F::default(); self.t];
+        for (r, row) in self.mds_full.iter().enumerate() {
+            let mut acc = F::default();
+            for c in 0..self.t {
+                acc = acc.add(row[c].mul(state[c]));
+            }
+            res[r] = acc;
+        }
+        state.copy_from_slice(&res);
+    }
+
+    //$$y_i = (\mu_i - 1)x_i + \sum_{j=0}^{t-1} x_j$$
+    fn mul_mds_partial(&self, state: &mut [F]) {
+        let mut sum = F::default();
+        for &x in state.iter() {
+            sum = sum.add(x);
+        }
+        for (i, (x, row)) in state.iter_mut().zip(&self.mds_partial).enumerate() {
+            let mu_minus_one = row[i].add(F::from_u8(1));
+            *x = sum.add(mu_minus_one.mul(*x));
+        }
+    }
+}
@@ -1,323 +1,91 @@
-mod params;
-
-use binius_field::{
-    BinaryField128b, BinaryField32b, BinaryField64b,
-    PackedField, 
+use poseidonb_runner::{
+    params_128_t4, params_128_t6, params_32_t16, params_32_t24, params_64_t12, params_64_t8,
+    FieldConst, Poseidonb, PreparedParams,
+};
+use rand::{
+    distributions::{Distribution, Standard},
+    rngs::StdRng,
+    Rng, SeedableRng,
 };
-use std::fmt::Debug;
 use std::time::Instant;
 
+// Benchmark
 
-pub trait FieldOps:
-    'static + Copy + Clone + Debug + Default + PartialEq + Send + Sync
-{
-    fn add(self, rhs: Self) -> Self;
-    fn mul(self, rhs: Self) -> Self;
-    fn safe_square(self) -> Self;
-    fn from_u8(v: u8) -> Self;
-    fn pow_alpha(self) -> Self {
-        let x2 = self.safe_square();
-        let x4 = x2.safe_square();
-        self.mul(x2).mul(x4) // x^7
-    }
-}
-
-// Allows populating constants from "native representation" (consistent with the tower basis in binius_field).
-pub trait FieldConst: FieldOps {
-    type Raw: Copy;
-    fn from_raw(v: Self::Raw) -> Self;
-}
-
-macro_rules! impl_field_ops {
-    ($ty:ty, $raw:ty) => {
-        impl FieldOps for $ty {
-            #[inline(always)]
-            fn add(self, rhs: Self) -> Self { self + rhs }
-            #[inline(always)]
-            fn mul(self, rhs: Self) -> Self { self * rhs }
-            #[inline(always)]
-            fn safe_square(self) -> Self { self.square() }
-            #[inline(always)]
-            fn from_u8(v: u8) -> Self { Self::from(v as $raw) }
-        }
-        impl FieldConst for $ty {
-            type Raw = $raw;
-            #[inline(always)]
-            fn from_raw(v: Self::Raw) -> Self { Self::from(v) }
-        }
-    };
-}
-
-impl_field_ops!(BinaryField32b, u32);
-impl_field_ops!(BinaryField64b, u64);
-impl_field_ops!(BinaryField128b, u128);
-
-
-// Poseidonb parameter structure
-
-struct PreparedParams<F: FieldConst> {
-    t: usize,
-    rf: usize,
-    rp: usize,
-    rc: Vec<Vec<F>>,
-    mds_full: Vec<Vec<F>>,
-    mds_partial: Vec<Vec<F>>,
+// Text is the default; `--output-format json` (or BENCH_OUTPUT_FORMAT=json) prints one JSON array
+// of `{instance, t, ns_per_op, perms_per_sec}` objects instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    Text,
+    Json,
 }
 
-fn prep_params<F: FieldConst, const T: usize, const R: usize>(
-    rc_raw: &[[F::Raw; R]; T],
-    mds_full_raw: &[[F::Raw; T]; T],
-    mds_partial_raw: &[[F::Raw; T]; T],
-    rf: usize,
-    rp: usize,
-) -> PreparedParams<F> {
-    PreparedParams {
-        t: T,
-        rf,
-        rp,
-        rc: rc_raw
-            .iter()
-            .map(|row| row.iter().copied().map(F::from_raw).collect())
-            .collect(),
-        mds_full: mds_full_raw
-            .iter()
-            .map(|row| row.iter().copied().map(F::from_raw).collect())
-            .collect(),
-        mds_partial: mds_partial_raw
+impl OutputFormat {
+    fn from_args() -> Self {
+        let args: Vec<String> = std::env::args().collect();
+        let flag = args
             .iter()
-            .map(|row| row.iter().copied().map(F::from_raw).collect())
-            .collect(),
+            .position(|a| a == "--output-format")
+            .and_then(|i| args.get(i + 1).cloned());
+        match flag.or_else(|| std::env::var("BENCH_OUTPUT_FORMAT").ok()).as_deref() {
+            Some("json") => OutputFormat::Json,
+            Some("text") | None => OutputFormat::Text,
+            Some(other) => panic!("unknown output format {other:?} (expected text or json)"),
+        }
     }
 }
 
-// 6 instance parameters (directly reusing tables from binius_poseidonb/hades)
-fn params_32_t16() -> PreparedParams<BinaryField32b> {
-    use params::params32_t16 as p;
-    prep_params(
-        &p::RC,
-        &p::MDS_FULL,
-        &p::MDS_PARTIAL,
-        p::R_F,
-        p::R_P,
-    )
-}
-fn params_32_t24() -> PreparedParams<BinaryField32b> {
-    use params::params32_t24 as p;
-    prep_params(
-        &p::RC,
-        &p::MDS_FULL,
-        &p::MDS_PARTIAL,
-        p::R_F,
-        p::R_P,
-    )
-}
-fn params_64_t8() -> PreparedParams<BinaryField64b> {
-    use params::params64_t8 as p;
-    prep_params(
-        &p::RC,
-        &p::MDS_FULL,
-        &p::MDS_PARTIAL,
-        p::R_F,
-        p::R_P,
-    )
-}
-fn params_64_t12() -> PreparedParams<BinaryField64b> {
-    use params::params64_t12 as p;
-    prep_params(
-        &p::RC,
-        &p::MDS_FULL,
-        &p::MDS_PARTIAL,
-        p::R_F,
-        p::R_P,
-    )
-}
-fn params_128_t4() -> PreparedParams<BinaryField128b> {
-    use params::params128_t4 as p;
-    prep_params(
-        &p::RC,
-        &p::MDS_FULL,
-        &p::MDS_PARTIAL,
-        p::R_F,
-        p::R_P,
-    )
-}
-fn params_128_t6() -> PreparedParams<BinaryField128b> {
-    use params::params128_t6 as p;
-    prep_params(
-        &p::RC,
-        &p::MDS_FULL,
-        &p::MDS_PARTIAL,
-        p::R_F,
-        p::R_P,
-    )
-}
-
+// Default iteration count for t <= 4, divided by t/4 for larger states but never below the floor.
+const BASE_ITERATIONS: usize = 100_000;
+const MIN_ITERATIONS: usize = 20_000;
 
-// Poseidonb Permutation
-
-struct Poseidonb<F: FieldConst> {
+struct BenchResult {
+    instance: String,
     t: usize,
-    rf: usize,
-    rp: usize,
-    rc: Vec<Vec<F>>,
-    mds_full: Vec<Vec<F>>,
-    mds_partial: Vec<Vec<F>>,
+    ns_per_op: f64,
+    perms_per_sec: f64,
 }
 
-impl<F: FieldConst> Poseidonb<F> {
-    fn new(params: PreparedParams<F>) -> Self {
-        Self {
-            t: params.t,
-            rf: params.rf,
-            rp: params.rp,
-            rc: params.rc,
-            mds_full: params.mds_full,
-            mds_partial: params.mds_partial,
-        }
-    }
-
-    fn permute(&self, state: &mut [F]) {
-        debug_assert_eq!(state.len(), self.t);
-        let half_f = self.rf / 2;
-        let mut round = 0usize;
-
-        // Minit = MDS_FULL
-        self.mul_mds_full(state);
-
-        // First half of full rounds
-        for _ in 0..half_f {
-            self.round_full(state, round);
-            round += 1;
-        }
-
-        // Partial rounds
-        for _ in 0..self.rp {
-            self.round_partial(state, round);
-            round += 1;
-        }
-
-        // Second half of full rounds
-        for _ in 0..half_f {
-            self.round_full(state, round);
-            round += 1;
-        }
-    }
-
-    #[inline(always)]
-    fn round_full(&self, state: &mut [F], r: usize) {
-        for i in 0..self.t {
-            state[i] = state[i].add(self.rc[i][r]);
-        }
-        for x in state.iter_mut() {
-            *x = x.pow_alpha();
-        }
-        self.mul_mds_full(state);
-    }
-
-    #[inline(always)]
-    fn round_partial(&self, state: &mut [F], r: usize) {
-        state[0] = state[0].add(self.rc[0][r]);
-        state[0] = state[0].pow_alpha();
-        self.mul_mds_partial(state);
-    }
-
-    fn mul_mds_full(&self, state: &mut [F]) {
-
-        // t=4: fast algorithm using the ((A B),(B,A)) structure of M4（12 times muls over GF instead of naive 16 times muls over GF）
-        // 3 block-mults in GF(2^n): P1=A*x1, P2=B*x2, P3=(A+B)*(x1+x2); y1=P1+P2, y2=P3+y1
-        if self.t == 4 {
-            let x0 = state[0];
-            let x1 = state[1];
-            let x2 = state[2];
-            let x3 = state[3];
-
-            let a00 = self.mds_full[0][0];
-            let a01 = self.mds_full[0][1];
-            let a10 = self.mds_full[1][0];
-            let a11 = self.mds_full[1][1];
-
-            let b00 = self.mds_full[0][2];
-            let b01 = self.mds_full[0][3];
-            let b10 = self.mds_full[1][2];
-            let b11 = self.mds_full[1][3];
-
-            let s0 = x0.add(x2);
-            let s1 = x1.add(x3);
-
-            let p1_0 = a00.mul(x0).add(a01.mul(x1));
-            let p1_1 = a10.mul(x0).add(a11.mul(x1));
-
-            let p2_0 = b00.mul(x2).add(b01.mul(x3));
-            let p2_1 = b10.mul(x2).add(b11.mul(x3));
-
-            let ab00 = a00.add(b00);
-            let ab01 = a01.add(b01);
-            let ab10 = a10.add(b10);
-            let ab11 = a11.add(b11);
-
-            let p3_0 = ab00.mul(s0).add(ab01.mul(s1));
-            let p3_1 = ab10.mul(s0).add(ab11.mul(s1));
-
-            let y0 = p1_0.add(p2_0);
-            let y1 = p1_1.add(p2_1);
-            let y2 = p3_0.add(y0);
-            let y3 = p3_1.add(y1);
-
-            state[0] = y0;
-            state[1] = y1;
-            state[2] = y2;
-            state[3] = y3;
-            return;
-        }
-
-        let mut res = vec![F::default(); self.t];
-        for (r, row) in self.mds_full.iter().enumerate() {
-            let mut acc = F::default();
-            for c in 0..self.t {
-                acc = acc.add(row[c].mul(state[c]));
-            }
-            res[r] = acc;
-        }
-        state.copy_from_slice(&res);
-    }
-
-    //$$y_i = (\mu_i - 1)x_i + \sum_{j=0}^{t-1} x_j$$
-    fn mul_mds_partial(&self, state: &mut [F]) {
-        let mut sum = F::default();
-        for &x in state.iter() {
-            sum = sum.add(x);
-        }
-        for i in 0..self.t {
-            let mu = self.mds_partial[i][i];
-            let mu_minus_one = mu.add(F::from_u8(1));
-            state[i] = sum.add(mu_minus_one.mul(state[i]));
-        }
-    }
+fn results_to_json(results: &[BenchResult]) -> String {
+    let rows: Vec<serde_json::Value> = results
+        .iter()
+        .map(|r| {
+            serde_json::json!({
+                "instance": r.instance,
+                "t": r.t,
+                "ns_per_op": r.ns_per_op,
+                "perms_per_sec": r.perms_per_sec,
+            })
+        })
+        .collect();
+    serde_json::to_string_pretty(&rows).expect("bench results serialize")
 }
 
-
-// Benchmark
-
-
-fn run_poseidon_bench<F: FieldConst>(title: &str, params: PreparedParams<F>) {
-    println!("--------------------------------------------------");
-    println!(
-        "{} | t={} | rf={} | rp={}",
-        title, params.t, params.rf, params.rp
-    );
+fn run_poseidon_bench<F: FieldConst>(
+    title: &str,
+    params: PreparedParams<F>,
+    format: OutputFormat,
+    iterations: Option<usize>,
+) -> BenchResult
+where
+    Standard: Distribution<F::Raw>,
+{
+    if format == OutputFormat::Text {
+        println!("--------------------------------------------------");
+        println!(
+            "{} | t={} | rf={} | rp={}",
+            title, params.t, params.rf, params.rp
+        );
+    }
 
     let poseidon = Poseidonb::new(params);
 
-    let mut state: Vec<F> = (0..poseidon.t)
-        .map(|i| F::from_u8((i as u8).wrapping_add(1)))
-        .collect();
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut state: Vec<F> = (0..poseidon.t).map(|_| F::from_raw(rng.gen())).collect();
 
-    let base_iter = 100000usize;
-    // Calculate a scaling factor based on state size 't'.
-    // Larger states (larger 't') are slower, so we reduce iterations to keep runtime reasonable.
+    // Larger states (larger 't') are slower, so we reduce iterations to keep runtime reasonable,
+    // but keep at least MIN_ITERATIONS for statistical significance.
     let scale = (poseidon.t / 4).max(1);
-    // Ensure we run at least 20,000 iterations for statistical significance.
-    let iterations = (base_iter / scale).max(20_000);
+    let iterations = iterations.unwrap_or((BASE_ITERATIONS / scale).max(MIN_ITERATIONS));
 
     let start = Instant::now();
     for _ in 0..iterations {
@@ -325,20 +93,58 @@ fn run_poseidon_bench<F: FieldConst>(title: &str, params: PreparedParams<F>) {
     }
     let elapsed = start.elapsed();
     let ns_per_op = elapsed.as_nanos() as f64 / iterations as f64;
-    let ops_per_sec = iterations as f64 / elapsed.as_secs_f64();
+    let perms_per_sec = iterations as f64 / elapsed.as_secs_f64();
+
+    if format == OutputFormat::Text {
+        println!("Time per perm: {ns_per_op:.2} ns");
+        println!("Throughput:    {perms_per_sec:.2} perms/sec");
+    }
 
-    println!("Time per perm: {:.2} ns", ns_per_op);
-    println!("Throughput:    {:.2} perms/sec", ops_per_sec);
-    
+    BenchResult {
+        instance: title.to_string(),
+        t: poseidon.t,
+        ns_per_op,
+        perms_per_sec,
+    }
+}
+
+fn run_all(format: OutputFormat, iterations: Option<usize>) -> Vec<BenchResult> {
+    vec![
+        run_poseidon_bench("GF(2^32) t=16 (Poseidonb)", params_32_t16(), format, iterations),
+        run_poseidon_bench("GF(2^32) t=24 (Poseidonb)", params_32_t24(), format, iterations),
+        run_poseidon_bench("GF(2^64) t=8 (Poseidonb)", params_64_t8(), format, iterations),
+        run_poseidon_bench("GF(2^64) t=12 (Poseidonb)", params_64_t12(), format, iterations),
+        run_poseidon_bench("GF(2^128) t=4 (Poseidonb)", params_128_t4(), format, iterations),
+        run_poseidon_bench("GF(2^128) t=6 (Poseidonb)", params_128_t6(), format, iterations),
+    ]
 }
 
 fn main() {
-    println!("=== Poseidonb Benchmark ===");
+    let format = OutputFormat::from_args();
+    if format == OutputFormat::Text {
+        println!("=== Poseidonb Benchmark ===");
+    }
 
-    run_poseidon_bench("GF(2^32) t=16 (Poseidonb)", params_32_t16());
-    run_poseidon_bench("GF(2^32) t=24 (Poseidonb)", params_32_t24());
-    run_poseidon_bench("GF(2^64) t=8 (Poseidonb)", params_64_t8());
-    run_poseidon_bench("GF(2^64) t=12 (Poseidonb)", params_64_t12());
-    run_poseidon_bench("GF(2^128) t=4 (Poseidonb)", params_128_t4());
-    run_poseidon_bench("GF(2^128) t=6 (Poseidonb)", params_128_t6());
+    let results = run_all(format, None);
+    if format == OutputFormat::Json {
+        println!("{}", results_to_json(&results));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_output_lists_all_instances() {
+        let json = results_to_json(&run_all(OutputFormat::Json, Some(1)));
+        let rows: Vec<serde_json::Value> = serde_json::from_str(&json).unwrap();
+        assert_eq!(rows.len(), 6);
+        for (row, t) in rows.iter().zip([16, 24, 8, 12, 4, 6]) {
+            assert!(row["instance"].as_str().unwrap().contains(&format!("t={t}")));
+            assert_eq!(row["t"], t);
+            assert!(row["ns_per_op"].is_number());
+            assert!(row["perms_per_sec"].is_number());
+        }
+    }
 }